@@ -13,6 +13,14 @@ impl Framebuffer {
         Framebuffer { image: img, background_color: bg, current_color: Color::WHITE }
     }
 
+    pub fn width(&self) -> i32 {
+        self.image.width()
+    }
+
+    pub fn height(&self) -> i32 {
+        self.image.height()
+    }
+
     pub fn set_background_color(&mut self, color: Color) {
         self.background_color = color;
     }
@@ -27,27 +35,38 @@ impl Framebuffer {
         self.current_color = color;
     }
 
-    pub fn point(&mut self, pos: Vector2) {
-        let x = pos.x as i32;
-        let h = self.image.height();
-        let y_src = pos.y as i32;
-        let y = h - 1 - y_src;
-        self.image.draw_pixel(x, y, self.current_color);
+    // Rectángulo relleno con la esquina en `(x, y)`; el eje y va de abajo hacia arriba
+    pub fn rect(&mut self, x: i32, y: i32, width: i32, height: i32) {
+        let y = self.image.height() - y - height;
+        self.image.draw_rectangle(x, y, width, height, self.current_color);
+    }
+
+    // Convierte una posición de la ventana (el mouse) a las coordenadas de `rect`
+    pub fn screen_to_framebuffer(&self, pos: Vector2) -> Vector2 {
+        Vector2 {
+            x: pos.x,
+            y: (self.image.height() - 1) as f32 - pos.y,
+        }
     }
 
     pub fn render_to_file(&self, file: &str) {
         self.image.export_image(file);
     }
 
-    pub fn swap_buffers(
+    // Muestra la imagen y encima lo que dibuje `draw_overlay` (el HUD)
+    pub fn swap_buffers<F>(
         &self,
         window: &mut RaylibHandle,
-        raylib_thread: &RaylibThread
-    ){
+        raylib_thread: &RaylibThread,
+        draw_overlay: F,
+    ) where
+        F: FnOnce(&mut RaylibDrawHandle),
+    {
         if let Ok(texture) = window.load_texture_from_image(raylib_thread, &self.image){
             let mut renderer = window.begin_drawing(raylib_thread);
 
             renderer.draw_texture(&texture, 0, 0, Color::WHITE);
+            draw_overlay(&mut renderer);
         }
     }
 
@@ -1,10 +1,13 @@
 use raylib::prelude::*;
 
+use crate::view::View;
+
 pub struct GameOfLife {
     width: usize,
     height: usize,
     current_generation: Vec<Vec<bool>>,
     next_generation: Vec<Vec<bool>>,
+    generation: u64,
 }
 
 impl GameOfLife {
@@ -14,10 +17,53 @@ impl GameOfLife {
             height,
             current_generation: vec![vec![false; width]; height],
             next_generation: vec![vec![false; width]; height],
+            generation: 0,
         }
     }
 
-    
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // Generaciones calculadas desde el inicio
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    // Células vivas en la generación actual
+    pub fn population(&self) -> usize {
+        self.current_generation
+            .iter()
+            .map(|row| row.iter().filter(|&&alive| alive).count())
+            .sum()
+    }
+
+    pub fn is_alive(&self, x: i32, y: i32) -> bool {
+        x >= 0
+            && y >= 0
+            && (x as usize) < self.width
+            && (y as usize) < self.height
+            && self.current_generation[y as usize][x as usize]
+    }
+
+    // Revive o mata una célula; fuera del tablero no hace nada
+    pub fn set(&mut self, x: i32, y: i32, alive: bool) {
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            self.current_generation[y as usize][x as usize] = alive;
+        }
+    }
+
+    // Mata todas las células y vuelve a la generación 0
+    pub fn clear(&mut self) {
+        for row in self.current_generation.iter_mut() {
+            row.fill(false);
+        }
+        self.generation = 0;
+    }
     
     pub fn set_alive(&mut self, x: usize, y: usize) {
         if x < self.width && y < self.height {
@@ -91,28 +137,35 @@ impl GameOfLife {
         
         // Intercambiar generaciones
         std::mem::swap(&mut self.current_generation, &mut self.next_generation);
+        self.generation += 1;
     }
     
-    pub fn render(&self, framebuffer: &mut crate::framebuffer::Framebuffer, scale: i32) {
-        // Calcular offset para centrar el juego en la pantalla
-        let game_pixel_width = self.width as i32 * scale;
-        let game_pixel_height = self.height as i32 * scale;
-        
-        // Asumir que el framebuffer es 800x800
-        let offset_x = (800 - game_pixel_width) / 2;
-        let offset_y = (800 - game_pixel_height) / 2;
-        
-        for y in 0..self.height {
-            for x in 0..self.width {
+    pub fn render(&self, framebuffer: &mut crate::framebuffer::Framebuffer, view: &View) {
+        // Borde del tablero, para ver dónde termina al alejar o mover la vista
+        let (left, top) = view.cell_origin(0, 0);
+        let (right, bottom) = view.cell_origin(self.width, self.height);
+        framebuffer.set_current_color(Color::DARKGRAY);
+        framebuffer.rect(left - 1, top - 1, right - left + 2, 1);
+        framebuffer.rect(left - 1, bottom, right - left + 2, 1);
+        framebuffer.rect(left - 1, top, 1, bottom - top);
+        framebuffer.rect(right, top, 1, bottom - top);
+
+        // Sólo las filas y columnas que caen dentro de la ventana
+        let (first_x, first_y) = view.cell_at(Vector2 { x: 0.0, y: 0.0 });
+        let (last_x, last_y) = view.cell_at(Vector2 {
+            x: framebuffer.width() as f32,
+            y: framebuffer.height() as f32,
+        });
+        let columns = first_x.clamp(0, self.width as i32) as usize..(last_x + 1).clamp(0, self.width as i32) as usize;
+        let rows = first_y.clamp(0, self.height as i32) as usize..(last_y + 1).clamp(0, self.height as i32) as usize;
+
+        framebuffer.set_current_color(Color::WHITE);
+        for y in rows {
+            for x in columns.clone() {
                 if self.current_generation[y][x] {
-                    for dy in 0..scale {
-                        for dx in 0..scale {
-                            framebuffer.point(Vector2 {
-                                x: (offset_x + x as i32 * scale + dx) as f32,
-                                y: (offset_y + y as i32 * scale + dy) as f32,
-                            });
-                        }
-                    }
+                    let (cell_x, cell_y) = view.cell_origin(x, y);
+                    let (next_x, next_y) = view.cell_origin(x + 1, y + 1);
+                    framebuffer.rect(cell_x, cell_y, next_x - cell_x, next_y - cell_y);
                 }
             }
         }
@@ -1,11 +1,13 @@
 mod framebuffer;
 mod game_of_life;
 mod patterns;
+mod view;
 
 use std::{thread, time::Duration};
 use raylib::prelude::*;
 use framebuffer::Framebuffer;
 use game_of_life::GameOfLife;
+use view::View;
 use rand::{rng, Rng}; 
 
 use patterns::pulsar::create_multiple_pulsars;
@@ -13,28 +15,28 @@ use patterns::blinker::create_multiple_blinkers;
 use patterns::glider_creator::{Direction, create_multiple_creators_gliders_with_directions};
 use patterns::heavy_weight_spaceship::create_multiple_heavy_weight_spaceships;
 use patterns::toad::create_multiple_toads;
+use patterns::pattern_file::Pattern;
 
+// Velocidades que se eligen con + y -, en generaciones por segundo
+const SPEEDS: [f32; 9] = [1.0, 2.0, 5.0, 10.0, 20.0, 30.0, 60.0, 120.0, 240.0];
+// Generaciones que se pueden recuperar en un frame lento
+const MAX_STEPS_PER_FRAME: u32 = 8;
+// Cuánto acerca o aleja cada paso de la rueda del mouse
+const ZOOM_STEP: f32 = 1.15;
 
-fn main() {
-    let window_width = 800;
-    let window_height = 800;
-    
-    let game_width = 100;
-    let game_height = 100;
-    let cell_size = 6;
-
-    let (mut window, raylib_thread) = raylib::init()
-        .size(window_width, window_height)
-        .title("Conway's Game of Life - Esteban Edition")
-        .log_level(TraceLogLevel::LOG_WARNING)
-        .build();
-
-    let mut framebuffer: Framebuffer = Framebuffer::new(window_width as i32, window_height as i32);
-    framebuffer.set_background_color(Color::BLACK);
-    
-    let mut game = GameOfLife::new(game_width, game_height);
-    
+// Marca las células en línea recta entre dos celdas, para que arrastrar rápido no deje huecos
+fn paint_line(game: &mut GameOfLife, from: (i32, i32), to: (i32, i32), alive: bool) {
+    let steps = (to.0 - from.0).abs().max((to.1 - from.1).abs()).max(1);
+    for i in 0..=steps {
+        let t = i as f32 / steps as f32;
+        let x = from.0 as f32 + (to.0 - from.0) as f32 * t;
+        let y = from.1 as f32 + (to.1 - from.1) as f32 * t;
+        game.set(x.round() as i32, y.round() as i32, alive);
+    }
+}
 
+// Patrones de la demostración inicial, pensados para un tablero de 100x100
+fn create_demo(game: &mut GameOfLife) {
     let positions_pulsars = [
          (25, 25),  
          (75, 25),  
@@ -43,21 +45,21 @@ fn main() {
          (50, 50),  
      ];
 
-     create_multiple_pulsars(&mut game, &positions_pulsars);
+     create_multiple_pulsars(game, &positions_pulsars);
 
 
      let positions_blinkers = [ 
          (50, 50),  
      ];
 
-    create_multiple_blinkers(&mut game, &positions_blinkers);
+    create_multiple_blinkers(game, &positions_blinkers);
 
 
     let glider_configs = [
         (5, 97, Direction::Up),     
     ];
     
-    create_multiple_creators_gliders_with_directions(&mut game, &glider_configs);
+    create_multiple_creators_gliders_with_directions(game, &glider_configs);
 
 
 
@@ -72,7 +74,7 @@ fn main() {
         (90,2),
      ];
 
-    create_multiple_heavy_weight_spaceships(&mut game, &positions_heavy_weight_spaceships);
+    create_multiple_heavy_weight_spaceships(game, &positions_heavy_weight_spaceships);
 
 
     let mut rng = rng();
@@ -85,27 +87,214 @@ fn main() {
         })
         .collect();
 
-    create_multiple_toads(&mut game, &toad_config);
+    create_multiple_toads(game, &toad_config);
+}
+
+// Lee un patrón y avisa por consola si no se pudo, por ejemplo si es de otra regla
+fn load_pattern(path: &str) -> Option<Pattern> {
+    match Pattern::load(path) {
+        Ok(pattern) => {
+            println!("Patrón cargado: {}", pattern.title());
+            Some(pattern)
+        }
+        Err(err) => {
+            println!("No se pudo cargar {}: {}", path, err);
+            None
+        }
+    }
+}
+
+fn main() {
+    let window_width = 800;
+    let window_height = 800;
+    
+    // Un patrón cargado que no entra en 100x100 agranda el tablero, con margen para que crezca
+    let loaded = std::env::args().nth(1).and_then(|path| load_pattern(&path));
+    let game_width = loaded.as_ref().map_or(100, |p| (p.width + 40).max(100));
+    let game_height = loaded.as_ref().map_or(100, |p| (p.height + 40).max(100));
+
+    let (mut window, raylib_thread) = raylib::init()
+        .size(window_width, window_height)
+        .title("Conway's Game of Life - Esteban Edition")
+        .resizable()
+        .log_level(TraceLogLevel::LOG_WARNING)
+        .build();
+    window.set_target_fps(60);
+
+    let mut framebuffer: Framebuffer = Framebuffer::new(window_width as i32, window_height as i32);
+    framebuffer.set_background_color(Color::BLACK);
+    
+    let mut game = GameOfLife::new(game_width, game_height);
+    
+
+    // `cargo run -- archivo.rle` (o `.cells`) empieza con ese patrón en el centro en
+    // lugar de la demostración
+    match loaded {
+        Some(pattern) => {
+            let (center_x, center_y) = (game_width as i32 / 2, game_height as i32 / 2);
+            pattern.place(&mut game, center_x, center_y, Direction::Right);
+        }
+        None => create_demo(&mut game),
+    }
+
 
     //esperar que cargue el primer frame
     thread::sleep(Duration::from_millis(1000)); 
 
+    let mut view = View::fit(window_width, window_height, game_width, game_height);
+    let mut paused = false;
+    let mut speed = 3; // 10 generaciones por segundo, lo que daban los 100 ms de antes
+    let mut accumulator = 0.0;
+    // Al arrastrar con el clic izquierdo: el estado que se pinta y la última célula tocada
+    let mut painting: Option<(bool, (i32, i32))> = None;
+    // Patrón soltado sobre la ventana que sigue al mouse hasta colocarlo, con su dirección
+    let mut held: Option<(Pattern, Direction)> = None;
 
     while !window.window_should_close() {
+        // El framebuffer sigue el tamaño de la ventana
+        if window.is_window_resized() {
+            framebuffer = Framebuffer::new(window.get_screen_width(), window.get_screen_height());
+            framebuffer.set_background_color(Color::BLACK);
+        }
+
         if window.is_key_pressed(KeyboardKey::KEY_S) {  
             framebuffer.render_to_file("out.bmp");
             println!("Renderizado guardado en out.bmp");
         }
 
+        // E guarda el tablero como RLE
+        if window.is_key_pressed(KeyboardKey::KEY_E) {
+            let board = Pattern::from_game(&game, "Tablero");
+            match board.save_rle("tablero.rle") {
+                Ok(()) => println!("Tablero guardado en tablero.rle"),
+                Err(err) => println!("No se pudo guardar tablero.rle: {}", err),
+            }
+        }
+
+        // Un archivo .rle o .cells soltado sobre la ventana queda listo para colocar;
+        // T cambia la dirección y X lo descarta
+        if window.is_file_dropped() {
+            let dropped = window.load_dropped_files();
+            if let Some(pattern) = dropped.paths().first().and_then(|path| load_pattern(path)) {
+                held = Some((pattern, Direction::Right));
+            }
+        }
+        if let Some((_, direction)) = held.as_mut()
+            && window.is_key_pressed(KeyboardKey::KEY_T)
+        {
+            *direction = direction.next();
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_X) {
+            held = None;
+        }
+
+        // Pausa, paso a paso, velocidad, limpiar y encuadrar
+        if window.is_key_pressed(KeyboardKey::KEY_SPACE) {
+            paused = !paused;
+            accumulator = 0.0;
+        }
+        if paused && window.is_key_pressed(KeyboardKey::KEY_N) {
+            game.update();
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_EQUAL) || window.is_key_pressed(KeyboardKey::KEY_KP_ADD) {
+            speed = (speed + 1).min(SPEEDS.len() - 1);
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_MINUS) || window.is_key_pressed(KeyboardKey::KEY_KP_SUBTRACT) {
+            speed = speed.saturating_sub(1);
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_C) {
+            game.clear();
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_R) {
+            view = View::fit(framebuffer.width(), framebuffer.height(), game.width(), game.height());
+        }
+
+        // Rueda para el zoom y clic derecho (o central) para mover la vista
+        let mouse = framebuffer.screen_to_framebuffer(window.get_mouse_position());
+        let wheel = window.get_mouse_wheel_move();
+        if wheel != 0.0 {
+            view.zoom_at(mouse, ZOOM_STEP.powf(wheel));
+        }
+        if window.is_mouse_button_down(MouseButton::MOUSE_BUTTON_RIGHT)
+            || window.is_mouse_button_down(MouseButton::MOUSE_BUTTON_MIDDLE)
+        {
+            let delta = window.get_mouse_delta();
+            // El eje y del framebuffer está invertido respecto a la ventana
+            view.pan(Vector2 { x: delta.x, y: -delta.y });
+        }
+
+        // Clic izquierdo cambia la célula; arrastrando se pinta con el estado nuevo de la primera
+        let cell = view.cell_at(mouse);
+        if held.is_some() {
+            if window.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT)
+                && let Some((pattern, direction)) = held.take()
+            {
+                pattern.place(&mut game, cell.0, cell.1, direction);
+            }
+        } else if window.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+            let alive = !game.is_alive(cell.0, cell.1);
+            game.set(cell.0, cell.1, alive);
+            painting = Some((alive, cell));
+        } else if let Some((alive, last)) = painting {
+            if window.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+                paint_line(&mut game, last, cell, alive);
+                painting = Some((alive, cell));
+            } else {
+                painting = None;
+            }
+        }
+
+        // Avanza las generaciones que tocan según la velocidad, sin depender de los FPS
+        if !paused {
+            let interval = 1.0 / SPEEDS[speed];
+            accumulator = (accumulator + window.get_frame_time()).min(interval * MAX_STEPS_PER_FRAME as f32);
+            while accumulator >= interval {
+                accumulator -= interval;
+                game.update();
+            }
+        }
+
         framebuffer.clear();
-        framebuffer.set_current_color(Color::WHITE);
-        
-        game.render(&mut framebuffer, cell_size);
-        
-        game.update();
+        game.render(&mut framebuffer, &view);
 
-        framebuffer.swap_buffers(&mut window, &raylib_thread);
+        // Vista previa del patrón que se va a colocar
+        if let Some((pattern, direction)) = &held {
+            framebuffer.set_current_color(Color::LIME);
+            for (x, y) in pattern.placed_cells(cell.0, cell.1, *direction) {
+                let left = (view.offset.x + x as f32 * view.zoom).floor() as i32;
+                let top = (view.offset.y + y as f32 * view.zoom).floor() as i32;
+                framebuffer.rect(left, top, view.zoom.ceil() as i32, view.zoom.ceil() as i32);
+            }
+        }
 
-        thread::sleep(Duration::from_millis(100)); // Más lento para ver las 3 fases
+        let estado = format!(
+            "Generación: {}   Población: {}   Velocidad: {} gen/s{}",
+            game.generation(),
+            game.population(),
+            SPEEDS[speed],
+            if paused { "   PAUSA" } else { "" }
+        );
+        let colocando = held.as_ref().map(|(pattern, direction)| {
+            format!(
+                "{}   Dirección: {}   T: cambiar   Clic: colocar   X: descartar",
+                pattern.title(),
+                direction.name()
+            )
+        });
+        let alto = framebuffer.height();
+        framebuffer.swap_buffers(&mut window, &raylib_thread, |d| {
+            d.draw_rectangle(0, 0, d.get_screen_width(), 34, Color::new(0, 0, 0, 180));
+            d.draw_text(&estado, 10, 8, 20, Color::GOLD);
+            if let Some(texto) = &colocando {
+                d.draw_text(texto, 10, 40, 18, Color::LIME);
+            }
+            d.draw_text(
+                "ESPACIO: pausa   N: paso   +/-: velocidad   Rueda: zoom   Clic der.: mover   Clic izq.: editar   C: limpiar   R: encuadrar   E: guardar RLE",
+                10,
+                alto - 24,
+                16,
+                Color::LIGHTGRAY,
+            );
+        });
     }
 }
\ No newline at end of file
@@ -9,6 +9,37 @@ pub enum Direction {
     Up,     // Rotado 270° (↑)
 }
 
+impl Direction {
+    // Transforma una posición relativa al centro del patrón según la dirección
+    pub fn apply(self, dx: i32, dy: i32) -> (i32, i32) {
+        match self {
+            Direction::Right => (dx, dy),
+            Direction::Left => (-dx, dy),
+            Direction::Down => (dy, dx),
+            Direction::Up => (-dy, -dx),
+        }
+    }
+
+    // La siguiente dirección, para ir cambiándola con una tecla
+    pub fn next(self) -> Self {
+        match self {
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+            Direction::Up => Direction::Right,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Direction::Right => "derecha",
+            Direction::Left => "izquierda",
+            Direction::Down => "abajo",
+            Direction::Up => "arriba",
+        }
+    }
+}
+
 pub fn create_creators_glider_with_direction(
     game: &mut GameOfLife, 
     center_x: usize, 
@@ -37,23 +68,10 @@ pub fn create_creators_glider_with_direction(
         (34, 2), (35, 2), (34, 3), (35, 3),
     ];
     
-    // Aplicar transformación según la dirección
+    // Aplicar transformación según la dirección; lo que cae fuera del tablero se descarta
     for (dx, dy) in positions {
-        let (final_x, final_y) = match direction {
-            Direction::Right => (center_x as i32 + dx, center_y as i32 + dy),
-            Direction::Left => (center_x as i32 - dx, center_y as i32 + dy),
-            Direction::Down => (center_x as i32 + dy, center_y as i32 + dx),
-            Direction::Up => (center_x as i32 - dy, center_y as i32 - dx),
-        };
-        
-        // Verificar límites antes de colocar
-        if final_x >= 0 && final_y >= 0 {
-            let x = final_x as usize;
-            let y = final_y as usize;
-            if x < 100 && y < 100 { // Límites del juego
-                game.set_alive(x, y);
-            }
-        }
+        let (offset_x, offset_y) = direction.apply(dx, dy);
+        game.set(center_x as i32 + offset_x, center_y as i32 + offset_y, true);
     }
 }
 
@@ -3,4 +3,5 @@ pub mod blinker;
 pub mod block;
 pub mod glider_creator;
 pub mod heavy_weight_spaceship;
-pub mod toad;
\ No newline at end of file
+pub mod toad;
+pub mod pattern_file;
\ No newline at end of file
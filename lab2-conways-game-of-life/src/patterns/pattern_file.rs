@@ -0,0 +1,413 @@
+use std::fs;
+
+use crate::game_of_life::GameOfLife;
+use super::glider_creator::Direction;
+
+// Regla de Conway en la notación de los archivos RLE
+const CONWAY_RULE: &str = "B3/S23";
+// Largo máximo de las líneas de datos de un RLE, como recomienda el formato
+const RLE_LINE_WIDTH: usize = 70;
+
+// Patrón leído de un archivo RLE (.rle) o de texto plano (.cells), con los datos de su cabecera
+pub struct Pattern {
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub comments: Vec<String>,
+    // Regla del archivo RLE (`rule =` o `#r`); sin ella se asume la de Conway
+    pub rule: Option<String>,
+    pub width: usize,
+    pub height: usize,
+    // Células vivas, relativas a la esquina superior izquierda
+    pub cells: Vec<(i32, i32)>,
+}
+
+impl Pattern {
+    fn empty() -> Self {
+        Self {
+            name: None,
+            author: None,
+            comments: Vec::new(),
+            rule: None,
+            width: 0,
+            height: 0,
+            cells: Vec::new(),
+        }
+    }
+
+    // Lee un archivo según su extensión: `.cells` es texto plano y cualquier otra, RLE
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        if path.to_lowercase().ends_with(".cells") {
+            Self::parse_plaintext(&text)
+        } else {
+            Self::parse_rle(&text)
+        }
+    }
+
+    // Formato RLE: líneas `#N` (nombre), `#O` (autor), `#C` (comentario) y `#r` (regla), la
+    // cabecera `x = ancho, y = alto, rule = ...` y las corridas `3o2b$`, terminadas en `!`.
+    // Un patrón de otra regla que la de Conway es un error, porque el juego no la simula
+    pub fn parse_rle(text: &str) -> Result<Self, String> {
+        let mut pattern = Self::empty();
+        let mut header_found = false;
+        let (mut x, mut y) = (0, 0);
+        let mut count = String::new();
+
+        'lines: for (index, line) in text.lines().enumerate() {
+            let number = index + 1;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(rest) = line.strip_prefix('#') {
+                let mut chars = rest.chars();
+                let tag = chars.next().unwrap_or(' ');
+                let value = chars.as_str().trim().to_string();
+                match tag {
+                    'N' => pattern.name = Some(value),
+                    'O' => pattern.author = Some(value),
+                    'C' | 'c' => pattern.comments.push(value),
+                    'r' => pattern.rule = Some(value),
+                    _ => {}
+                }
+                continue;
+            }
+
+            if !header_found {
+                for field in line.split(',') {
+                    let Some((key, value)) = field.split_once('=') else {
+                        return Err(format!("línea {}: se esperaba la cabecera `x = ..., y = ...`", number));
+                    };
+                    let value = value.trim();
+                    match key.trim() {
+                        "x" => pattern.width = value.parse().map_err(|_| format!("línea {}: ancho inválido", number))?,
+                        "y" => pattern.height = value.parse().map_err(|_| format!("línea {}: alto inválido", number))?,
+                        "rule" => pattern.rule = Some(value.to_string()),
+                        _ => {}
+                    }
+                }
+                header_found = true;
+                continue;
+            }
+
+            for c in line.chars() {
+                if c.is_ascii_digit() {
+                    count.push(c);
+                    continue;
+                }
+                if c.is_whitespace() {
+                    continue;
+                }
+                let run: i32 = if count.is_empty() { 1 } else {
+                    count.parse().map_err(|_| format!("línea {}: corrida demasiado larga", number))?
+                };
+                count.clear();
+                match c {
+                    'b' | '.' => x += run,
+                    '$' => {
+                        y += run;
+                        x = 0;
+                    }
+                    '!' => break 'lines,
+                    // En los archivos de dos estados cualquier otra letra es una célula viva
+                    c if c.is_ascii_alphabetic() => {
+                        for _ in 0..run {
+                            pattern.cells.push((x, y));
+                            x += 1;
+                        }
+                    }
+                    c => return Err(format!("línea {}: carácter inesperado '{}'", number, c)),
+                }
+            }
+        }
+
+        if !header_found {
+            return Err("falta la cabecera `x = ..., y = ...`".to_string());
+        }
+        if !pattern.is_conway() {
+            return Err(format!(
+                "usa la regla {}; el juego sólo simula la de Conway ({})",
+                pattern.rule.as_deref().unwrap_or(""),
+                CONWAY_RULE
+            ));
+        }
+        pattern.fit_bounds();
+        Ok(pattern)
+    }
+
+    // Formato de texto plano: líneas `!Name:`, `!Author:` y otros comentarios con `!`, y
+    // una fila por línea con `.` para las muertas y `O` para las vivas
+    pub fn parse_plaintext(text: &str) -> Result<Self, String> {
+        let mut pattern = Self::empty();
+        let mut y = 0;
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim_end();
+            if let Some(rest) = line.strip_prefix('!') {
+                let rest = rest.trim();
+                if let Some(name) = rest.strip_prefix("Name:") {
+                    pattern.name = Some(name.trim().to_string());
+                } else if let Some(author) = rest.strip_prefix("Author:") {
+                    pattern.author = Some(author.trim().to_string());
+                } else if !rest.is_empty() {
+                    pattern.comments.push(rest.to_string());
+                }
+                continue;
+            }
+
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    '.' => {}
+                    'O' | '*' => pattern.cells.push((x as i32, y)),
+                    c => return Err(format!("línea {}: carácter inesperado '{}'", index + 1, c)),
+                }
+            }
+            y += 1;
+        }
+
+        pattern.fit_bounds();
+        Ok(pattern)
+    }
+
+    // Agranda el ancho y el alto si hay células fuera de los de la cabecera
+    fn fit_bounds(&mut self) {
+        for &(x, y) in &self.cells {
+            self.width = self.width.max(x as usize + 1);
+            self.height = self.height.max(y as usize + 1);
+        }
+    }
+
+    // Si el patrón es para las reglas de Conway, las únicas que simula el juego
+    fn is_conway(&self) -> bool {
+        self.rule.as_deref().is_none_or(|rule| {
+            rule.eq_ignore_ascii_case(CONWAY_RULE) || rule == "23/3"
+        })
+    }
+
+    // Las células vivas del tablero, recortadas a lo que ocupan
+    pub fn from_game(game: &GameOfLife, name: &str) -> Self {
+        let mut cells = Vec::new();
+        for y in 0..game.height() as i32 {
+            for x in 0..game.width() as i32 {
+                if game.is_alive(x, y) {
+                    cells.push((x, y));
+                }
+            }
+        }
+        let left = cells.iter().map(|&(x, _)| x).min().unwrap_or(0);
+        let top = cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+
+        let mut pattern = Self::empty();
+        pattern.name = Some(name.to_string());
+        pattern.comments.push(format!("Generación {} del Juego de la Vida", game.generation()));
+        pattern.rule = Some(CONWAY_RULE.to_string());
+        pattern.cells = cells.into_iter().map(|(x, y)| (x - left, y - top)).collect();
+        pattern.fit_bounds();
+        pattern
+    }
+
+    // Escribe el patrón en formato RLE, con las líneas de datos de a lo sumo 70 caracteres
+    pub fn to_rle(&self) -> String {
+        let mut text = String::new();
+        if let Some(name) = &self.name {
+            text.push_str(&format!("#N {}\n", name));
+        }
+        if let Some(author) = &self.author {
+            text.push_str(&format!("#O {}\n", author));
+        }
+        for comment in &self.comments {
+            text.push_str(&format!("#C {}\n", comment));
+        }
+        text.push_str(&format!(
+            "x = {}, y = {}, rule = {}\n",
+            self.width,
+            self.height,
+            self.rule.as_deref().unwrap_or(CONWAY_RULE)
+        ));
+
+        // Corridas de cada fila, sin las muertas del final; las filas vacías se juntan en un `$`
+        let mut rows = vec![Vec::new(); self.height];
+        for &(x, y) in &self.cells {
+            rows[y as usize].push(x);
+        }
+        let mut runs: Vec<(usize, char)> = Vec::new();
+        let mut push = |count: usize, tag: char| {
+            if count == 0 {
+                return;
+            }
+            match runs.last_mut() {
+                Some(last) if last.1 == tag => last.0 += count,
+                _ => runs.push((count, tag)),
+            }
+        };
+        for (y, row) in rows.iter_mut().enumerate() {
+            if y > 0 {
+                push(1, '$');
+            }
+            row.sort_unstable();
+            let mut next = 0;
+            for &x in row.iter() {
+                push((x - next) as usize, 'b');
+                push(1, 'o');
+                next = x + 1;
+            }
+        }
+        push(1, '!');
+
+        let mut line = String::new();
+        for (count, tag) in runs {
+            let token = if count == 1 { tag.to_string() } else { format!("{}{}", count, tag) };
+            if line.len() + token.len() > RLE_LINE_WIDTH {
+                text.push_str(&line);
+                text.push('\n');
+                line.clear();
+            }
+            line.push_str(&token);
+        }
+        text.push_str(&line);
+        text.push('\n');
+        text
+    }
+
+    pub fn save_rle(&self, path: &str) -> std::io::Result<()> {
+        fs::write(path, self.to_rle())
+    }
+
+    // Posiciones en el tablero con el centro del patrón en `(center_x, center_y)`, girado o
+    // espejado igual que el cañón de gliders
+    pub fn placed_cells(&self, center_x: i32, center_y: i32, direction: Direction) -> Vec<(i32, i32)> {
+        let (half_w, half_h) = (self.width as i32 / 2, self.height as i32 / 2);
+        self.cells
+            .iter()
+            .map(|&(x, y)| {
+                let (dx, dy) = direction.apply(x - half_w, y - half_h);
+                (center_x + dx, center_y + dy)
+            })
+            .collect()
+    }
+
+    // Revive las células del patrón; lo que cae fuera del tablero se descarta
+    pub fn place(&self, game: &mut GameOfLife, center_x: i32, center_y: i32, direction: Direction) {
+        for (x, y) in self.placed_cells(center_x, center_y, direction) {
+            game.set(x, y, true);
+        }
+    }
+
+    pub fn title(&self) -> String {
+        let name = self.name.as_deref().unwrap_or("Sin nombre");
+        match &self.author {
+            Some(author) => format!("{} ({}) {}x{}", name, author, self.width, self.height),
+            None => format!("{} {}x{}", name, self.width, self.height),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLIDER_RLE: &str = "#N Glider\n#O Richard K. Guy\n#C La nave más chica\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n";
+    const GLIDER_CELLS: &str = "!Name: Glider\n!Author: Richard K. Guy\n!La nave más chica\n.O.\n..O\nOOO\n";
+
+    fn sorted(mut cells: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+        cells.sort_unstable();
+        cells
+    }
+
+    fn glider() -> Vec<(i32, i32)> {
+        sorted(vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)])
+    }
+
+    #[test]
+    fn reads_rle_header_and_cells() {
+        let pattern = Pattern::parse_rle(GLIDER_RLE).unwrap();
+        assert_eq!(pattern.name.as_deref(), Some("Glider"));
+        assert_eq!(pattern.author.as_deref(), Some("Richard K. Guy"));
+        assert_eq!(pattern.comments, vec!["La nave más chica".to_string()]);
+        assert_eq!((pattern.width, pattern.height), (3, 3));
+        assert_eq!(sorted(pattern.cells), glider());
+    }
+
+    #[test]
+    fn reads_plaintext_like_rle() {
+        let pattern = Pattern::parse_plaintext(GLIDER_CELLS).unwrap();
+        assert_eq!(pattern.name.as_deref(), Some("Glider"));
+        assert_eq!(pattern.author.as_deref(), Some("Richard K. Guy"));
+        assert_eq!(pattern.comments, vec!["La nave más chica".to_string()]);
+        assert_eq!((pattern.width, pattern.height), (3, 3));
+        assert_eq!(sorted(pattern.cells), glider());
+    }
+
+    #[test]
+    fn rejects_other_rules() {
+        assert!(Pattern::parse_rle("x = 1, y = 1, rule = B36/S23\no!").is_err());
+        assert!(Pattern::parse_rle("#r 23/36\nx = 1, y = 1\no!").is_err());
+        // La regla de Conway en sus dos notaciones, o sin regla
+        assert!(Pattern::parse_rle("x = 1, y = 1, rule = b3/s23\no!").is_ok());
+        assert!(Pattern::parse_rle("#r 23/3\nx = 1, y = 1\no!").is_ok());
+        assert!(Pattern::parse_rle("x = 1, y = 1\no!").is_ok());
+    }
+
+    #[test]
+    fn writes_rle_and_reads_it_back() {
+        let pattern = Pattern::parse_rle(GLIDER_RLE).unwrap();
+        assert_eq!(pattern.to_rle(), GLIDER_RLE);
+
+        let plain = Pattern::parse_plaintext(GLIDER_CELLS).unwrap();
+        let again = Pattern::parse_rle(&plain.to_rle()).unwrap();
+        assert_eq!(again.name, plain.name);
+        assert_eq!(again.author, plain.author);
+        assert_eq!(again.comments, plain.comments);
+        assert_eq!((again.width, again.height), (plain.width, plain.height));
+        assert_eq!(sorted(again.cells), sorted(plain.cells));
+    }
+
+    #[test]
+    fn joins_empty_rows_and_wraps_long_lines() {
+        let pattern = Pattern::parse_rle("x = 1, y = 3\no2$o!").unwrap();
+        assert_eq!(sorted(pattern.cells.clone()), vec![(0, 0), (0, 2)]);
+        assert!(pattern.to_rle().ends_with("\no2$o!\n"));
+
+        // Una fila de células alternadas no entra en una sola línea de datos
+        let mut long = Pattern::empty();
+        long.cells = (0..100).map(|x| (x * 2, 0)).collect();
+        long.fit_bounds();
+        let text = long.to_rle();
+        let data: Vec<&str> = text.lines().skip(1).collect();
+        assert!(data.len() > 1);
+        assert!(data.iter().all(|line| line.len() <= RLE_LINE_WIDTH));
+        let again = Pattern::parse_rle(&text).unwrap();
+        assert_eq!(sorted(again.cells), sorted(long.cells));
+    }
+
+    #[test]
+    fn saves_the_board_trimmed() {
+        let mut game = GameOfLife::new(20, 20);
+        for (x, y) in glider() {
+            game.set(x + 7, y + 4, true);
+        }
+        let pattern = Pattern::from_game(&game, "Tablero");
+        assert_eq!((pattern.width, pattern.height), (3, 3));
+        assert_eq!(sorted(pattern.cells.clone()), glider());
+        let again = Pattern::parse_rle(&pattern.to_rle()).unwrap();
+        assert_eq!(sorted(again.cells), glider());
+    }
+
+    #[test]
+    fn places_rotated_and_mirrored() {
+        let pattern = Pattern::parse_rle(GLIDER_RLE).unwrap();
+        // Relativas al centro (1, 1): (0, -1), (1, 0), (-1, 1), (0, 1), (1, 1)
+        let placed = |direction| sorted(pattern.placed_cells(10, 10, direction));
+        assert_eq!(placed(Direction::Right), sorted(vec![(10, 9), (11, 10), (9, 11), (10, 11), (11, 11)]));
+        assert_eq!(placed(Direction::Left), sorted(vec![(10, 9), (9, 10), (11, 11), (10, 11), (9, 11)]));
+        assert_eq!(placed(Direction::Down), sorted(vec![(9, 10), (10, 11), (11, 9), (11, 10), (11, 11)]));
+        assert_eq!(placed(Direction::Up), sorted(vec![(11, 10), (10, 9), (9, 11), (9, 10), (9, 9)]));
+
+        let mut game = GameOfLife::new(20, 20);
+        pattern.place(&mut game, 10, 10, Direction::Left);
+        for (x, y) in placed(Direction::Left) {
+            assert!(game.is_alive(x, y));
+        }
+        assert_eq!(game.population(), 5);
+    }
+}
@@ -0,0 +1,60 @@
+use raylib::prelude::*;
+
+// Zoom mínimo y máximo, en píxeles por célula
+const MIN_ZOOM: f32 = 1.0;
+const MAX_ZOOM: f32 = 64.0;
+
+// Qué parte del tablero se ve: el tamaño de cada célula en píxeles y dónde queda
+// la esquina de la célula (0, 0) en el framebuffer
+pub struct View {
+    pub zoom: f32,
+    pub offset: Vector2,
+}
+
+impl View {
+    // Tablero entero, centrado y lo más grande que entre en la ventana
+    pub fn fit(screen_width: i32, screen_height: i32, columns: usize, rows: usize) -> Self {
+        let zoom = (screen_width as f32 / columns as f32)
+            .min(screen_height as f32 / rows as f32)
+            .floor()
+            .clamp(MIN_ZOOM, MAX_ZOOM);
+
+        Self {
+            zoom,
+            offset: Vector2 {
+                x: ((screen_width as f32 - columns as f32 * zoom) / 2.0).floor(),
+                y: ((screen_height as f32 - rows as f32 * zoom) / 2.0).floor(),
+            },
+        }
+    }
+
+    // Célula bajo un punto del framebuffer; puede quedar fuera del tablero
+    pub fn cell_at(&self, pos: Vector2) -> (i32, i32) {
+        (
+            ((pos.x - self.offset.x) / self.zoom).floor() as i32,
+            ((pos.y - self.offset.y) / self.zoom).floor() as i32,
+        )
+    }
+
+    // Esquina de una célula en el framebuffer
+    pub fn cell_origin(&self, x: usize, y: usize) -> (i32, i32) {
+        (
+            (self.offset.x + x as f32 * self.zoom).floor() as i32,
+            (self.offset.y + y as f32 * self.zoom).floor() as i32,
+        )
+    }
+
+    pub fn pan(&mut self, delta: Vector2) {
+        self.offset.x += delta.x;
+        self.offset.y += delta.y;
+    }
+
+    // Acerca (factor > 1) o aleja dejando quieto lo que está bajo `pos`
+    pub fn zoom_at(&mut self, pos: Vector2, factor: f32) {
+        let zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        let ratio = zoom / self.zoom;
+        self.offset.x = pos.x - (pos.x - self.offset.x) * ratio;
+        self.offset.y = pos.y - (pos.y - self.offset.y) * ratio;
+        self.zoom = zoom;
+    }
+}
@@ -0,0 +1,334 @@
+// audio.rs
+
+use rand::Rng;
+use raycaster::math::Vec2;
+use raycaster::player::Player;
+use raycaster::session::Sounds;
+use raylib::audio::{Music, RaylibAudio, Sound};
+use std::f32::consts::PI;
+
+const MUSIC_FILE: &str = "assets/sonidos/bg_music_taylor.wav";
+const WIN_FILE: &str = "assets/sonidos/victoria.wav";
+const STEP_FILE: &str = "assets/sonidos/steps.wav";
+const WALL_FILE: &str = "assets/slamming-wall.ogg";
+
+/// Frecuencia de muestreo de los sonidos generados.
+const SAMPLE_RATE: u32 = 22050;
+/// Distancia recorrida entre dos pasos, en celdas.
+const STRIDE: f32 = 0.45;
+/// Alcance de la campanada de la meta y del motor de los rivales, en celdas.
+const GOAL_RANGE: f32 = 8.0;
+const RIVAL_RANGE: f32 = 7.0;
+/// Segundos entre dos campanadas de la meta.
+const CHIME_PERIOD: f32 = 2.0;
+/// Cuánto se corre hacia un lado una fuente que está de costado (1 es el paneo completo).
+const PAN_WIDTH: f32 = 0.8;
+
+/// Volúmenes elegidos en el menú de sonido, de 0 a 1.
+#[derive(Clone, Copy, Debug)]
+pub struct AudioSettings {
+    pub music: f32,
+    pub sfx: f32,
+    pub muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            music: 0.8,
+            sfx: 1.0,
+            muted: false,
+        }
+    }
+}
+
+impl AudioSettings {
+    fn music_volume(&self) -> f32 {
+        if self.muted { 0.0 } else { self.music }
+    }
+
+    fn sfx_volume(&self) -> f32 {
+        if self.muted { 0.0 } else { self.sfx }
+    }
+}
+
+/// Música, efectos y fuentes posicionales. Cualquier sonido que no se pueda cargar (o todos,
+/// si no hay dispositivo de audio) queda en `None` y el juego sigue sin él.
+pub struct GameAudio<'a> {
+    device: Option<&'a RaylibAudio>,
+    music: Option<Music<'a>>,
+    win: Option<Sound<'a>>,
+    wall: Option<Sound<'a>>,
+    step: Option<Sound<'a>>,
+    chime: Option<Sound<'a>>,
+    engine: Option<Sound<'a>>,
+    /// Fondo del nivel, que se repite mientras se juega.
+    ambient: Option<Sound<'a>>,
+    pub settings: AudioSettings,
+    /// Distancia recorrida desde el último paso.
+    stride: f32,
+    /// Segundos hasta la próxima campanada de la meta.
+    chime_timer: f32,
+}
+
+impl<'a> GameAudio<'a> {
+    pub fn new(device: Option<&'a RaylibAudio>) -> Self {
+        let music = device.and_then(|d| match d.new_music(MUSIC_FILE) {
+            Ok(music) => Some(music),
+            Err(e) => {
+                eprintln!("Sin música de fondo ({}): {}", MUSIC_FILE, e);
+                None
+            }
+        });
+        let audio = Self {
+            device,
+            music,
+            win: load(device, WIN_FILE),
+            wall: load(device, WALL_FILE),
+            step: load(device, STEP_FILE),
+            chime: synth(device, &chime_samples()),
+            engine: synth(device, &engine_samples()),
+            ambient: None,
+            settings: AudioSettings::default(),
+            stride: 0.0,
+            chime_timer: 0.0,
+        };
+        if let Some(music) = &audio.music {
+            music.play_stream();
+        }
+        audio.apply_volume();
+        audio
+    }
+
+    /// Cambia el fondo del nivel: el archivo indicado o, sin él (o si no se puede cargar),
+    /// viento generado según la dificultad.
+    pub fn set_ambient(&mut self, path: Option<&str>, difficulty: u8) {
+        if let Some(old) = &self.ambient {
+            old.stop();
+        }
+        self.ambient = path
+            .and_then(|path| load(self.device, path))
+            .or_else(|| synth(self.device, &ambient_samples(difficulty)));
+        self.apply_volume();
+    }
+
+    /// Aplica los volúmenes de `settings` a lo que suena de fondo.
+    pub fn apply_volume(&self) {
+        if let Some(music) = &self.music {
+            music.set_volume(self.settings.music_volume());
+        }
+        if let Some(ambient) = &self.ambient {
+            ambient.set_volume(self.settings.music_volume() * 0.6);
+        }
+    }
+
+    /// Alimenta la música y vuelve a empezar el fondo del nivel cuando termina; se llama una
+    /// vez por cuadro.
+    pub fn update_music(&self) {
+        if let Some(music) = &self.music {
+            music.update_stream();
+        }
+        if let Some(ambient) = &self.ambient
+            && !ambient.is_playing()
+        {
+            ambient.play();
+        }
+    }
+
+    fn play(&self, sound: &Option<Sound>, volume: f32) {
+        if let Some(sound) = sound {
+            sound.set_volume(volume * self.settings.sfx_volume());
+            sound.set_pan(0.5);
+            sound.play();
+        }
+    }
+
+    pub fn win(&self) {
+        self.play(&self.win, 1.0);
+    }
+
+    /// Calla las fuentes posicionales, por ejemplo al abrir el editor o al ganar.
+    pub fn stop_sources(&self) {
+        for sound in [&self.chime, &self.engine].into_iter().flatten() {
+            sound.stop();
+        }
+    }
+}
+
+impl Sounds for GameAudio<'_> {
+    fn wall_hit(&self) {
+        self.play(&self.wall, 0.8);
+    }
+
+    /// Suena un paso cada `STRIDE` celdas recorridas, así que la cadencia sigue a la
+    /// velocidad; cada paso cambia un poco de tono para que no suenen todos iguales.
+    fn footsteps(&mut self, moved: f32, block_size: usize) {
+        if moved <= 0.0 {
+            return;
+        }
+        self.stride += moved / block_size as f32;
+        if self.stride >= STRIDE {
+            self.stride -= STRIDE;
+            if let Some(step) = &self.step {
+                step.set_pitch(rand::rng().random_range(0.9..1.1));
+            }
+            self.play(&self.step, 0.5);
+        }
+    }
+
+    /// Campanada periódica desde la meta y motor del rival más cercano, con volumen y paneo
+    /// según dónde están respecto de la vista del jugador.
+    fn update_sources(
+        &mut self,
+        player: &Player,
+        goal: Option<Vec2>,
+        rivals: &[Vec2],
+        block_size: usize,
+        delta_time: f32,
+    ) {
+        let block = block_size as f32;
+        let sfx = self.settings.sfx_volume();
+
+        self.chime_timer -= delta_time;
+        if self.chime_timer <= 0.0 {
+            self.chime_timer = CHIME_PERIOD;
+            if let (Some(chime), Some(goal)) = (&self.chime, goal)
+                && let Some((gain, pan)) = spatialize(player, goal, GOAL_RANGE * block)
+            {
+                chime.set_volume(gain * sfx);
+                chime.set_pan(pan);
+                chime.play();
+            }
+        }
+
+        if let Some(engine) = &self.engine {
+            let loudest = rivals
+                .iter()
+                .filter_map(|&pos| spatialize(player, pos, RIVAL_RANGE * block))
+                .max_by(|a, b| a.0.total_cmp(&b.0));
+            match loudest {
+                Some((gain, pan)) => {
+                    engine.set_volume(gain * sfx * 0.7);
+                    engine.set_pan(pan);
+                    if !engine.is_playing() {
+                        engine.play();
+                    }
+                }
+                None => engine.stop(),
+            }
+        }
+    }
+}
+
+/// Volumen (de 0 a 1) y paneo para una fuente en `source`, o `None` si está fuera de
+/// alcance. Se atenúa con la distancia y un poco más si está detrás del jugador.
+fn spatialize(player: &Player, source: Vec2, range: f32) -> Option<(f32, f32)> {
+    let (dx, dy) = (source.x - player.pos.x, source.y - player.pos.y);
+    let distance = dx.hypot(dy);
+    if distance >= range {
+        return None;
+    }
+    // Ángulo de la fuente respecto de la vista: positivo hacia la derecha, como `player.a`.
+    let relative = dy.atan2(dx) - player.a;
+    let gain = (1.0 - distance / range).powi(2) * (0.85 + 0.15 * relative.cos());
+    // En raylib el paneo va de 1 (izquierda) a 0 (derecha), con 0.5 al centro.
+    let pan = 0.5 - 0.5 * PAN_WIDTH * relative.sin();
+    Some((gain, pan))
+}
+
+/// Carga un sonido de disco; si falta se avisa por consola y el juego sigue sin él.
+fn load<'a>(device: Option<&'a RaylibAudio>, path: &str) -> Option<Sound<'a>> {
+    match device?.new_sound(path) {
+        Ok(sound) => Some(sound),
+        Err(e) => {
+            eprintln!("Sin sonido {}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Sonido generado a partir de muestras entre -1 y 1, pasando por un WAV en memoria.
+fn synth<'a>(device: Option<&'a RaylibAudio>, samples: &[f32]) -> Option<Sound<'a>> {
+    let device = device?;
+    let wave = device
+        .new_wave_from_memory(".wav", &wav_bytes(samples))
+        .ok()?;
+    device.new_sound_from_wave(&wave).ok()
+}
+
+/// WAV PCM de 16 bits, mono, a `SAMPLE_RATE`.
+fn wav_bytes(samples: &[f32]) -> Vec<u8> {
+    let data_len = samples.len() as u32 * 2;
+    let mut bytes = Vec::with_capacity(44 + data_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes()); // tamaño del bloque fmt
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // un canal
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes()); // bytes por segundo
+    bytes.extend_from_slice(&2u16.to_le_bytes()); // bytes por muestra
+    bytes.extend_from_slice(&16u16.to_le_bytes()); // bits por muestra
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    bytes
+}
+
+/// Muestras de `seconds` segundos, calculadas a partir del tiempo de cada una.
+fn samples(seconds: f32, f: impl FnMut(f32) -> f32) -> Vec<f32> {
+    let n = (seconds * SAMPLE_RATE as f32) as usize;
+    (0..n)
+        .map(|k| k as f32 / SAMPLE_RATE as f32)
+        .map(f)
+        .collect()
+}
+
+/// Campanada de la meta: una quinta (880 y 1320 Hz) que se apaga en menos de un segundo.
+fn chime_samples() -> Vec<f32> {
+    samples(0.8, |t| {
+        let tone = (2.0 * PI * 880.0 * t).sin() + 0.5 * (2.0 * PI * 1320.0 * t).sin();
+        0.4 * tone * (-5.0 * t).exp()
+    })
+}
+
+/// Motor de un rival: diente de sierra de 70 Hz con un leve vaivén. Dura medio segundo,
+/// un número entero de ciclos de ambos, para repetirse sin cortes.
+fn engine_samples() -> Vec<f32> {
+    samples(0.5, |t| {
+        let saw = 2.0 * (70.0 * t).fract() - 1.0;
+        let wobble = 0.8 + 0.2 * (2.0 * PI * 6.0 * t).sin();
+        0.35 * saw * wobble
+    })
+}
+
+/// Viento de fondo: ruido filtrado que sube y baja en ráfagas, más grave y con un zumbido
+/// más marcado cuanto más difícil es el nivel. Dura cuatro segundos y se repite.
+fn ambient_samples(difficulty: u8) -> Vec<f32> {
+    let (cutoff, drone) = match difficulty {
+        2 => (0.03, 55.0),
+        3 => (0.015, 41.0),
+        _ => (0.06, 0.0),
+    };
+    let gain = 0.37 / f32::sqrt(cutoff);
+    let mut rng = rand::rng();
+    let mut low = 0.0;
+    let mut out = samples(4.0, |t| {
+        low += cutoff * (rng.random_range(-1.0..1.0) - low);
+        let gust = 0.6 + 0.4 * (2.0 * PI * 0.25 * t).sin();
+        0.5 * low * gain * gust + 0.12 * (2.0 * PI * drone * t).sin()
+    });
+    // Fundido en los extremos para que el ruido no chasquee al repetirse
+    let fade = SAMPLE_RATE as usize / 20;
+    let n = out.len();
+    for k in 0..fade {
+        let f = k as f32 / fade as f32;
+        out[k] *= f;
+        out[n - 1 - k] *= f;
+    }
+    out
+}
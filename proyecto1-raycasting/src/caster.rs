@@ -1,48 +1,272 @@
 // caster.rs
 
-use raylib::color::Color;
+use crate::level::{Level, RayCell};
+use crate::math::Vec2;
 
-use crate::framebuffer::Framebuffer;
-use crate::maze::Maze;
-use crate::player::Player;
+/// Cara de la celda golpeada por el rayo.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    /// Línea de la cuadrícula con `x` constante (pared este/oeste).
+    Vertical,
+    /// Línea de la cuadrícula con `y` constante (pared norte/sur).
+    Horizontal,
+}
 
+/// Cara vertical que toca un rayo: la pared que corta `cast_ray_from` o cada pared, puerta y
+/// escalón que dibuja la vista 3D.
 pub struct Intersect {
+    /// Distancia exacta recorrida por el rayo hasta la cara, en píxeles del mundo.
     pub distance: f32,
+    /// Distancia proyectada sobre la dirección de la cámara (sin efecto ojo de pez).
+    pub perpendicular_distance: f32,
+    /// Carácter de la celda golpeada.
     pub impact: char,
+    pub side: Side,
+    /// Coordenada horizontal de textura en la cara golpeada, en `[0, 1)`.
+    pub wall_u: f32,
+    /// Punto de impacto, en píxeles del mundo.
+    pub hit: Vec2,
+}
+
+/// Recorrido de un rayo por la cuadrícula (DDA), en unidades de celda: cada llamada a
+/// `advance` entra a la celda siguiente que cruza el rayo.
+pub struct RayWalk {
+    /// Lado de una celda en píxeles del mundo.
+    block: f32,
+    /// Coseno entre el rayo y la dirección de la cámara, para la distancia perpendicular.
+    correction: f32,
+    pos_x: f32,
+    pos_y: f32,
+    dir_x: f32,
+    dir_y: f32,
+    pub map_x: isize,
+    pub map_y: isize,
+    step_x: isize,
+    step_y: isize,
+    side_x: f32,
+    side_y: f32,
+    delta_x: f32,
+    delta_y: f32,
+}
+
+impl RayWalk {
+    /// Rayo desde `origin` (en píxeles del mundo) con ángulo `a`, en celdas de `block` píxeles.
+    /// `view_angle` es la dirección de la cámara.
+    pub fn new(origin: Vec2, a: f32, view_angle: f32, block: f32) -> Self {
+        let (dir_x, dir_y) = (a.cos(), a.sin());
+
+        // Posición en unidades de celda.
+        let pos_x = origin.x / block;
+        let pos_y = origin.y / block;
+        let map_x = pos_x.floor() as isize;
+        let map_y = pos_y.floor() as isize;
+
+        // Distancia (en celdas) que avanza el rayo para cruzar una celda completa en cada eje.
+        let delta_x = if dir_x == 0.0 {
+            f32::INFINITY
+        } else {
+            (1.0 / dir_x).abs()
+        };
+        let delta_y = if dir_y == 0.0 {
+            f32::INFINITY
+        } else {
+            (1.0 / dir_y).abs()
+        };
+
+        let (step_x, side_x) = if dir_x < 0.0 {
+            (-1, (pos_x - map_x as f32) * delta_x)
+        } else {
+            (1, (map_x as f32 + 1.0 - pos_x) * delta_x)
+        };
+        let (step_y, side_y) = if dir_y < 0.0 {
+            (-1, (pos_y - map_y as f32) * delta_y)
+        } else {
+            (1, (map_y as f32 + 1.0 - pos_y) * delta_y)
+        };
+        Self {
+            block,
+            correction: (a - view_angle).cos(),
+            pos_x,
+            pos_y,
+            dir_x,
+            dir_y,
+            map_x,
+            map_y,
+            step_x,
+            step_y,
+            side_x,
+            side_y,
+            delta_x,
+            delta_y,
+        }
+    }
+
+    /// Entra a la celda siguiente (`map_x`, `map_y`) y devuelve la distancia recorrida hasta
+    /// su borde, en celdas, y la cara por la que entró.
+    pub fn advance(&mut self) -> (f32, Side) {
+        if self.side_x < self.side_y {
+            self.map_x += self.step_x;
+            let dist = self.side_x;
+            self.side_x += self.delta_x;
+            (dist, Side::Vertical)
+        } else {
+            self.map_y += self.step_y;
+            let dist = self.side_y;
+            self.side_y += self.delta_y;
+            (dist, Side::Horizontal)
+        }
+    }
+
+    /// Impacto con la cara `side` de la celda actual, que el rayo tocó a `cells` celdas de
+    /// recorrido; `impact` es el carácter de la celda.
+    pub fn intersect(&self, cells: f32, side: Side, impact: char) -> Intersect {
+        let distance = cells * self.block;
+        Intersect {
+            distance,
+            perpendicular_distance: distance * self.correction,
+            impact,
+            side,
+            wall_u: self.wall_u(cells, side),
+            hit: Vec2::new(
+                (self.pos_x + cells * self.dir_x) * self.block,
+                (self.pos_y + cells * self.dir_y) * self.block,
+            ),
+        }
+    }
+
+    /// Si el rayo, que entró a la celda de una puerta a `dist` por la cara `side`, choca con
+    /// la parte cerrada (`open` es la fracción deslizada), devuelve el impacto con el plano
+    /// de la puerta, con la coordenada de textura sobre ella.
+    pub fn door_hit(&self, dist: f32, side: Side, open: f32, impact: char) -> Option<Intersect> {
+        // Plano de la puerta a media celda de la cara por la que entró el rayo.
+        let (mid, along, index) = match side {
+            Side::Vertical => {
+                let mid = dist + self.delta_x / 2.0;
+                (mid, self.pos_y + mid * self.dir_y, self.map_y)
+            }
+            Side::Horizontal => {
+                let mid = dist + self.delta_y / 2.0;
+                (mid, self.pos_x + mid * self.dir_x, self.map_x)
+            }
+        };
+        let u = along - along.floor();
+        (along.floor() as isize == index && u >= open).then(|| Intersect {
+            wall_u: (u - open).min(0.9999),
+            ..self.intersect(mid, side, impact)
+        })
+    }
+
+    /// Coordenada horizontal de textura de una pared golpeada a `dist` celdas por `side`.
+    /// Se invierte en dos de las cuatro orientaciones para que la textura no salga en espejo.
+    fn wall_u(&self, dist: f32, side: Side) -> f32 {
+        let u = match side {
+            Side::Vertical => {
+                let u = (self.pos_y + dist * self.dir_y).rem_euclid(1.0);
+                if self.dir_x > 0.0 { u } else { 1.0 - u }
+            }
+            Side::Horizontal => {
+                let u = (self.pos_x + dist * self.dir_x).rem_euclid(1.0);
+                if self.dir_y < 0.0 { u } else { 1.0 - u }
+            }
+        };
+        u.min(0.9999)
+    }
 }
 
-pub fn cast_ray(
-    framebuffer: &mut Framebuffer,
-    maze: &Maze,
-    player: &Player,
+/// Recorre la cuadrícula celda por celda (DDA) desde `origin` hasta encontrar una pared.
+/// `view_angle` es la dirección de la cámara usada para la distancia perpendicular.
+///
+/// Las puertas se dibujan hundidas a media celda y se deslizan hacia un costado al abrirse;
+/// el rayo atraviesa la parte ya abierta. La altura de las paredes no cuenta: la luz y la
+/// vista de los rivales se cortan también en los muros bajos.
+pub fn cast_ray_from(
+    level: &Level,
+    origin: Vec2,
+    view_angle: f32,
     a: f32,
     block_size: usize,
-    draw_line: bool,
 ) -> Intersect {
-    let mut d = 0.0;
-
-    framebuffer.set_current_color(Color::WHITESMOKE);
-
+    let mut walk = RayWalk::new(origin, a, view_angle, block_size as f32);
     loop {
-        let cos = d * a.cos();
-        let sin = d * a.sin();
-        let x = (player.pos.x + cos) as usize;
-        let y = (player.pos.y + sin) as usize;
-
-        let i = x / block_size;
-        let j = y / block_size;
-
-        if maze[j][i] != ' ' {
-            return Intersect {
-                distance: d,
-                impact: maze[j][i],
-            };
+        let (dist, side) = walk.advance();
+
+        // Fuera del laberinto cuenta como pared para no recorrer infinitamente.
+        if walk.map_y < 0 || walk.map_x < 0 {
+            return walk.intersect(dist, side, '+');
         }
+        let (i, j) = (walk.map_x as usize, walk.map_y as usize);
+        match level.ray_cell(i, j) {
+            RayCell::Empty => {}
+            RayCell::Solid => return walk.intersect(dist, side, level.cell(i, j)),
+            RayCell::Door(open) => {
+                if let Some(hit) = walk.door_hit(dist, side, open, level.cell(i, j)) {
+                    return hit;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+    use crate::maze::test_level;
 
-        if draw_line {
-            framebuffer.set_pixel(x as u32, y as u32);
+    const BLOCK: usize = 100;
+
+    /// Nivel de prueba; `D` es una puerta sin llave.
+    fn level(rows: &[&str]) -> Level {
+        test_level(rows, &["D = puerta"])
+    }
+
+    /// Sala de 5x3 celdas libres.
+    fn room() -> Level {
+        level(&["+++++++", "+     +", "+     +", "+     +", "+++++++"])
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-2
+    }
+
+    #[test]
+    fn flat_wall_has_the_same_perpendicular_distance_across_the_view() {
+        let level = room();
+        let origin = Vec2::new(150.0, 250.0);
+        // La pared este está en x = 600, a 450 de la cámara que mira hacia ella
+        for a in [-0.3, -0.1, 0.0, 0.2, 0.3] {
+            let hit = cast_ray_from(&level, origin, 0.0, a, BLOCK);
+            assert!(close(hit.perpendicular_distance, 450.0), "a = {}", a);
+            assert!(close(hit.distance, 450.0 / a.cos()), "a = {}", a);
+            assert!(close(hit.hit.x, 600.0));
+            assert_eq!(hit.side, Side::Vertical);
+            assert_eq!(hit.impact, '+');
         }
+    }
+
+    #[test]
+    fn perpendicular_distance_is_measured_along_the_view() {
+        let level = room();
+        let origin = Vec2::new(150.0, 250.0);
+        // Hacia la pared de arriba (y = 100) mirando de frente y de costado
+        let straight = cast_ray_from(&level, origin, -FRAC_PI_2, -FRAC_PI_2, BLOCK);
+        assert!(close(straight.distance, 150.0));
+        assert!(close(straight.perpendicular_distance, 150.0));
+        assert_eq!(straight.side, Side::Horizontal);
+
+        let sideways = cast_ray_from(&level, origin, 0.0, -FRAC_PI_2, BLOCK);
+        assert!(close(sideways.distance, 150.0));
+        assert!(close(sideways.perpendicular_distance, 0.0));
+    }
 
-        d += 10.0;
+    #[test]
+    fn closed_door_is_hit_at_the_middle_of_its_cell() {
+        let level = level(&["+++++++", "+  D  +", "+++++++"]);
+        let hit = cast_ray_from(&level, Vec2::new(150.0, 150.0), 0.0, 0.0, BLOCK);
+        // La puerta ocupa la celda de x = 300 a 400 y su plano está en x = 350
+        assert!(close(hit.distance, 200.0));
+        assert!(close(hit.perpendicular_distance, 200.0));
+        assert_eq!(hit.impact, 'D');
     }
 }
@@ -0,0 +1,33 @@
+// color.rs
+
+/// Color de un píxel del framebuffer o de una textura, con transparencia. Es propio del
+/// juego para que el núcleo no dependa de raylib; los valores de las constantes son los de
+/// la paleta de raylib.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Rgba {
+    pub const BLACK: Rgba = Rgba::new(0, 0, 0, 255);
+    pub const WHITE: Rgba = Rgba::new(255, 255, 255, 255);
+    pub const RED: Rgba = Rgba::new(230, 41, 55, 255);
+    pub const GREEN: Rgba = Rgba::new(0, 228, 48, 255);
+    pub const LIME: Rgba = Rgba::new(0, 158, 47, 255);
+    pub const YELLOW: Rgba = Rgba::new(253, 249, 0, 255);
+    pub const GOLD: Rgba = Rgba::new(255, 203, 0, 255);
+    pub const ORANGE: Rgba = Rgba::new(255, 161, 0, 255);
+    pub const BROWN: Rgba = Rgba::new(127, 106, 79, 255);
+    pub const SKYBLUE: Rgba = Rgba::new(102, 191, 255, 255);
+    pub const VIOLET: Rgba = Rgba::new(135, 60, 190, 255);
+    pub const BLUEVIOLET: Rgba = Rgba::new(138, 43, 226, 255);
+    pub const DARKPURPLE: Rgba = Rgba::new(112, 31, 126, 255);
+    pub const TRANSPARENT: Rgba = Rgba::new(0, 0, 0, 0);
+
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+}
@@ -0,0 +1,163 @@
+// config.rs
+
+use std::fs;
+
+use crate::audio::AudioSettings;
+use crate::input::{Controls, KeyBindings, key_from_name, key_list};
+
+/// Archivo local donde se guardan las opciones.
+pub const CONFIG_FILE: &str = "config.txt";
+/// Filas del menú de opciones que se ajustan con las flechas; después vienen "Controles"
+/// y "Volver".
+pub const SETTINGS_ROWS: usize = 10;
+/// Fila del volumen de los efectos, que suena de prueba al cambiarlo.
+pub const SFX_ROW: usize = 8;
+
+/// Opciones del menú de configuración, guardadas entre partidas.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Campo de visión en grados.
+    pub fov: f32,
+    /// Fracción de la resolución de la ventana con que se dibuja la vista 3D.
+    pub resolution_scale: f32,
+    /// Teclas, captura del mouse y curvas del mouse y del stick.
+    pub controls: Controls,
+    pub audio: AudioSettings,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            fov: 60.0,
+            resolution_scale: 1.0,
+            controls: Controls::default(),
+            audio: AudioSettings::default(),
+        }
+    }
+}
+
+/// Si la fila `row` es de sí o no, y ENTER también la cambia.
+pub fn is_toggle(row: usize) -> bool {
+    row == 4 || row == 9
+}
+
+impl Config {
+    /// Lee líneas `clave: valor`; si falta el archivo, o una línea no se entiende, se usa
+    /// el valor por defecto. Las teclas de cada acción van separadas por comas.
+    pub fn load(path: &str) -> Self {
+        let mut config = Config::default();
+        let Ok(text) = fs::read_to_string(path) else {
+            return config;
+        };
+        for line in text.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            if let Some(action) = KeyBindings::ACTIONS.iter().position(|(_, k)| *k == key) {
+                let keys: Vec<_> = value.split(',').filter_map(key_from_name).collect();
+                if !keys.is_empty() {
+                    *config.controls.keys.actions_mut()[action] = keys;
+                }
+                continue;
+            }
+            let number = value.parse::<f32>().ok().filter(|n| n.is_finite());
+            let controls = &mut config.controls;
+            match (key, number) {
+                ("campo_vision", Some(n)) => config.fov = n,
+                ("escala_resolucion", Some(n)) => config.resolution_scale = n,
+                ("sensibilidad", Some(n)) => controls.mouse.sensitivity = n,
+                ("curva_mouse", Some(n)) => controls.mouse.exponent = n,
+                ("capturar_mouse", _) => controls.capture_mouse = value == "si",
+                ("sensibilidad_stick", Some(n)) => controls.stick.sensitivity = n,
+                ("curva_stick", Some(n)) => controls.stick.exponent = n,
+                ("musica", Some(n)) => config.audio.music = n,
+                ("efectos", Some(n)) => config.audio.sfx = n,
+                ("silencio", _) => config.audio.muted = value == "si",
+                _ => {}
+            }
+        }
+        config.adjust(usize::MAX, 0);
+        config
+    }
+
+    /// Guarda las opciones; un error de escritura sólo se informa por consola.
+    pub fn save(&self, path: &str) {
+        let controls = &self.controls;
+        let mut text = format!(
+            "campo_vision: {}\nescala_resolucion: {}\nsensibilidad: {}\ncurva_mouse: {}\ncapturar_mouse: {}\nsensibilidad_stick: {}\ncurva_stick: {}\nmusica: {}\nefectos: {}\nsilencio: {}\n",
+            self.fov,
+            self.resolution_scale,
+            controls.mouse.sensitivity,
+            controls.mouse.exponent,
+            yes_no(controls.capture_mouse),
+            controls.stick.sensitivity,
+            controls.stick.exponent,
+            self.audio.music,
+            self.audio.sfx,
+            yes_no(self.audio.muted),
+        );
+        let actions = KeyBindings::ACTIONS.iter().zip(controls.keys.actions());
+        for ((_, key), keys) in actions {
+            text.push_str(&format!("{}: {}\n", key, key_list(keys)));
+        }
+        if let Err(err) = fs::write(path, text) {
+            eprintln!("No se pudieron guardar las opciones en {}: {}", path, err);
+        }
+    }
+
+    /// Textos de las filas del menú de opciones, en orden.
+    pub fn rows(&self) -> [String; SETTINGS_ROWS] {
+        let controls = &self.controls;
+        let capture = if controls.capture_mouse { "sí" } else { "no" };
+        [
+            format!("Campo de visión: {:.0} grados", self.fov),
+            format!("Resolución: {:.0} %", self.resolution_scale * 100.0),
+            format!("Sensibilidad del mouse: {:.3}", controls.mouse.sensitivity),
+            format!("Curva del mouse: {:.1}", controls.mouse.exponent),
+            format!("Capturar el mouse: {}", capture),
+            format!("Sensibilidad del stick: {:.1}", controls.stick.sensitivity),
+            format!("Curva del stick: {:.2}", controls.stick.exponent),
+            format!("Música: {:.0} %", self.audio.music * 100.0),
+            format!("Efectos: {:.0} %", self.audio.sfx * 100.0),
+            format!("Silencio: {}", if self.audio.muted { "sí" } else { "no" }),
+        ]
+    }
+
+    /// Mueve la opción de la fila `row` `steps` pasos (negativo baja) y deja todas dentro
+    /// de sus límites.
+    pub fn adjust(&mut self, row: usize, steps: i32) {
+        let steps = steps as f32;
+        let (mouse, stick) = (&mut self.controls.mouse, &mut self.controls.stick);
+        match row {
+            0 => self.fov += 5.0 * steps,
+            1 => self.resolution_scale += 0.25 * steps,
+            2 => mouse.sensitivity += 0.002 * steps,
+            3 => mouse.exponent += 0.1 * steps,
+            4 if steps != 0.0 => self.controls.capture_mouse = !self.controls.capture_mouse,
+            5 => stick.sensitivity += 0.5 * steps,
+            6 => stick.exponent += 0.25 * steps,
+            7 => self.audio.music += 0.1 * steps,
+            8 => self.audio.sfx += 0.1 * steps,
+            9 if steps != 0.0 => self.audio.muted = !self.audio.muted,
+            _ => {}
+        }
+        self.fov = self.fov.clamp(45.0, 100.0);
+        self.resolution_scale = self.resolution_scale.clamp(0.25, 1.0);
+        mouse.sensitivity = round_to(mouse.sensitivity.clamp(0.002, 0.03), 0.001);
+        mouse.exponent = round_to(mouse.exponent.clamp(1.0, 2.0), 0.1);
+        stick.sensitivity = round_to(stick.sensitivity.clamp(1.0, 6.0), 0.5);
+        stick.exponent = round_to(stick.exponent.clamp(1.0, 3.0), 0.25);
+        self.audio.music = round_to(self.audio.music.clamp(0.0, 1.0), 0.1);
+        self.audio.sfx = round_to(self.audio.sfx.clamp(0.0, 1.0), 0.1);
+    }
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value { "si" } else { "no" }
+}
+
+/// Redondea al múltiplo de `step` más cercano, para que los pasos de 0.1 no acumulen error.
+fn round_to(value: f32, step: f32) -> f32 {
+    (value / step).round() * step
+}
@@ -0,0 +1,65 @@
+// display.rs
+
+use raycaster::color::Rgba;
+use raycaster::framebuffer::Framebuffer;
+use raycaster::textures::CpuImage;
+use raylib::prelude::*;
+
+/// Decodifica un archivo de imagen con raylib para las texturas del juego
+/// (`textures::LoadImage`).
+pub fn load_image(path: &str) -> CpuImage {
+    let img = Image::load_image(path).expect("No se pudo cargar la imagen");
+    let w = img.width as usize;
+    let h = img.height as usize;
+    let pixels = img
+        .get_image_data()
+        .iter()
+        .map(|c| Rgba::new(c.r, c.g, c.b, c.a))
+        .collect();
+    CpuImage { w, h, pixels }
+}
+
+/// Muestra el framebuffer en la ventana: lo copia a una textura y la estira a todo el ancho.
+/// La textura se reutiliza mientras el framebuffer no cambie de tamaño.
+#[derive(Default)]
+pub struct Display {
+    texture: Option<Texture2D>,
+}
+
+impl Display {
+    pub fn present<F>(
+        &mut self,
+        window: &mut RaylibHandle,
+        raylib_thread: &RaylibThread,
+        framebuffer: &Framebuffer,
+        draw_overlay: F,
+        fps: u32,
+    ) where
+        F: Fn(&mut RaylibDrawHandle, u32),
+    {
+        let (width, height) = (framebuffer.width as i32, framebuffer.height as i32);
+        let fits = self
+            .texture
+            .as_ref()
+            .is_some_and(|t| t.width() == width && t.height() == height);
+        if !fits {
+            let blank = Image::gen_image_color(width, height, Color::BLACK);
+            self.texture = window.load_texture_from_image(raylib_thread, &blank).ok();
+        }
+        let Some(texture) = self.texture.as_mut() else {
+            return;
+        };
+        if texture
+            .update_texture(&framebuffer.to_rgba_bytes())
+            .is_err()
+        {
+            return;
+        }
+
+        // Un framebuffer más chico que la ventana (resolución reducida) se estira para llenarla
+        let scale = window.get_screen_width() as f32 / framebuffer.width as f32;
+        let mut renderer = window.begin_drawing(raylib_thread);
+        renderer.draw_texture_ex(&*texture, Vector2::new(0.0, 0.0), 0.0, scale, Color::WHITE);
+        draw_overlay(&mut renderer, fps);
+    }
+}
@@ -0,0 +1,335 @@
+// editor.rs
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::enemy::Cell;
+use crate::level::Level;
+use crate::level_file::{CAMPAIGN_DIR, Entity, EntityKind, LevelFile, parse_level};
+use crate::lighting::Light;
+use crate::math::Vec2;
+use crate::maze::{Maze, Tile};
+
+/// Pasos que se pueden deshacer.
+const MAX_UNDO: usize = 100;
+
+/// Qué pinta el botón izquierdo del mouse.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Brush {
+    Wall,
+    Floor,
+    Goal,
+    Start,
+    Cup,
+    Rival,
+}
+
+impl Brush {
+    /// Pinceles en el orden de las teclas 1 a 6.
+    pub const ALL: [Brush; 6] = [
+        Brush::Wall,
+        Brush::Floor,
+        Brush::Goal,
+        Brush::Start,
+        Brush::Cup,
+        Brush::Rival,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Brush::Wall => "Pared",
+            Brush::Floor => "Piso",
+            Brush::Goal => "Meta",
+            Brush::Start => "Inicio",
+            Brush::Cup => "Copa",
+            Brush::Rival => "Rival",
+        }
+    }
+}
+
+/// Entrada de un cuadro del editor, ya leída del teclado y el mouse por el front end
+/// (`input::poll_editor`).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EditorInput {
+    /// Pincel elegido en este cuadro, si se apretó una de las teclas 1 a 6.
+    pub brush: Option<Brush>,
+    pub undo: bool,
+    pub save: bool,
+    /// Posición del mouse en la pantalla.
+    pub pointer: Vec2,
+    /// Botón izquierdo mantenido: pinta con el pincel.
+    pub paint: bool,
+    /// Botón derecho mantenido: borra (pinta piso).
+    pub erase: bool,
+    /// Si uno de los dos botones se apretó en este cuadro, lo que empieza un trazo.
+    pub pressed: bool,
+    /// Volver a 3D para probar el nivel editado.
+    pub play: bool,
+}
+
+/// Posición del laberinto en pantalla: esquina superior izquierda y lado de cada celda.
+#[derive(Clone, Copy, Debug)]
+pub struct EditorView {
+    pub x: usize,
+    pub y: usize,
+    pub cell: usize,
+}
+
+impl EditorView {
+    /// Escala el laberinto para que quepa centrado en el rectángulo dado.
+    pub fn fit(maze: &Maze, x: usize, y: usize, width: usize, height: usize) -> Self {
+        let rows = maze.len().max(1);
+        let cols = maze.iter().map(Vec::len).max().unwrap_or(1).max(1);
+        let cell = (width / cols).min(height / rows).max(1);
+        Self {
+            x: x + (width - cell * cols) / 2,
+            y: y + (height - cell * rows) / 2,
+            cell,
+        }
+    }
+
+    /// Celda bajo un punto de la pantalla, si está dentro del laberinto.
+    pub fn cell_at(&self, maze: &Maze, pos: Vec2) -> Option<Cell> {
+        if pos.x < self.x as f32 || pos.y < self.y as f32 {
+            return None;
+        }
+        let i = (pos.x as usize - self.x) / self.cell;
+        let j = (pos.y as usize - self.y) / self.cell;
+        maze.get(j).and_then(|row| row.get(i)).map(|_| (i, j))
+    }
+}
+
+/// Estado que se puede deshacer.
+struct Snapshot {
+    maze: Maze,
+    start: Cell,
+    entities: Vec<Entity>,
+    lights: Vec<Light>,
+}
+
+/// Editor del modo 2D. Pinta directamente sobre el `Level` en juego, así que al volver a
+/// 3D el nivel se prueba tal como quedó; copas y rivales se guardan aparte hasta entonces.
+pub struct Editor {
+    pub brush: Brush,
+    /// Nivel que se guarda: nombre, autor, tiempo, texturas y entidades.
+    file: LevelFile,
+    history: Vec<Snapshot>,
+    /// Estado antes del trazo en curso; pasa a `history` cuando el trazo cambia algo.
+    stroke: Option<Snapshot>,
+    /// Celda bajo el mouse.
+    pub hover: Option<Cell>,
+    /// Resultado del último guardado para mostrar en el HUD.
+    pub status: String,
+}
+
+impl Editor {
+    /// Abre el editor sobre `level`, con las copas y rivales que tiene la partida actual.
+    pub fn new(
+        level: &mut Level,
+        file: Option<&LevelFile>,
+        cups: &[Cell],
+        rivals: &[Cell],
+    ) -> Self {
+        level.reset();
+        let mut file = file
+            .cloned()
+            .unwrap_or_else(|| LevelFile::new("Nivel editado", Maze::new(), level.legend.clone()));
+        file.entities = cups
+            .iter()
+            .map(|&cell| Entity {
+                kind: EntityKind::Cup,
+                cell,
+            })
+            .chain(rivals.iter().map(|&cell| Entity {
+                kind: EntityKind::Enemy,
+                cell,
+            }))
+            .collect();
+        if file.path.is_none() {
+            // Un nivel generado guarda las antorchas que le tocaron al azar.
+            file.lights = level.lighting.lights.clone();
+            if !file.lights.is_empty() {
+                file.ambient = Some(level.lighting.ambient());
+            }
+        }
+        Self {
+            brush: Brush::Wall,
+            file,
+            history: Vec::new(),
+            stroke: None,
+            hover: None,
+            status: String::new(),
+        }
+    }
+
+    pub fn entities(&self) -> &[Entity] {
+        &self.file.entities
+    }
+
+    pub fn lights(&self) -> &[Light] {
+        &self.file.lights
+    }
+
+    /// Nivel tal como está en el editor, listo para jugar o guardar.
+    pub fn level_file(&self, level: &Level) -> LevelFile {
+        let mut file = self.file.clone();
+        file.maze = level.maze.clone();
+        file.start = Some(level.start);
+        file
+    }
+
+    /// Aplica la entrada de un cuadro: cambia el pincel, pinta o borra bajo el mouse,
+    /// deshace o guarda.
+    pub fn process_input(&mut self, input: &EditorInput, level: &mut Level, view: EditorView) {
+        if let Some(brush) = input.brush {
+            self.brush = brush;
+        }
+        if input.undo {
+            self.undo(level);
+        }
+        if input.save {
+            self.status = match self.save(level) {
+                Ok(path) => format!("Guardado en {}", path.display()),
+                Err(e) => format!("No se guardó: {}", e),
+            };
+        }
+
+        if !input.paint && !input.erase {
+            self.stroke = None;
+        } else if self.stroke.is_none() && input.pressed {
+            self.stroke = Some(self.snapshot(level));
+        }
+
+        self.hover = view.cell_at(&level.maze, input.pointer);
+        if let Some(cell) = self.hover {
+            if input.paint {
+                self.paint(level, cell, self.brush);
+            } else if input.erase {
+                self.paint(level, cell, Brush::Floor);
+            }
+        }
+    }
+
+    fn snapshot(&self, level: &Level) -> Snapshot {
+        Snapshot {
+            maze: level.maze.clone(),
+            start: level.start,
+            entities: self.file.entities.clone(),
+            lights: self.file.lights.clone(),
+        }
+    }
+
+    /// Guarda el estado previo al trazo la primera vez que el trazo cambia algo.
+    fn record(&mut self) {
+        if let Some(snapshot) = self.stroke.take() {
+            self.history.push(snapshot);
+            if self.history.len() > MAX_UNDO {
+                self.history.remove(0);
+            }
+        }
+    }
+
+    fn undo(&mut self, level: &mut Level) {
+        if let Some(snapshot) = self.history.pop() {
+            level.set_maze(snapshot.maze);
+            level.start = snapshot.start;
+            self.file.entities = snapshot.entities;
+            self.file.lights = snapshot.lights;
+        }
+    }
+
+    fn entity_at(&self, cell: Cell) -> bool {
+        self.file.entities.iter().any(|e| e.cell == cell)
+    }
+
+    /// Aplica el pincel a una celda. El borde exterior sólo admite pared o meta, y la celda
+    /// inicial no se puede tapar.
+    fn paint(&mut self, level: &mut Level, (i, j): Cell, brush: Brush) {
+        let cell = (i, j);
+        let border = i == 0 || j == 0 || j + 1 == level.maze.len() || i + 1 == level.maze[j].len();
+        let is_start = cell == level.start;
+        let wall = level
+            .legend
+            .char_for(Tile::Wall { texture: 0 })
+            .unwrap_or('+');
+        let goal = level.legend.char_for(Tile::Goal).unwrap_or('g');
+        let current = level.cell(i, j);
+
+        match brush {
+            Brush::Wall | Brush::Floor | Brush::Goal => {
+                let target = match brush {
+                    Brush::Wall => wall,
+                    Brush::Floor => ' ',
+                    _ => goal,
+                };
+                if is_start || (border && target == ' ') {
+                    return;
+                }
+                if current == target && !self.entity_at(cell) {
+                    return;
+                }
+                self.record();
+                if target == goal {
+                    // Hay una sola meta: la anterior vuelve a ser pared.
+                    for (gj, row) in level.maze.clone().iter().enumerate() {
+                        for (gi, &c) in row.iter().enumerate() {
+                            if level.legend.tile(c) == Tile::Goal {
+                                level.set_cell(gi, gj, wall);
+                            }
+                        }
+                    }
+                }
+                level.set_cell(i, j, target);
+                self.file.entities.retain(|e| e.cell != cell);
+                if target != ' ' {
+                    self.file.lights.retain(|l| l.cell != cell);
+                }
+            }
+            Brush::Start => {
+                if border || is_start || level.tile(i, j) != Tile::Empty || self.entity_at(cell) {
+                    return;
+                }
+                self.record();
+                level.start = cell;
+            }
+            Brush::Cup | Brush::Rival => {
+                let kind = if brush == Brush::Cup {
+                    EntityKind::Cup
+                } else {
+                    EntityKind::Enemy
+                };
+                let present = self
+                    .file
+                    .entities
+                    .iter()
+                    .any(|e| e.cell == cell && e.kind == kind);
+                if is_start || present || level.tile(i, j) != Tile::Empty {
+                    return;
+                }
+                self.record();
+                self.file.entities.retain(|e| e.cell != cell);
+                self.file.entities.push(Entity { kind, cell });
+            }
+        }
+    }
+
+    /// Valida el nivel como si se leyera de disco y lo escribe. Un nivel de la campaña se
+    /// sobrescribe; uno generado se guarda como un archivo nuevo de la campaña.
+    pub fn save(&mut self, level: &Level) -> Result<PathBuf, String> {
+        let file = self.level_file(level);
+        let text = file.to_text();
+        parse_level(&text, &file.name).map_err(|e| e.message)?;
+        let path = file.path.unwrap_or_else(new_level_path);
+        fs::write(&path, text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        self.file.path = Some(path.clone());
+        Ok(path)
+    }
+}
+
+/// Primer `NN_editado.txt` libre en la carpeta de la campaña.
+fn new_level_path() -> PathBuf {
+    (1..)
+        .map(|n| Path::new(CAMPAIGN_DIR).join(format!("{:02}_editado.txt", n)))
+        .find(|path| !path.exists())
+        .unwrap_or_else(|| Path::new(CAMPAIGN_DIR).join("editado.txt"))
+}
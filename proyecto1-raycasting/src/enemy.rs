@@ -0,0 +1,270 @@
+// enemy.rs
+
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+use std::collections::VecDeque;
+
+use crate::caster::cast_ray_from;
+use crate::level::Level;
+use crate::math::Vec2;
+use crate::maze::Tile;
+use crate::sprite::{Sprite, SpriteKind};
+
+/// Celda del laberinto como `(columna, fila)`.
+pub type Cell = (usize, usize);
+
+/// Segundos que un rival sigue persiguiendo la última posición vista del jugador.
+const SIGHT_MEMORY: f32 = 3.0;
+/// Distancia máxima (en píxeles del mundo) a la que un rival puede ver al jugador.
+const SIGHT_RANGE: f32 = 900.0;
+/// Distancia mínima en celdas entre la salida y el punto de aparición de un rival.
+const MIN_SPAWN_DISTANCE: usize = 6;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnemyState {
+    /// Recorre los pasillos sin rumbo fijo.
+    Patrol,
+    /// Sigue el camino más corto hacia el jugador.
+    Chase,
+}
+
+pub struct Enemy {
+    pub pos: Vec2,
+    pub state: EnemyState,
+    spawn: Vec2,
+    speed: f32,
+    /// Centros de celda pendientes de recorrer, el siguiente primero.
+    path: VecDeque<Cell>,
+    /// Celda de la que viene al patrullar, para no dar media vuelta en cada cruce.
+    previous: Option<Cell>,
+    /// Tiempo desde que vio al jugador por última vez.
+    unseen: f32,
+}
+
+fn cell_of(pos: Vec2, block_size: usize) -> Cell {
+    (
+        (pos.x / block_size as f32) as usize,
+        (pos.y / block_size as f32) as usize,
+    )
+}
+
+fn cell_center((i, j): Cell, block_size: usize) -> Vec2 {
+    Vec2::new(
+        i as f32 * block_size as f32 + block_size as f32 / 2.0,
+        j as f32 * block_size as f32 + block_size as f32 / 2.0,
+    )
+}
+
+/// Vecinos a los que se puede pasar sin subir más de un escalón; con `through_doors` las
+/// puertas cerradas cuentan como abiertas.
+fn open_neighbors(level: &Level, (i, j): Cell, through_doors: bool) -> Vec<Cell> {
+    let mut out = Vec::new();
+    for (di, dj) in [(1isize, 0isize), (-1, 0), (0, 1), (0, -1)] {
+        let ni = i as isize + di;
+        let nj = j as isize + dj;
+        if ni < 0 || nj < 0 {
+            continue;
+        }
+        let (ni, nj) = (ni as usize, nj as usize);
+        if level.can_step((i, j), (ni, nj))
+            || (through_doors && matches!(level.tile(ni, nj), Tile::Door { .. }))
+        {
+            out.push((ni, nj));
+        }
+    }
+    out
+}
+
+/// Distancia en pasos (BFS) desde `from` a cada celda libre alcanzable.
+fn distances(level: &Level, from: Cell, through_doors: bool) -> Vec<Vec<Option<usize>>> {
+    let mut dist: Vec<Vec<Option<usize>>> =
+        level.maze.iter().map(|row| vec![None; row.len()]).collect();
+    dist[from.1][from.0] = Some(0);
+    let mut queue = VecDeque::from([from]);
+    while let Some(cell) = queue.pop_front() {
+        let d = dist[cell.1][cell.0].unwrap_or(0);
+        for (ni, nj) in open_neighbors(level, cell, through_doors) {
+            if dist[nj][ni].is_none() {
+                dist[nj][ni] = Some(d + 1);
+                queue.push_back((ni, nj));
+            }
+        }
+    }
+    dist
+}
+
+/// Camino más corto entre dos celdas libres, incluyendo ambos extremos.
+pub fn find_path(level: &Level, from: Cell, to: Cell) -> Option<VecDeque<Cell>> {
+    // BFS desde el destino: luego basta bajar por distancias decrecientes desde el origen.
+    let dist = distances(level, to, false);
+    let mut current = from;
+    let mut d = dist.get(from.1)?.get(from.0).copied().flatten()?;
+    let mut path = VecDeque::from([from]);
+    while d > 0 {
+        current = open_neighbors(level, current, false)
+            .into_iter()
+            .find(|&(i, j)| dist[j][i] == Some(d - 1))?;
+        path.push_back(current);
+        d -= 1;
+    }
+    Some(path)
+}
+
+/// Indica si no hay paredes entre `from` y `to`, lanzando un rayo con `cast_ray_from`.
+pub fn has_line_of_sight(level: &Level, from: Vec2, to: Vec2, block_size: usize) -> bool {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let distance = dx.hypot(dy);
+    if distance > SIGHT_RANGE {
+        return false;
+    }
+    let a = dy.atan2(dx);
+    cast_ray_from(level, from, a, a, block_size).distance > distance
+}
+
+/// Reparte `count` rivales en celdas libres a al menos `MIN_SPAWN_DISTANCE` pasos de `start`.
+/// Pueden quedar detrás de puertas cerradas, esperando a que el jugador las abra.
+pub fn spawn_enemies(
+    level: &Level,
+    block_size: usize,
+    start: Cell,
+    count: usize,
+    speed: f32,
+    rng: &mut ChaCha8Rng,
+) -> Vec<Enemy> {
+    let dist = distances(level, start, true);
+    let mut candidates: Vec<Cell> = Vec::new();
+    for (j, row) in dist.iter().enumerate() {
+        for (i, d) in row.iter().enumerate() {
+            if d.is_some_and(|d| d >= MIN_SPAWN_DISTANCE) && level.is_passable(i, j) {
+                candidates.push((i, j));
+            }
+        }
+    }
+
+    let mut enemies = Vec::new();
+    while enemies.len() < count && !candidates.is_empty() {
+        let cell = candidates.swap_remove(rng.random_range(0..candidates.len()));
+        enemies.push(Enemy::new(cell, block_size, speed));
+    }
+    enemies
+}
+
+impl Enemy {
+    /// Rival patrullando desde el centro de `cell`.
+    pub fn new(cell: Cell, block_size: usize, speed: f32) -> Self {
+        let pos = cell_center(cell, block_size);
+        Self {
+            pos,
+            state: EnemyState::Patrol,
+            spawn: pos,
+            speed,
+            path: VecDeque::new(),
+            previous: None,
+            unseen: 0.0,
+        }
+    }
+
+    /// Decide hacia dónde ir y avanza `speed * delta_time` por el camino. Las patrullas al
+    /// azar salen de `rng`, para que una repetición las reproduzca igual.
+    pub fn update(
+        &mut self,
+        level: &Level,
+        target: Vec2,
+        block_size: usize,
+        delta_time: f32,
+        rng: &mut ChaCha8Rng,
+    ) {
+        let here = cell_of(self.pos, block_size);
+
+        if has_line_of_sight(level, self.pos, target, block_size) {
+            self.state = EnemyState::Chase;
+            self.unseen = 0.0;
+            // El camino empieza en la celda actual para volver a su centro antes de girar.
+            if let Some(path) = find_path(level, here, cell_of(target, block_size)) {
+                self.path = path;
+            }
+        } else if self.state == EnemyState::Chase {
+            self.unseen += delta_time;
+            if self.unseen > SIGHT_MEMORY {
+                self.state = EnemyState::Patrol;
+                self.path.clear();
+            }
+        }
+
+        if self.path.is_empty() {
+            self.choose_patrol_step(level, here, rng);
+        }
+
+        let mut step = self.speed * delta_time;
+        while step > 0.0 {
+            let Some(&next) = self.path.front() else {
+                break;
+            };
+            let goal = cell_center(next, block_size);
+            let (dx, dy) = (goal.x - self.pos.x, goal.y - self.pos.y);
+            let remaining = dx.hypot(dy);
+            if remaining <= step {
+                self.pos = goal;
+                step -= remaining;
+                // Al perseguir se queda esperando en la última posición vista.
+                if self.path.len() == 1 && self.state == EnemyState::Chase {
+                    break;
+                }
+                self.path.pop_front();
+            } else {
+                self.pos.x += dx / remaining * step;
+                self.pos.y += dy / remaining * step;
+                step = 0.0;
+            }
+        }
+    }
+
+    /// Elige una celda vecina al azar, evitando volver por donde vino salvo en callejones.
+    fn choose_patrol_step(&mut self, level: &Level, here: Cell, rng: &mut ChaCha8Rng) {
+        let neighbors = open_neighbors(level, here, false);
+        let forward: Vec<Cell> = neighbors
+            .iter()
+            .copied()
+            .filter(|&c| Some(c) != self.previous)
+            .collect();
+        let options = if forward.is_empty() {
+            neighbors
+        } else {
+            forward
+        };
+        if options.is_empty() {
+            return;
+        }
+        let next = options[rng.random_range(0..options.len())];
+        self.previous = Some(here);
+        // Pasa primero por el centro de la celda actual para no cortar esquinas.
+        self.path.push_back(here);
+        self.path.push_back(next);
+    }
+
+    /// Indica si el rival está a menos de `radius` de `pos`.
+    pub fn touches(&self, pos: Vec2, radius: f32) -> bool {
+        (self.pos.x - pos.x).hypot(self.pos.y - pos.y) < radius
+    }
+
+    /// Celda donde aparece el rival.
+    pub fn spawn_cell(&self, block_size: usize) -> Cell {
+        cell_of(self.spawn, block_size)
+    }
+
+    /// Devuelve al rival a su punto de aparición, patrullando.
+    pub fn reset(&mut self) {
+        self.pos = self.spawn;
+        self.state = EnemyState::Patrol;
+        self.path.clear();
+        self.previous = None;
+        self.unseen = 0.0;
+    }
+
+    pub fn sprite(&self) -> Sprite {
+        Sprite {
+            pos: self.pos,
+            kind: SpriteKind::Enemy,
+        }
+    }
+}
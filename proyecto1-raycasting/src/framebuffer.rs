@@ -1,64 +1,160 @@
 // framebuffer.rs
 
-use raylib::prelude::*;
+use std::fs;
+use std::io;
 
+use crate::color::Rgba;
+
+/// Imagen en memoria donde se dibuja cada cuadro, píxel por píxel. No depende de la ventana:
+/// se muestra con `Display` o se guarda como PNG.
 pub struct Framebuffer {
     pub width: u32,
     pub height: u32,
-    pub color_buffer: Image,
-    background_color: Color,
-    current_color: Color,
+    /// Píxeles por filas, de arriba a abajo.
+    pub pixels: Vec<Rgba>,
+    background_color: Rgba,
+    current_color: Rgba,
 }
 
 impl Framebuffer {
     pub fn new(width: u32, height: u32) -> Self {
-        let color_buffer = Image::gen_image_color(width as i32, height as i32, Color::BLACK);
         Framebuffer {
             width,
             height,
-            color_buffer,
-            background_color: Color::BLACK,
-            current_color: Color::WHITE,
+            pixels: vec![Rgba::BLACK; (width * height) as usize],
+            background_color: Rgba::BLACK,
+            current_color: Rgba::WHITE,
         }
     }
 
     pub fn clear(&mut self) {
-        self.color_buffer =
-            Image::gen_image_color(self.width as i32, self.height as i32, self.background_color);
+        self.pixels.fill(self.background_color);
     }
 
     pub fn set_pixel(&mut self, x: u32, y: u32) {
         if x < self.width && y < self.height {
-            self.color_buffer
-                .draw_pixel(x as i32, y as i32, self.current_color);
+            self.pixels[(y * self.width + x) as usize] = self.current_color;
         }
     }
 
-    pub fn set_background_color(&mut self, color: Color) {
+    /// Color ya dibujado en `(x, y)`; fuera del framebuffer, el de fondo.
+    pub fn get_pixel(&self, x: u32, y: u32) -> Rgba {
+        if x < self.width && y < self.height {
+            self.pixels[(y * self.width + x) as usize]
+        } else {
+            self.background_color
+        }
+    }
+
+    /// Copia `source` con su esquina superior izquierda en `(x, y)`; lo que cae fuera se
+    /// recorta.
+    pub fn blit(&mut self, source: &Framebuffer, x: u32, y: u32) {
+        let width = source.width.min(self.width.saturating_sub(x)) as usize;
+        if width == 0 {
+            return;
+        }
+        for (row, line) in source.pixels.chunks(source.width as usize).enumerate() {
+            let target_y = y as usize + row;
+            if target_y >= self.height as usize {
+                break;
+            }
+            let start = target_y * self.width as usize + x as usize;
+            self.pixels[start..start + width].copy_from_slice(&line[..width]);
+        }
+    }
+
+    pub fn set_background_color(&mut self, color: Rgba) {
         self.background_color = color;
     }
 
-    pub fn set_current_color(&mut self, color: Color) {
+    pub fn set_current_color(&mut self, color: Rgba) {
         self.current_color = color;
     }
 
-    pub fn _render_to_file(&self, file_path: &str) {
-        self.color_buffer.export_image(file_path);
+    /// Los píxeles como bytes RGBA seguidos, el formato de una textura.
+    pub fn to_rgba_bytes(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|c| [c.r, c.g, c.b, c.a])
+            .collect()
+    }
+
+    /// Guarda el cuadro como PNG de 8 bits RGBA.
+    pub fn save_png(&self, path: &str) -> io::Result<()> {
+        fs::write(path, self.to_png())
+    }
+
+    /// Codifica el PNG sin comprimir: cada fila lleva el filtro 0 y los datos van en bloques
+    /// deflate "almacenados", que cualquier lector entiende.
+    fn to_png(&self) -> Vec<u8> {
+        let row_bytes = self.width as usize * 4;
+        let mut raw = Vec::with_capacity((row_bytes + 1) * self.height as usize);
+        let bytes = self.to_rgba_bytes();
+        for row in bytes.chunks(row_bytes.max(1)) {
+            raw.push(0);
+            raw.extend_from_slice(row);
+        }
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&self.width.to_be_bytes());
+        header.extend_from_slice(&self.height.to_be_bytes());
+        // 8 bits por canal, color RGBA, compresión y filtro estándar, sin entrelazado
+        header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+        let mut png = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+        push_chunk(&mut png, b"IHDR", &header);
+        push_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+        push_chunk(&mut png, b"IEND", &[]);
+        png
     }
+}
 
-    pub fn swap_buffers<F>(
-        &self,
-        window: &mut RaylibHandle,
-        raylib_thread: &RaylibThread,
-        draw_overlay: F,
-        fps: u32,
-    ) where
-        F: Fn(&mut RaylibDrawHandle, u32),
-    {
-        if let Ok(texture) = window.load_texture_from_image(raylib_thread, &self.color_buffer) {
-            let mut renderer = window.begin_drawing(raylib_thread);
-            renderer.draw_texture(&texture, 0, 0, Color::WHITE);
-            draw_overlay(&mut renderer, fps);
+/// Agrega un bloque PNG: largo, tipo, datos y CRC del tipo y los datos.
+fn push_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Flujo zlib con los datos sin comprimir, en bloques de hasta 65535 bytes.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(u16::MAX as usize).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = u8::from(blocks.peek().is_none());
+        let len = block.len() as u16;
+        out.push(last);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
         }
     }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
 }
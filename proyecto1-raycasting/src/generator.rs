@@ -0,0 +1,443 @@
+// generator.rs
+
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::collections::VecDeque;
+
+use crate::maze::Maze;
+
+const WALL: char = '+';
+const FLOOR: char = ' ';
+const GOAL: char = 'g';
+
+/// Intentos con semillas consecutivas si un laberinto no pasa la verificación.
+const MAX_ATTEMPTS: u64 = 8;
+
+/// Algoritmo con el que se tallan los pasillos. Todos son iterativos, así que el tamaño
+/// del laberinto no depende de la pila.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    /// Búsqueda en profundidad con pila: pasillos largos y sinuosos.
+    Backtracker,
+    /// Prim aleatorio: muchos callejones cortos.
+    Prim,
+    /// Kruskal con conjuntos disjuntos.
+    Kruskal,
+    /// Caminatas aleatorias con borrado de ciclos; uniforme entre todos los laberintos.
+    Wilson,
+    /// Fila por fila, recordando sólo a qué conjunto pertenece cada celda.
+    Eller,
+}
+
+impl Algorithm {
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Backtracker => "backtracking",
+            Algorithm::Prim => "prim",
+            Algorithm::Kruskal => "kruskal",
+            Algorithm::Wilson => "wilson",
+            Algorithm::Eller => "eller",
+        }
+    }
+}
+
+/// Parámetros de generación; la misma configuración produce siempre el mismo laberinto.
+#[derive(Clone, Copy, Debug)]
+pub struct MazeConfig {
+    /// Ancho en caracteres; si es par se agranda a impar.
+    pub width: usize,
+    /// Alto en caracteres; si es par se agranda a impar.
+    pub height: usize,
+    pub algorithm: Algorithm,
+    /// Fracción (0 a 1) de callejones sin salida que se abren para formar ciclos.
+    pub braid: f32,
+    /// Salas rectangulares que se abren sobre los pasillos.
+    pub rooms: usize,
+    pub seed: u64,
+}
+
+impl MazeConfig {
+    /// Configuración de los niveles 1 (fácil) a 3 (difícil). La semilla elige además
+    /// entre los algoritmos de cada dificultad.
+    pub fn for_difficulty(difficulty: u8, seed: u64) -> Self {
+        let (size, algorithms, braid, rooms): (usize, &[Algorithm], f32, usize) = match difficulty {
+            2 => (
+                21,
+                &[Algorithm::Kruskal, Algorithm::Eller, Algorithm::Wilson],
+                0.25,
+                2,
+            ),
+            3 => (31, &[Algorithm::Backtracker, Algorithm::Wilson], 0.1, 1),
+            _ => (11, &[Algorithm::Prim, Algorithm::Kruskal], 0.5, 1),
+        };
+        Self {
+            width: size,
+            height: size,
+            algorithm: algorithms[(seed % algorithms.len() as u64) as usize],
+            braid,
+            rooms,
+            seed,
+        }
+    }
+}
+
+/// Laberinto generado y verificado: la meta (`g`) es alcanzable desde el inicio.
+pub struct GeneratedMaze {
+    pub maze: Maze,
+    pub start: (usize, usize),
+}
+
+/// Cuadrícula de celdas sobre el laberinto de caracteres: la celda `(x, y)` está en
+/// `(2x + 1, 2y + 1)` y entre dos celdas vecinas hay un carácter de pared.
+struct Grid {
+    cols: usize,
+    rows: usize,
+    maze: Maze,
+}
+
+impl Grid {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            cols: (width - 1) / 2,
+            rows: (height - 1) / 2,
+            maze: vec![vec![WALL; width]; height],
+        }
+    }
+
+    fn index(&self, (x, y): (usize, usize)) -> usize {
+        y * self.cols + x
+    }
+
+    fn cell(&self, index: usize) -> (usize, usize) {
+        (index % self.cols, index / self.cols)
+    }
+
+    fn neighbors(&self, (x, y): (usize, usize)) -> Vec<(usize, usize)> {
+        let mut out = Vec::with_capacity(4);
+        if x > 0 {
+            out.push((x - 1, y));
+        }
+        if x + 1 < self.cols {
+            out.push((x + 1, y));
+        }
+        if y > 0 {
+            out.push((x, y - 1));
+        }
+        if y + 1 < self.rows {
+            out.push((x, y + 1));
+        }
+        out
+    }
+
+    /// Abre ambas celdas y la pared entre ellas.
+    fn link(&mut self, a: (usize, usize), b: (usize, usize)) {
+        self.maze[2 * a.1 + 1][2 * a.0 + 1] = FLOOR;
+        self.maze[a.1 + b.1 + 1][a.0 + b.0 + 1] = FLOOR;
+        self.maze[2 * b.1 + 1][2 * b.0 + 1] = FLOOR;
+    }
+
+    fn linked(&self, a: (usize, usize), b: (usize, usize)) -> bool {
+        self.maze[a.1 + b.1 + 1][a.0 + b.0 + 1] == FLOOR
+    }
+
+    fn exits(&self, cell: (usize, usize)) -> usize {
+        self.neighbors(cell)
+            .into_iter()
+            .filter(|&n| self.linked(cell, n))
+            .count()
+    }
+}
+
+fn backtracker(grid: &mut Grid, rng: &mut ChaCha8Rng) {
+    let mut visited = vec![false; grid.cols * grid.rows];
+    visited[0] = true;
+    let mut stack = vec![(0, 0)];
+    while let Some(&cell) = stack.last() {
+        let options: Vec<_> = grid
+            .neighbors(cell)
+            .into_iter()
+            .filter(|&n| !visited[grid.index(n)])
+            .collect();
+        if options.is_empty() {
+            stack.pop();
+            continue;
+        }
+        let next = options[rng.random_range(0..options.len())];
+        visited[grid.index(next)] = true;
+        grid.link(cell, next);
+        stack.push(next);
+    }
+}
+
+fn prim(grid: &mut Grid, rng: &mut ChaCha8Rng) {
+    let mut in_maze = vec![false; grid.cols * grid.rows];
+    in_maze[0] = true;
+    let mut frontier: Vec<_> = grid
+        .neighbors((0, 0))
+        .into_iter()
+        .map(|n| ((0, 0), n))
+        .collect();
+    while !frontier.is_empty() {
+        let (from, to) = frontier.swap_remove(rng.random_range(0..frontier.len()));
+        if in_maze[grid.index(to)] {
+            continue;
+        }
+        in_maze[grid.index(to)] = true;
+        grid.link(from, to);
+        for n in grid.neighbors(to) {
+            if !in_maze[grid.index(n)] {
+                frontier.push((to, n));
+            }
+        }
+    }
+}
+
+/// Raíz del conjunto de `i`, acortando el camino de paso.
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+fn kruskal(grid: &mut Grid, rng: &mut ChaCha8Rng) {
+    let mut edges = Vec::new();
+    for y in 0..grid.rows {
+        for x in 0..grid.cols {
+            if x + 1 < grid.cols {
+                edges.push(((x, y), (x + 1, y)));
+            }
+            if y + 1 < grid.rows {
+                edges.push(((x, y), (x, y + 1)));
+            }
+        }
+    }
+    edges.shuffle(rng);
+    let mut parent: Vec<usize> = (0..grid.cols * grid.rows).collect();
+    for (a, b) in edges {
+        let (ra, rb) = (
+            find(&mut parent, grid.index(a)),
+            find(&mut parent, grid.index(b)),
+        );
+        if ra != rb {
+            parent[ra] = rb;
+            grid.link(a, b);
+        }
+    }
+}
+
+fn wilson(grid: &mut Grid, rng: &mut ChaCha8Rng) {
+    let count = grid.cols * grid.rows;
+    let mut in_maze = vec![false; count];
+    in_maze[rng.random_range(0..count)] = true;
+    // Último paso dado desde cada celda; sobrescribirlo borra los ciclos de la caminata.
+    let mut next = vec![0; count];
+    let mut order: Vec<usize> = (0..count).collect();
+    order.shuffle(rng);
+
+    for start in order {
+        let mut current = start;
+        while !in_maze[current] {
+            let options = grid.neighbors(grid.cell(current));
+            let step = options[rng.random_range(0..options.len())];
+            next[current] = grid.index(step);
+            current = next[current];
+        }
+        current = start;
+        while !in_maze[current] {
+            in_maze[current] = true;
+            grid.link(grid.cell(current), grid.cell(next[current]));
+            current = next[current];
+        }
+    }
+}
+
+fn eller(grid: &mut Grid, rng: &mut ChaCha8Rng) {
+    let cols = grid.cols;
+    let mut sets: Vec<usize> = (0..cols).collect();
+    let mut next_set = cols;
+
+    for y in 0..grid.rows {
+        let last = y + 1 == grid.rows;
+        // Une vecinos de conjuntos distintos; en la última fila, todos.
+        for x in 0..cols - 1 {
+            if sets[x] != sets[x + 1] && (last || rng.random_bool(0.5)) {
+                grid.link((x, y), (x + 1, y));
+                let (old, new) = (sets[x + 1], sets[x]);
+                for set in sets.iter_mut().filter(|s| **s == old) {
+                    *set = new;
+                }
+            }
+        }
+        if last {
+            break;
+        }
+
+        // Cada conjunto baja al menos por una celda para no quedar aislado.
+        let mut down: Vec<bool> = (0..cols).map(|_| rng.random_bool(0.4)).collect();
+        let mut seen = Vec::new();
+        for &set in &sets {
+            if seen.contains(&set) {
+                continue;
+            }
+            seen.push(set);
+            let members: Vec<usize> = (0..cols).filter(|&x| sets[x] == set).collect();
+            if !members.iter().any(|&x| down[x]) {
+                down[members[rng.random_range(0..members.len())]] = true;
+            }
+        }
+        for x in 0..cols {
+            if down[x] {
+                grid.link((x, y), (x, y + 1));
+            } else {
+                sets[x] = next_set;
+                next_set += 1;
+            }
+        }
+    }
+}
+
+/// Abre salas de 2x2 a 3x3 celdas. Sólo quita paredes, así que el laberinto sigue conexo.
+fn carve_rooms(grid: &mut Grid, rooms: usize, rng: &mut ChaCha8Rng) {
+    for _ in 0..rooms {
+        let w = rng.random_range(2..=3).min(grid.cols);
+        let h = rng.random_range(2..=3).min(grid.rows);
+        let x = rng.random_range(0..=grid.cols - w);
+        let y = rng.random_range(0..=grid.rows - h);
+        for row in &mut grid.maze[2 * y + 1..2 * (y + h)] {
+            for c in &mut row[2 * x + 1..2 * (x + w)] {
+                *c = FLOOR;
+            }
+        }
+    }
+}
+
+/// Abre una pared en una fracción `braid` de los callejones, prefiriendo unir dos callejones.
+fn braid(grid: &mut Grid, braid: f32, rng: &mut ChaCha8Rng) {
+    if braid <= 0.0 {
+        return;
+    }
+    let mut dead_ends: Vec<_> = (0..grid.cols * grid.rows)
+        .map(|i| grid.cell(i))
+        .filter(|&c| grid.exits(c) == 1)
+        .collect();
+    dead_ends.shuffle(rng);
+    for cell in dead_ends {
+        // Un callejón anterior pudo haberlo abierto ya.
+        if grid.exits(cell) != 1 || rng.random::<f32>() >= braid {
+            continue;
+        }
+        let closed: Vec<_> = grid
+            .neighbors(cell)
+            .into_iter()
+            .filter(|&n| !grid.linked(cell, n))
+            .collect();
+        let preferred: Vec<_> = closed
+            .iter()
+            .copied()
+            .filter(|&n| grid.exits(n) == 1)
+            .collect();
+        let options = if preferred.is_empty() {
+            closed
+        } else {
+            preferred
+        };
+        if !options.is_empty() {
+            let other = options[rng.random_range(0..options.len())];
+            grid.link(cell, other);
+        }
+    }
+}
+
+fn open_neighbors(maze: &Maze, (i, j): (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+    [(1isize, 0isize), (-1, 0), (0, 1), (0, -1)]
+        .into_iter()
+        .filter_map(move |(di, dj)| {
+            let (ni, nj) = (i.checked_add_signed(di)?, j.checked_add_signed(dj)?);
+            maze.get(nj)?.get(ni).map(|&c| ((ni, nj), c))
+        })
+        .filter(|&(_, c)| c == FLOOR || c == GOAL)
+        .map(|(cell, _)| cell)
+}
+
+/// Distancias (BFS) desde `from` por celdas libres; la meta también se puede pisar.
+fn distances(maze: &Maze, from: (usize, usize)) -> Vec<Vec<Option<usize>>> {
+    let mut dist: Vec<Vec<Option<usize>>> = maze.iter().map(|row| vec![None; row.len()]).collect();
+    dist[from.1][from.0] = Some(0);
+    let mut queue = VecDeque::from([from]);
+    while let Some(cell) = queue.pop_front() {
+        let d = dist[cell.1][cell.0].unwrap_or(0);
+        for (ni, nj) in open_neighbors(maze, cell) {
+            if dist[nj][ni].is_none() {
+                dist[nj][ni] = Some(d + 1);
+                queue.push_back((ni, nj));
+            }
+        }
+    }
+    dist
+}
+
+/// Camino más corto entre dos celdas por pasillos libres, o `None` si no hay salida.
+pub fn solve(maze: &Maze, from: (usize, usize), to: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+    let dist = distances(maze, to);
+    let mut d = dist.get(from.1)?.get(from.0).copied().flatten()?;
+    let mut current = from;
+    let mut path = vec![from];
+    while d > 0 {
+        current = open_neighbors(maze, current).find(|&(i, j)| dist[j][i] == Some(d - 1))?;
+        path.push(current);
+        d -= 1;
+    }
+    Some(path)
+}
+
+/// Celda libre más lejana (en pasos) desde `from`. Nunca separa el laberinto: todo lo que
+/// sólo se alcanzara a través de ella estaría todavía más lejos.
+fn farthest_cell(maze: &Maze, from: (usize, usize)) -> (usize, usize) {
+    let dist = distances(maze, from);
+    let mut best = (from, 0);
+    for (j, row) in dist.iter().enumerate() {
+        for (i, d) in row.iter().enumerate() {
+            if let Some(d) = *d
+                && d > best.1
+            {
+                best = ((i, j), d);
+            }
+        }
+    }
+    best.0
+}
+
+fn carve(config: &MazeConfig, seed: u64) -> Maze {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut grid = Grid::new(config.width.max(5) | 1, config.height.max(5) | 1);
+    match config.algorithm {
+        Algorithm::Backtracker => backtracker(&mut grid, &mut rng),
+        Algorithm::Prim => prim(&mut grid, &mut rng),
+        Algorithm::Kruskal => kruskal(&mut grid, &mut rng),
+        Algorithm::Wilson => wilson(&mut grid, &mut rng),
+        Algorithm::Eller => eller(&mut grid, &mut rng),
+    }
+    carve_rooms(&mut grid, config.rooms, &mut rng);
+    braid(&mut grid, config.braid.clamp(0.0, 1.0), &mut rng);
+    grid.maze
+}
+
+/// Genera el laberinto de `config` con el inicio en `(1, 1)` y la meta en la celda más
+/// lejana, y comprueba con `solve` que haya un camino entre ambos. Si la verificación falla
+/// prueba con las semillas siguientes; `None` sólo si ninguna sirve.
+pub fn generate(config: &MazeConfig) -> Option<GeneratedMaze> {
+    let start = (1, 1);
+    (0..MAX_ATTEMPTS).find_map(|attempt| {
+        let mut maze = carve(config, config.seed.wrapping_add(attempt));
+        let goal = farthest_cell(&maze, start);
+        if goal == start {
+            return None;
+        }
+        maze[goal.1][goal.0] = GOAL;
+        solve(&maze, start, goal)?;
+        Some(GeneratedMaze { maze, start })
+    })
+}
@@ -0,0 +1,449 @@
+// input.rs
+
+use raycaster::editor::{Brush, EditorInput};
+use raycaster::math::Vec2;
+use raycaster::player::InputFrame;
+use raylib::prelude::*;
+
+/// Inclinación de la vista por segundo con AvPág y RePág.
+const PITCH_KEY_SPEED: f32 = 1.0;
+/// Píxeles de mouse por cuadro en que la curva del mouse no cambia la sensibilidad: más
+/// rápido gira de más y más lento gira de menos.
+const MOUSE_REFERENCE: f32 = 10.0;
+/// Inclinación de un stick que se ignora; los sticks gastados no vuelven justo al centro.
+const STICK_DEADZONE: f32 = 0.15;
+/// Inclinación del stick izquierdo a partir de la cual cuenta como tecla apretada.
+const STICK_PRESS: f32 = 0.5;
+
+/// Teclas de cada acción; cualquiera de la lista la activa.
+#[derive(Clone, Debug)]
+pub struct KeyBindings {
+    pub forward: Vec<KeyboardKey>,
+    pub back: Vec<KeyboardKey>,
+    pub strafe_left: Vec<KeyboardKey>,
+    pub strafe_right: Vec<KeyboardKey>,
+    pub turn_left: Vec<KeyboardKey>,
+    pub turn_right: Vec<KeyboardKey>,
+    pub interact: Vec<KeyboardKey>,
+    pub peek_map: Vec<KeyboardKey>,
+}
+
+impl Default for KeyBindings {
+    /// W/S o flechas para avanzar y retroceder, A/D para moverse de lado, las flechas
+    /// laterales para girar, E para usar y TAB para espiar el mapa.
+    fn default() -> Self {
+        Self {
+            forward: vec![KeyboardKey::KEY_W, KeyboardKey::KEY_UP],
+            back: vec![KeyboardKey::KEY_S, KeyboardKey::KEY_DOWN],
+            strafe_left: vec![KeyboardKey::KEY_A],
+            strafe_right: vec![KeyboardKey::KEY_D],
+            turn_left: vec![KeyboardKey::KEY_LEFT],
+            turn_right: vec![KeyboardKey::KEY_RIGHT],
+            interact: vec![KeyboardKey::KEY_E],
+            peek_map: vec![KeyboardKey::KEY_TAB],
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Cada acción con su nombre en el menú de controles y su clave en el archivo de
+    /// opciones, en el orden de `actions`.
+    pub const ACTIONS: [(&str, &str); 8] = [
+        ("Avanzar", "tecla_avanzar"),
+        ("Retroceder", "tecla_retroceder"),
+        ("Izquierda (de lado)", "tecla_izquierda"),
+        ("Derecha (de lado)", "tecla_derecha"),
+        ("Girar a la izquierda", "tecla_girar_izquierda"),
+        ("Girar a la derecha", "tecla_girar_derecha"),
+        ("Usar", "tecla_usar"),
+        ("Mostrar el minimapa", "tecla_minimapa"),
+    ];
+
+    /// Primer jugador de una carrera, a la izquierda del teclado: W/S, A/D para girar, Q/E
+    /// de lado, F para usar y R para espiar el mapa.
+    pub fn left_racer() -> Self {
+        Self {
+            forward: vec![KeyboardKey::KEY_W],
+            back: vec![KeyboardKey::KEY_S],
+            strafe_left: vec![KeyboardKey::KEY_Q],
+            strafe_right: vec![KeyboardKey::KEY_E],
+            turn_left: vec![KeyboardKey::KEY_A],
+            turn_right: vec![KeyboardKey::KEY_D],
+            interact: vec![KeyboardKey::KEY_F],
+            peek_map: vec![KeyboardKey::KEY_R],
+        }
+    }
+
+    /// Segundo jugador, a la derecha: las flechas para moverse y girar, coma y punto de
+    /// lado, Enter para usar y Shift derecho para espiar el mapa.
+    pub fn right_racer() -> Self {
+        Self {
+            forward: vec![KeyboardKey::KEY_UP],
+            back: vec![KeyboardKey::KEY_DOWN],
+            strafe_left: vec![KeyboardKey::KEY_COMMA],
+            strafe_right: vec![KeyboardKey::KEY_PERIOD],
+            turn_left: vec![KeyboardKey::KEY_LEFT],
+            turn_right: vec![KeyboardKey::KEY_RIGHT],
+            interact: vec![KeyboardKey::KEY_ENTER],
+            peek_map: vec![KeyboardKey::KEY_RIGHT_SHIFT],
+        }
+    }
+
+    pub fn actions(&self) -> [&Vec<KeyboardKey>; 8] {
+        [
+            &self.forward,
+            &self.back,
+            &self.strafe_left,
+            &self.strafe_right,
+            &self.turn_left,
+            &self.turn_right,
+            &self.interact,
+            &self.peek_map,
+        ]
+    }
+
+    pub fn actions_mut(&mut self) -> [&mut Vec<KeyboardKey>; 8] {
+        [
+            &mut self.forward,
+            &mut self.back,
+            &mut self.strafe_left,
+            &mut self.strafe_right,
+            &mut self.turn_left,
+            &mut self.turn_right,
+            &mut self.interact,
+            &mut self.peek_map,
+        ]
+    }
+}
+
+/// Nombre de cada tecla que se puede asignar, el mismo en el menú y en el archivo de
+/// opciones. ESC queda fuera: siempre pausa y vuelve atrás.
+const KEY_NAMES: [(KeyboardKey, &str); 60] = [
+    (KeyboardKey::KEY_A, "A"),
+    (KeyboardKey::KEY_B, "B"),
+    (KeyboardKey::KEY_C, "C"),
+    (KeyboardKey::KEY_D, "D"),
+    (KeyboardKey::KEY_E, "E"),
+    (KeyboardKey::KEY_F, "F"),
+    (KeyboardKey::KEY_G, "G"),
+    (KeyboardKey::KEY_H, "H"),
+    (KeyboardKey::KEY_I, "I"),
+    (KeyboardKey::KEY_J, "J"),
+    (KeyboardKey::KEY_K, "K"),
+    (KeyboardKey::KEY_L, "L"),
+    (KeyboardKey::KEY_M, "M"),
+    (KeyboardKey::KEY_N, "N"),
+    (KeyboardKey::KEY_O, "O"),
+    (KeyboardKey::KEY_P, "P"),
+    (KeyboardKey::KEY_Q, "Q"),
+    (KeyboardKey::KEY_R, "R"),
+    (KeyboardKey::KEY_S, "S"),
+    (KeyboardKey::KEY_T, "T"),
+    (KeyboardKey::KEY_U, "U"),
+    (KeyboardKey::KEY_V, "V"),
+    (KeyboardKey::KEY_W, "W"),
+    (KeyboardKey::KEY_X, "X"),
+    (KeyboardKey::KEY_Y, "Y"),
+    (KeyboardKey::KEY_Z, "Z"),
+    (KeyboardKey::KEY_ZERO, "0"),
+    (KeyboardKey::KEY_ONE, "1"),
+    (KeyboardKey::KEY_TWO, "2"),
+    (KeyboardKey::KEY_THREE, "3"),
+    (KeyboardKey::KEY_FOUR, "4"),
+    (KeyboardKey::KEY_FIVE, "5"),
+    (KeyboardKey::KEY_SIX, "6"),
+    (KeyboardKey::KEY_SEVEN, "7"),
+    (KeyboardKey::KEY_EIGHT, "8"),
+    (KeyboardKey::KEY_NINE, "9"),
+    (KeyboardKey::KEY_UP, "ARRIBA"),
+    (KeyboardKey::KEY_DOWN, "ABAJO"),
+    (KeyboardKey::KEY_LEFT, "IZQUIERDA"),
+    (KeyboardKey::KEY_RIGHT, "DERECHA"),
+    (KeyboardKey::KEY_SPACE, "ESPACIO"),
+    (KeyboardKey::KEY_TAB, "TAB"),
+    (KeyboardKey::KEY_ENTER, "ENTER"),
+    (KeyboardKey::KEY_BACKSPACE, "RETROCESO"),
+    (KeyboardKey::KEY_LEFT_SHIFT, "SHIFT_IZQ"),
+    (KeyboardKey::KEY_RIGHT_SHIFT, "SHIFT_DER"),
+    (KeyboardKey::KEY_LEFT_CONTROL, "CTRL_IZQ"),
+    (KeyboardKey::KEY_RIGHT_CONTROL, "CTRL_DER"),
+    (KeyboardKey::KEY_LEFT_ALT, "ALT_IZQ"),
+    (KeyboardKey::KEY_RIGHT_ALT, "ALT_DER"),
+    (KeyboardKey::KEY_COMMA, "COMA"),
+    (KeyboardKey::KEY_PERIOD, "PUNTO"),
+    (KeyboardKey::KEY_SEMICOLON, "PUNTO_Y_COMA"),
+    (KeyboardKey::KEY_SLASH, "BARRA"),
+    (KeyboardKey::KEY_PAGE_UP, "REPAG"),
+    (KeyboardKey::KEY_PAGE_DOWN, "AVPAG"),
+    (KeyboardKey::KEY_HOME, "INICIO"),
+    (KeyboardKey::KEY_END, "FIN"),
+    (KeyboardKey::KEY_INSERT, "INSERTAR"),
+    (KeyboardKey::KEY_DELETE, "SUPRIMIR"),
+];
+
+/// Nombre de `key`; `None` si no se puede asignar.
+pub fn key_name(key: KeyboardKey) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, name)| *name)
+}
+
+/// Tecla con el nombre `name`, sin importar mayúsculas.
+pub fn key_from_name(name: &str) -> Option<KeyboardKey> {
+    KEY_NAMES
+        .iter()
+        .find(|(_, n)| n.eq_ignore_ascii_case(name.trim()))
+        .map(|(key, _)| *key)
+}
+
+/// Las teclas de una acción separadas por comas, como se muestran y se guardan.
+pub fn key_list(keys: &[KeyboardKey]) -> String {
+    let names: Vec<&str> = keys.iter().filter_map(|&key| key_name(key)).collect();
+    names.join(", ")
+}
+
+/// Respuesta de un eje de giro: `sensitivity` es el giro con una entrada de referencia y
+/// `exponent` cuánto crece para entradas mayores (1 es lineal; más alto da precisión en
+/// los movimientos chicos y velocidad en los grandes).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Curve {
+    pub sensitivity: f32,
+    pub exponent: f32,
+}
+
+impl Curve {
+    /// Giro para `amount`, medido en múltiplos de `reference`.
+    pub fn apply(&self, amount: f32, reference: f32) -> f32 {
+        let ratio = amount.abs() / reference;
+        amount.signum() * self.sensitivity * reference * ratio.powf(self.exponent)
+    }
+}
+
+/// Teclas, gamepad y respuesta del mouse y los sticks con que se lee la entrada; las
+/// velocidades de movimiento son parte de la simulación (`Movement`).
+#[derive(Clone, Debug)]
+pub struct Controls {
+    pub keys: KeyBindings,
+    /// Radianes por píxel que se mueve el mouse.
+    pub mouse: Curve,
+    /// Radianes por segundo con el stick derecho del todo inclinado.
+    pub stick: Curve,
+    /// Si el cursor queda atrapado en la ventana mientras se juega; sin él, el mouse no
+    /// gira la vista.
+    pub capture_mouse: bool,
+    /// Número del gamepad que se lee, si está conectado.
+    pub gamepad: i32,
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Self {
+            keys: KeyBindings::default(),
+            mouse: Curve {
+                sensitivity: 0.01,
+                exponent: 1.0,
+            },
+            stick: Curve {
+                sensitivity: 3.0,
+                exponent: 2.0,
+            },
+            capture_mouse: true,
+            gamepad: 0,
+        }
+    }
+}
+
+impl Controls {
+    /// Controles de uno de los dos jugadores de una carrera, con el gamepad del mismo
+    /// número; el mouse no gira a ninguno.
+    pub fn racer(keys: KeyBindings, gamepad: i32) -> Self {
+        Self {
+            keys,
+            capture_mouse: false,
+            gamepad,
+            ..Self::default()
+        }
+    }
+}
+
+fn any_down(rl: &RaylibHandle, keys: &[KeyboardKey]) -> bool {
+    keys.iter().any(|&key| rl.is_key_down(key))
+}
+
+fn any_pressed(rl: &RaylibHandle, keys: &[KeyboardKey]) -> bool {
+    keys.iter().any(|&key| rl.is_key_pressed(key))
+}
+
+/// Inclinación de un eje del gamepad sin la zona muerta, de -1 a 1; 0 si no está conectado.
+fn stick(rl: &RaylibHandle, gamepad: i32, axis: GamepadAxis) -> f32 {
+    if !rl.is_gamepad_available(gamepad) {
+        return 0.0;
+    }
+    let value = rl.get_gamepad_axis_movement(gamepad, axis);
+    let magnitude = ((value.abs() - STICK_DEADZONE) / (1.0 - STICK_DEADZONE)).clamp(0.0, 1.0);
+    value.signum() * magnitude
+}
+
+fn button_down(rl: &RaylibHandle, gamepad: i32, button: GamepadButton) -> bool {
+    rl.is_gamepad_available(gamepad) && rl.is_gamepad_button_down(gamepad, button)
+}
+
+fn button_pressed(rl: &RaylibHandle, gamepad: i32, button: GamepadButton) -> bool {
+    rl.is_gamepad_available(gamepad) && rl.is_gamepad_button_pressed(gamepad, button)
+}
+
+/// Lee el teclado, el mouse y el gamepad de este cuadro. El stick izquierdo y la cruceta
+/// mueven, el stick derecho gira, A usa e Y muestra el minimapa.
+pub fn poll(rl: &RaylibHandle, controls: &Controls, delta_time: f32) -> InputFrame {
+    use GamepadButton::*;
+    let keys = &controls.keys;
+    let pad = controls.gamepad;
+    let move_x = stick(rl, pad, GamepadAxis::GAMEPAD_AXIS_LEFT_X);
+    let move_y = stick(rl, pad, GamepadAxis::GAMEPAD_AXIS_LEFT_Y);
+    let mouse = if controls.capture_mouse {
+        controls
+            .mouse
+            .apply(rl.get_mouse_delta().x, MOUSE_REFERENCE)
+    } else {
+        0.0
+    };
+    let turn = stick(rl, pad, GamepadAxis::GAMEPAD_AXIS_RIGHT_X);
+    InputFrame {
+        forward: any_down(rl, &keys.forward)
+            || move_y < -STICK_PRESS
+            || button_down(rl, pad, GAMEPAD_BUTTON_LEFT_FACE_UP),
+        back: any_down(rl, &keys.back)
+            || move_y > STICK_PRESS
+            || button_down(rl, pad, GAMEPAD_BUTTON_LEFT_FACE_DOWN),
+        strafe_left: any_down(rl, &keys.strafe_left) || move_x < -STICK_PRESS,
+        strafe_right: any_down(rl, &keys.strafe_right) || move_x > STICK_PRESS,
+        turn_left: any_down(rl, &keys.turn_left)
+            || button_down(rl, pad, GAMEPAD_BUTTON_LEFT_FACE_LEFT),
+        turn_right: any_down(rl, &keys.turn_right)
+            || button_down(rl, pad, GAMEPAD_BUTTON_LEFT_FACE_RIGHT),
+        interact: any_pressed(rl, &keys.interact)
+            || button_pressed(rl, pad, GAMEPAD_BUTTON_RIGHT_FACE_DOWN),
+        peek_map: any_pressed(rl, &keys.peek_map)
+            || button_pressed(rl, pad, GAMEPAD_BUTTON_RIGHT_FACE_UP),
+        look: mouse + controls.stick.apply(turn, 1.0) * delta_time,
+    }
+}
+
+/// Cuánto mirar hacia arriba (positivo) o abajo en este cuadro: el movimiento vertical del
+/// mouse, el stick derecho y AvPág/RePág. No pasa por la simulación, así que no queda en
+/// las repeticiones.
+pub fn poll_pitch(rl: &RaylibHandle, controls: &Controls, delta_time: f32) -> f32 {
+    let mut pitch = -controls.stick.apply(
+        stick(rl, controls.gamepad, GamepadAxis::GAMEPAD_AXIS_RIGHT_Y),
+        1.0,
+    ) * delta_time;
+    if controls.capture_mouse {
+        pitch -= controls
+            .mouse
+            .apply(rl.get_mouse_delta().y, MOUSE_REFERENCE);
+    }
+    if rl.is_key_down(KeyboardKey::KEY_PAGE_UP) {
+        pitch += PITCH_KEY_SPEED * delta_time;
+    }
+    if rl.is_key_down(KeyboardKey::KEY_PAGE_DOWN) {
+        pitch -= PITCH_KEY_SPEED * delta_time;
+    }
+    pitch
+}
+
+/// Teclas y mouse del editor: 1 a 6 eligen el pincel, clic izquierdo pinta, clic derecho
+/// borra, Ctrl+Z deshace, Ctrl+S guarda y M vuelve a jugar.
+pub fn poll_editor(rl: &RaylibHandle) -> EditorInput {
+    let number_keys = [
+        KeyboardKey::KEY_ONE,
+        KeyboardKey::KEY_TWO,
+        KeyboardKey::KEY_THREE,
+        KeyboardKey::KEY_FOUR,
+        KeyboardKey::KEY_FIVE,
+        KeyboardKey::KEY_SIX,
+    ];
+    let brush = number_keys
+        .into_iter()
+        .zip(Brush::ALL)
+        .find(|&(key, _)| rl.is_key_pressed(key))
+        .map(|(_, brush)| brush);
+    let ctrl = rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+        || rl.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+    let mouse = rl.get_mouse_position();
+    EditorInput {
+        brush,
+        undo: ctrl && rl.is_key_pressed(KeyboardKey::KEY_Z),
+        save: ctrl && rl.is_key_pressed(KeyboardKey::KEY_S),
+        pointer: Vec2::new(mouse.x, mouse.y),
+        paint: rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT),
+        erase: rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_RIGHT),
+        pressed: rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT)
+            || rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT),
+        play: rl.is_key_pressed(KeyboardKey::KEY_M),
+    }
+}
+
+/// ESC, o B y Start en el primer gamepad: pausa y vuelve atrás en los menús.
+pub fn back_pressed(rl: &RaylibHandle) -> bool {
+    rl.is_key_pressed(KeyboardKey::KEY_ESCAPE)
+        || button_pressed(rl, 0, GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT)
+        || button_pressed(rl, 0, GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT)
+}
+
+/// ENTER, o A en el primer gamepad: acepta la opción elegida en los menús.
+pub fn accept_pressed(rl: &RaylibHandle) -> bool {
+    rl.is_key_pressed(KeyboardKey::KEY_ENTER)
+        || button_pressed(rl, 0, GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN)
+}
+
+/// Arriba (-1) o abajo (1) en los menús con las flechas o la cruceta del primer gamepad.
+pub fn menu_step(rl: &RaylibHandle) -> i32 {
+    if rl.is_key_pressed(KeyboardKey::KEY_UP)
+        || button_pressed(rl, 0, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP)
+    {
+        -1
+    } else if rl.is_key_pressed(KeyboardKey::KEY_DOWN)
+        || button_pressed(rl, 0, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN)
+    {
+        1
+    } else {
+        0
+    }
+}
+
+/// Izquierda (-1) o derecha (1) para ajustar una opción, con las flechas o la cruceta.
+pub fn adjust_step(rl: &RaylibHandle) -> i32 {
+    if rl.is_key_pressed(KeyboardKey::KEY_RIGHT)
+        || button_pressed(rl, 0, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT)
+    {
+        1
+    } else if rl.is_key_pressed(KeyboardKey::KEY_LEFT)
+        || button_pressed(rl, 0, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT)
+    {
+        -1
+    } else {
+        0
+    }
+}
+
+/// Atrapa el cursor en la ventana (oculto, y el mouse sólo gira la vista) o lo suelta.
+#[derive(Default)]
+pub struct MouseCapture {
+    captured: bool,
+}
+
+impl MouseCapture {
+    /// Deja el cursor atrapado si `wanted` y suelto si no; sólo llama a raylib al cambiar.
+    pub fn update(&mut self, rl: &mut RaylibHandle, wanted: bool) {
+        if wanted == self.captured {
+            return;
+        }
+        if wanted {
+            rl.disable_cursor();
+        } else {
+            rl.enable_cursor();
+        }
+        self.captured = wanted;
+    }
+}
@@ -0,0 +1,314 @@
+// level.rs
+
+use std::collections::{HashMap, HashSet};
+
+use crate::lighting::Lighting;
+use crate::math::Vec2;
+use crate::maze::{KeyColor, Legend, Maze, Tile};
+use crate::sprite::{Sprite, SpriteKind};
+
+/// Fracción de puerta que se abre por segundo.
+const DOOR_SPEED: f32 = 1.5;
+/// Apertura a partir de la cual se puede cruzar una puerta.
+const DOOR_PASSABLE: f32 = 0.9;
+/// Distancia (en bloques) a la que el jugador alcanza puertas e interruptores.
+const REACH: f32 = 0.9;
+/// Alto de una pared sin altura propia, en bloques.
+pub const WALL_HEIGHT: f32 = 1.0;
+/// Desnivel máximo (en bloques) que se sube caminando; bajar se puede desde cualquier altura.
+const MAX_STEP: f32 = 0.3;
+
+/// Altura de cada celda en bloques (`None` usa la de siempre): el alto de las paredes y
+/// puertas, o la elevación del piso en las celdas libres.
+pub type Heights = Vec<Vec<Option<f32>>>;
+
+/// Cómo ve un rayo una celda.
+pub enum RayCell {
+    Empty,
+    Solid,
+    /// Puerta con la fracción ya deslizada dentro de la pared.
+    Door(f32),
+}
+
+/// Laberinto junto con el estado que cambia al jugar: puertas, llaves e interruptores.
+pub struct Level {
+    pub maze: Maze,
+    pub legend: Legend,
+    /// Copia sin modificar para reiniciar el nivel.
+    initial: Maze,
+    /// Apertura (0 a 1) de las puertas que el jugador activó.
+    doors: HashMap<(usize, usize), f32>,
+    pub keys: Vec<KeyColor>,
+    /// Caracteres de muros móviles que un interruptor dejó abiertos.
+    open_groups: HashSet<char>,
+    /// Celda donde empieza el jugador.
+    pub start: (usize, usize),
+    heights: Heights,
+    /// Niebla y mapa de luz; se calcula una vez que el nivel está armado.
+    pub lighting: Lighting,
+}
+
+impl Level {
+    /// Crea el nivel con el jugador en la primera celda libre (de arriba a abajo).
+    pub fn new(maze: Maze, legend: Legend) -> Self {
+        let start = maze
+            .iter()
+            .enumerate()
+            .find_map(|(j, row)| row.iter().position(|&c| c == ' ').map(|i| (i, j)))
+            .unwrap_or((1, 1));
+        Self {
+            initial: maze.clone(),
+            maze,
+            legend,
+            doors: HashMap::new(),
+            keys: Vec::new(),
+            open_groups: HashSet::new(),
+            start,
+            heights: Heights::new(),
+            lighting: Lighting::default(),
+        }
+    }
+
+    pub fn with_start(mut self, start: (usize, usize)) -> Self {
+        self.start = start;
+        self
+    }
+
+    pub fn with_heights(mut self, heights: Heights) -> Self {
+        self.heights = heights;
+        self
+    }
+
+    /// Centro de la celda inicial en coordenadas del mundo.
+    pub fn start_position(&self, block_size: usize) -> Vec2 {
+        Vec2::new(
+            (self.start.0 as f32 + 0.5) * block_size as f32,
+            (self.start.1 as f32 + 0.5) * block_size as f32,
+        )
+    }
+
+    /// Centro de la meta en coordenadas del mundo, si el laberinto tiene una.
+    pub fn goal_position(&self, block_size: usize) -> Option<Vec2> {
+        self.maze.iter().enumerate().find_map(|(j, row)| {
+            let i = row
+                .iter()
+                .position(|&c| self.legend.tile(c) == Tile::Goal)?;
+            Some(Vec2::new(
+                (i as f32 + 0.5) * block_size as f32,
+                (j as f32 + 0.5) * block_size as f32,
+            ))
+        })
+    }
+
+    /// Indica si `pos` está a menos de un bloque del centro de la meta.
+    pub fn reached_goal(&self, pos: Vec2, block_size: usize) -> bool {
+        self.goal_position(block_size)
+            .is_some_and(|goal| pos.distance_to(goal) < block_size as f32)
+    }
+
+    /// Vuelve al estado inicial: puertas cerradas, llaves en su lugar y muros en su sitio.
+    pub fn reset(&mut self) {
+        self.maze = self.initial.clone();
+        self.doors.clear();
+        self.keys.clear();
+        self.open_groups.clear();
+    }
+
+    /// Reemplaza el laberinto (también el de reinicio) y cierra puertas y pasajes.
+    pub fn set_maze(&mut self, maze: Maze) {
+        self.initial = maze.clone();
+        self.maze = maze;
+        self.reset();
+    }
+
+    /// Cambia una celda del laberinto de forma permanente, como lo hace el editor.
+    pub fn set_cell(&mut self, i: usize, j: usize, cell: char) {
+        self.initial[j][i] = cell;
+        self.maze[j][i] = cell;
+    }
+
+    /// Carácter en `(i, j)`; fuera del laberinto cuenta como pared.
+    pub fn cell(&self, i: usize, j: usize) -> char {
+        self.maze
+            .get(j)
+            .and_then(|row| row.get(i))
+            .copied()
+            .unwrap_or('+')
+    }
+
+    pub fn tile(&self, i: usize, j: usize) -> Tile {
+        self.legend.tile(self.cell(i, j))
+    }
+
+    /// Indica si `(i, j)` está dentro del laberinto.
+    pub fn contains(&self, i: usize, j: usize) -> bool {
+        self.maze.get(j).is_some_and(|row| i < row.len())
+    }
+
+    fn height(&self, i: usize, j: usize) -> Option<f32> {
+        self.heights.get(j)?.get(i).copied().flatten()
+    }
+
+    /// Alto (en bloques) de la pared o puerta en `(i, j)`, medido desde el suelo.
+    pub fn wall_height(&self, i: usize, j: usize) -> f32 {
+        self.height(i, j).unwrap_or(WALL_HEIGHT)
+    }
+
+    /// Elevación del piso en `(i, j)`, en bloques. Sólo las celdas libres se elevan; un
+    /// muro móvil abierto o una puerta quedan a nivel del suelo.
+    pub fn floor_height(&self, i: usize, j: usize) -> f32 {
+        match self.tile(i, j) {
+            Tile::Empty | Tile::Key(_) => self.height(i, j).unwrap_or(0.0),
+            _ => 0.0,
+        }
+    }
+
+    /// Elevación del piso bajo `pos`.
+    pub fn floor_at(&self, pos: Vec2, block_size: usize) -> f32 {
+        let block = block_size as f32;
+        if pos.x < 0.0 || pos.y < 0.0 {
+            return 0.0;
+        }
+        self.floor_height((pos.x / block) as usize, (pos.y / block) as usize)
+    }
+
+    /// Lo más alto del nivel, sea pared o piso; más allá de eso sólo se ve cielo.
+    pub fn max_height(&self) -> f32 {
+        self.heights
+            .iter()
+            .flatten()
+            .flatten()
+            .fold(WALL_HEIGHT, |max, &h| max.max(h))
+    }
+
+    /// Indica si se puede pasar de la celda `from` a la vecina `to`: tiene que ser
+    /// transitable y no estar más de un escalón por encima.
+    pub fn can_step(&self, from: (usize, usize), to: (usize, usize)) -> bool {
+        self.is_passable(to.0, to.1)
+            && self.floor_height(to.0, to.1) <= self.floor_height(from.0, from.1) + MAX_STEP
+    }
+
+    pub fn door_open(&self, i: usize, j: usize) -> f32 {
+        self.doors.get(&(i, j)).copied().unwrap_or(0.0)
+    }
+
+    pub fn group_open(&self, target: char) -> bool {
+        self.open_groups.contains(&target)
+    }
+
+    pub fn ray_cell(&self, i: usize, j: usize) -> RayCell {
+        match self.tile(i, j) {
+            Tile::Empty | Tile::Key(_) => RayCell::Empty,
+            Tile::MovingWall { .. } if self.group_open(self.cell(i, j)) => RayCell::Empty,
+            Tile::Door { .. } => RayCell::Door(self.door_open(i, j)),
+            _ => RayCell::Solid,
+        }
+    }
+
+    /// Indica si el jugador (o un rival) puede ocupar la celda.
+    pub fn is_passable(&self, i: usize, j: usize) -> bool {
+        match self.ray_cell(i, j) {
+            RayCell::Empty => true,
+            RayCell::Door(open) => open >= DOOR_PASSABLE,
+            RayCell::Solid => false,
+        }
+    }
+
+    /// Avanza la animación de las puertas que se están abriendo.
+    pub fn update(&mut self, delta_time: f32) {
+        for open in self.doors.values_mut() {
+            *open = (*open + DOOR_SPEED * delta_time).min(1.0);
+        }
+    }
+
+    /// Usa la puerta o el interruptor frente al jugador y devuelve un mensaje para el HUD.
+    pub fn interact(&mut self, pos: Vec2, angle: f32, block_size: usize) -> Option<String> {
+        let reach = REACH * block_size as f32;
+        let x = pos.x + angle.cos() * reach;
+        let y = pos.y + angle.sin() * reach;
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+        let cell = (
+            (x / block_size as f32) as usize,
+            (y / block_size as f32) as usize,
+        );
+        match self.tile(cell.0, cell.1) {
+            Tile::Door { key } => {
+                if self.doors.contains_key(&cell) {
+                    return None;
+                }
+                if let Some(color) = key
+                    && !self.keys.contains(&color)
+                {
+                    return Some(format!("Necesitas la llave {}", color.name()));
+                }
+                self.doors.insert(cell, 0.0);
+                None
+            }
+            Tile::Switch { target } => {
+                if !self.open_groups.remove(&target) {
+                    self.open_groups.insert(target);
+                    return Some("Se abrió un pasaje".to_string());
+                }
+                Some("Se cerró un pasaje".to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// Recoge la llave de la celda en `pos`, si hay una.
+    pub fn pick_up_key(&mut self, pos: Vec2, block_size: usize) -> Option<KeyColor> {
+        let i = (pos.x / block_size as f32) as usize;
+        let j = (pos.y / block_size as f32) as usize;
+        let Tile::Key(color) = self.tile(i, j) else {
+            return None;
+        };
+        self.maze[j][i] = ' ';
+        self.keys.push(color);
+        Some(color)
+    }
+
+    /// Sprites de las llaves que siguen en el piso.
+    pub fn key_sprites(&self, block_size: usize) -> Vec<Sprite> {
+        let mut sprites = Vec::new();
+        for (j, row) in self.maze.iter().enumerate() {
+            for (i, &cell) in row.iter().enumerate() {
+                if let Tile::Key(color) = self.legend.tile(cell) {
+                    sprites.push(Sprite {
+                        pos: Vec2::new(
+                            (i as f32 + 0.5) * block_size as f32,
+                            (j as f32 + 0.5) * block_size as f32,
+                        ),
+                        kind: SpriteKind::Key(color),
+                    });
+                }
+            }
+        }
+        sprites
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::test_level;
+
+    const BLOCK: usize = 100;
+
+    #[test]
+    fn reached_goal_within_one_block_of_its_center() {
+        let level = test_level(&["+++++", "+  g+", "+++++"], &[]);
+        assert_eq!(level.goal_position(BLOCK), Some(Vec2::new(350.0, 150.0)));
+        assert!(level.reached_goal(Vec2::new(350.0, 150.0), BLOCK));
+        assert!(level.reached_goal(Vec2::new(260.0, 150.0), BLOCK));
+        assert!(!level.reached_goal(Vec2::new(250.0, 150.0), BLOCK));
+        assert!(!level.reached_goal(Vec2::new(150.0, 150.0), BLOCK));
+    }
+
+    #[test]
+    fn no_goal_is_never_reached() {
+        let level = test_level(&["+++++", "+   +", "+++++"], &[]);
+        assert!(!level.reached_goal(Vec2::new(350.0, 150.0), BLOCK));
+    }
+}
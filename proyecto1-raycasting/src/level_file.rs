@@ -0,0 +1,622 @@
+// level_file.rs
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::color::Rgba;
+use crate::level::Heights;
+use crate::lighting::{Fog, Light, TORCH_COLOR};
+use crate::maze::{Legend, Maze, Tile, column_of};
+use crate::minimap::MinimapMode;
+
+/// Versión del formato que entiende este lector (primera línea `nivel 1`).
+pub const FORMAT_VERSION: u32 = 1;
+/// Carpeta de la campaña; sus niveles se juegan en orden alfabético de archivo.
+pub const CAMPAIGN_DIR: &str = "assets/niveles";
+
+const MAP_HEADER: &str = "[mapa]";
+const LEGEND_HEADER: &str = "[leyenda]";
+const HEIGHTS_HEADER: &str = "[alturas]";
+/// Cada dígito de `[alturas]` son cuartos de bloque.
+const HEIGHT_STEPS: f32 = 4.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntityKind {
+    /// Copa coleccionable.
+    Cup,
+    /// Punto de aparición de un rival.
+    Enemy,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Entity {
+    pub kind: EntityKind,
+    pub cell: (usize, usize),
+}
+
+/// Texturas que un nivel puede reemplazar; `None` usa la del juego.
+#[derive(Clone, Debug, Default)]
+pub struct LevelTextures {
+    pub floor: Option<String>,
+    pub sky: Option<String>,
+    pub goal: Option<String>,
+}
+
+/// Nivel leído de un archivo: mapa, leyenda y los datos de la cabecera.
+#[derive(Clone)]
+pub struct LevelFile {
+    pub name: String,
+    pub author: Option<String>,
+    pub maze: Maze,
+    pub legend: Legend,
+    /// Alto de las paredes y elevación del piso por celda; vacío si el nivel es plano.
+    pub heights: Heights,
+    /// Celda inicial; sin ella se usa la primera celda libre.
+    pub start: Option<(usize, usize)>,
+    /// Ángulo inicial en radianes.
+    pub angle: Option<f32>,
+    /// Segundos para llegar a la meta.
+    pub time_limit: Option<f32>,
+    /// Cuándo se puede ver el minimapa; sin ella decide la dificultad.
+    pub minimap: Option<MinimapMode>,
+    /// Antorchas u otras luces puntuales.
+    pub lights: Vec<Light>,
+    /// Luz ambiente (1 es plena); sin ella es plena salvo que haya luces.
+    pub ambient: Option<f32>,
+    pub fog: Option<Fog>,
+    /// Sonido de fondo que se repite durante el nivel; sin él se genera viento.
+    pub sound: Option<String>,
+    pub textures: LevelTextures,
+    /// Copas y rivales fijos; si no hay, el juego los reparte al azar.
+    pub entities: Vec<Entity>,
+    /// Archivo del que se leyó; `None` si el nivel no viene de disco.
+    pub path: Option<PathBuf>,
+}
+
+impl LevelFile {
+    /// Nivel sin datos de cabecera: empieza en la primera celda libre, sin límite de tiempo.
+    pub fn new(name: &str, maze: Maze, legend: Legend) -> Self {
+        Self {
+            name: name.to_string(),
+            author: None,
+            maze,
+            legend,
+            heights: Heights::new(),
+            start: None,
+            angle: None,
+            time_limit: None,
+            minimap: None,
+            lights: Vec::new(),
+            ambient: None,
+            fog: None,
+            sound: None,
+            textures: LevelTextures::default(),
+            entities: Vec::new(),
+            path: None,
+        }
+    }
+
+    /// Celdas de las entidades de un tipo.
+    pub fn cells(&self, kind: EntityKind) -> Vec<(usize, usize)> {
+        self.entities
+            .iter()
+            .filter(|e| e.kind == kind)
+            .map(|e| e.cell)
+            .collect()
+    }
+
+    /// Texto del nivel en el formato completo (`nivel 1`), tal como lo lee `parse_level`.
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![
+            format!("nivel {}", FORMAT_VERSION),
+            format!("nombre: {}", self.name),
+        ];
+        if let Some(author) = &self.author {
+            lines.push(format!("autor: {}", author));
+        }
+        if let Some((x, y)) = self.start {
+            lines.push(format!("inicio: {} {}", x, y));
+        }
+        if let Some(angle) = self.angle {
+            // Redondeado a centésimas para no guardar el error de convertir a radianes.
+            lines.push(format!(
+                "angulo: {}",
+                (angle.to_degrees() * 100.0).round() / 100.0
+            ));
+        }
+        if let Some(limit) = self.time_limit {
+            lines.push(format!("tiempo: {}", limit));
+        }
+        if let Some(mode) = self.minimap {
+            lines.push(format!("minimapa: {}", mode.name()));
+        }
+        if let Some(ambient) = self.ambient {
+            lines.push(format!("ambiente: {}", ambient));
+        }
+        if let Some(fog) = self.fog {
+            let c = fog.color;
+            lines.push(format!("niebla: {} {} {} {}", c.r, c.g, c.b, fog.distance));
+        }
+        for (key, path) in [
+            ("piso", &self.textures.floor),
+            ("cielo", &self.textures.sky),
+            ("meta", &self.textures.goal),
+            ("sonido", &self.sound),
+        ] {
+            if let Some(path) = path {
+                lines.push(format!("{}: {}", key, path));
+            }
+        }
+        for entity in &self.entities {
+            let key = match entity.kind {
+                EntityKind::Cup => "copa",
+                EntityKind::Enemy => "rival",
+            };
+            lines.push(format!("{}: {} {}", key, entity.cell.0, entity.cell.1));
+        }
+        for light in &self.lights {
+            let (x, y) = light.cell;
+            let c = light.color;
+            lines.push(format!("luz: {} {} {} {} {}", x, y, c.r, c.g, c.b));
+        }
+        lines.push(MAP_HEADER.to_string());
+        lines.extend(self.maze.iter().map(|row| row.iter().collect::<String>()));
+        if self.heights.iter().flatten().any(Option::is_some) {
+            lines.push(HEIGHTS_HEADER.to_string());
+            lines.extend(self.heights.iter().map(|row| {
+                row.iter()
+                    .map(|h| match h {
+                        Some(h) => {
+                            char::from_digit((h * HEIGHT_STEPS).round() as u32, 10).unwrap_or('9')
+                        }
+                        None => '.',
+                    })
+                    .collect::<String>()
+            }));
+        }
+        let legend = self.legend.entries();
+        if !legend.is_empty() {
+            lines.push(LEGEND_HEADER.to_string());
+            lines.extend(legend);
+        }
+        lines.join("\n") + "\n"
+    }
+}
+
+/// Error de lectura con su posición en el archivo (línea y columna desde 1).
+#[derive(Debug)]
+pub struct LevelError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "línea {}, columna {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+fn error(line: usize, column: usize, message: impl Into<String>) -> LevelError {
+    LevelError {
+        line,
+        column,
+        message: message.into(),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Header,
+    Map,
+    Heights,
+    Legend,
+}
+
+/// Lee una fila de `[alturas]`: un dígito de 0 a 9 (en cuartos de bloque) o `.` para la
+/// altura de siempre.
+fn parse_heights(line: &str, number: usize) -> Result<Vec<Option<f32>>, LevelError> {
+    line.chars()
+        .enumerate()
+        .map(|(index, c)| match c {
+            '.' | ' ' => Ok(None),
+            _ => c
+                .to_digit(10)
+                .map(|d| Some(d as f32 / HEIGHT_STEPS))
+                .ok_or_else(|| {
+                    error(
+                        number,
+                        index + 1,
+                        format!("`{}` no es una altura (dígito de 0 a 9 o `.`)", c),
+                    )
+                }),
+        })
+        .collect()
+}
+
+/// Lee `x y` como celda.
+fn parse_cell(text: &str, line: usize, column: usize) -> Result<(usize, usize), LevelError> {
+    let numbers: Vec<usize> = text
+        .split_whitespace()
+        .map(|n| n.parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| error(line, column, format!("`{}` no es una celda `x y`", text)))?;
+    match numbers[..] {
+        [x, y] => Ok((x, y)),
+        _ => Err(error(
+            line,
+            column,
+            format!("se esperaban dos números `x y`, no `{}`", text),
+        )),
+    }
+}
+
+/// Lee `R G B` (de 0 a 255).
+fn parse_color(words: &[&str], line: usize, column: usize) -> Result<Rgba, LevelError> {
+    let channels: Vec<u8> = words
+        .iter()
+        .map(|w| w.parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| error(line, column, "los colores son tres números de 0 a 255"))?;
+    match channels[..] {
+        [r, g, b] => Ok(Rgba::new(r, g, b, 255)),
+        _ => Err(error(line, column, "se esperaba un color `R G B`")),
+    }
+}
+
+fn parse_number(text: &str, line: usize, column: usize) -> Result<f32, LevelError> {
+    text.parse()
+        .map_err(|_| error(line, column, format!("`{}` no es un número", text)))
+}
+
+/// Interpreta el texto de un nivel. Si la primera línea es `nivel N` se lee la cabecera
+/// completa; si no, el archivo es una cuadrícula simple con una `[leyenda]` opcional.
+///
+/// Formato completo:
+///
+/// ```text
+/// nivel 1
+/// nombre: Puertas y llaves
+/// autor: Alguien
+/// inicio: 1 1
+/// angulo: 90
+/// tiempo: 180
+/// minimapa: limitado 3
+/// ambiente: 0.3
+/// niebla: 20 20 40 1200
+/// luz: 5 1 255 170 90
+/// piso: assets/texturas/floor.jpg
+/// sonido: assets/sonidos/viento.ogg
+/// copa: 11 3
+/// rival: 15 9
+/// [mapa]
+/// +-----+
+/// |    g|
+/// +-----+
+/// [alturas]
+/// .2...
+/// ..1..
+/// [leyenda]
+/// D = puerta
+/// ```
+///
+/// La sección `[alturas]`, opcional, sigue la forma del mapa: en las paredes y puertas el
+/// dígito es su alto y en el piso su elevación, ambos en cuartos de bloque (`4` es una
+/// pared normal, `2` un muro que se puede mirar por encima). `.` deja la altura de siempre
+/// y las filas o columnas que faltan también.
+pub fn parse_level(text: &str, default_name: &str) -> Result<LevelFile, LevelError> {
+    let mut level = LevelFile::new(default_name, Maze::new(), Legend::default());
+    // Posición de cada celda declarada en la cabecera, para señalarla si no es válida.
+    let mut start_at = (0, 0);
+    let mut entities_at = Vec::new();
+    let mut lights_at = Vec::new();
+    let mut rows: Vec<(usize, Vec<char>)> = Vec::new();
+    let mut height_rows: Vec<(usize, Vec<Option<f32>>)> = Vec::new();
+    let mut map_line = 1;
+    let mut section = Section::Map;
+
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        let trimmed = line.trim();
+
+        if number == 1
+            && let Some(version) = trimmed.strip_prefix("nivel ")
+        {
+            let column = column_of(line, line.len() - version.len());
+            let version: u32 = version
+                .trim()
+                .parse()
+                .map_err(|_| error(number, column, "versión inválida"))?;
+            if version != FORMAT_VERSION {
+                return Err(error(
+                    number,
+                    column,
+                    format!(
+                        "versión {} no soportada (se espera {})",
+                        version, FORMAT_VERSION
+                    ),
+                ));
+            }
+            section = Section::Header;
+            continue;
+        }
+        if trimmed == MAP_HEADER && section != Section::Map {
+            section = Section::Map;
+            map_line = number + 1;
+            continue;
+        }
+        if trimmed == LEGEND_HEADER {
+            section = Section::Legend;
+            continue;
+        }
+        if trimmed == HEIGHTS_HEADER {
+            section = Section::Heights;
+            continue;
+        }
+
+        match section {
+            Section::Map => rows.push((number, line.chars().collect())),
+            Section::Heights => height_rows.push((number, parse_heights(line, number)?)),
+            Section::Legend => {
+                if trimmed.is_empty() {
+                    continue;
+                }
+                let before = level.legend.textures.len();
+                level
+                    .legend
+                    .parse_entry(line)
+                    .map_err(|(column, message)| error(number, column, message))?;
+                if level.legend.textures.len() > before {
+                    let path = &level.legend.textures[before];
+                    if !Path::new(path).exists() {
+                        let column = line.rfind(path.as_str()).map_or(1, |i| column_of(line, i));
+                        return Err(error(number, column, format!("no existe `{}`", path)));
+                    }
+                }
+            }
+            Section::Header => {
+                if trimmed.is_empty() || trimmed.starts_with("//") {
+                    continue;
+                }
+                let Some((key, value)) = line.split_once(':') else {
+                    return Err(error(number, 1, "se esperaba `clave: valor`"));
+                };
+                let key_column = column_of(line, key.len() - key.trim_start().len());
+                let value_start = line.len() - value.trim_start().len();
+                let column = column_of(line, value_start);
+                let value = value.trim();
+                match key.trim() {
+                    "nombre" => level.name = value.to_string(),
+                    "autor" => level.author = Some(value.to_string()),
+                    "inicio" => {
+                        level.start = Some(parse_cell(value, number, column)?);
+                        start_at = (number, column);
+                    }
+                    "angulo" => {
+                        level.angle = Some(parse_number(value, number, column)?.to_radians())
+                    }
+                    "tiempo" => {
+                        let limit = parse_number(value, number, column)?;
+                        if limit <= 0.0 {
+                            return Err(error(number, column, "el tiempo debe ser positivo"));
+                        }
+                        level.time_limit = Some(limit);
+                    }
+                    "luz" => {
+                        // `x y` y, opcionalmente, `R G B`.
+                        let words: Vec<&str> = value.split_whitespace().collect();
+                        if words.len() != 2 && words.len() != 5 {
+                            return Err(error(number, column, "se esperaba `x y [R G B]`"));
+                        }
+                        let cell = parse_cell(&words[..2].join(" "), number, column)?;
+                        let color = match words.len() {
+                            5 => parse_color(&words[2..], number, column)?,
+                            _ => TORCH_COLOR,
+                        };
+                        level.lights.push(Light { cell, color });
+                        lights_at.push((number, column));
+                    }
+                    "ambiente" => {
+                        let ambient = parse_number(value, number, column)?;
+                        if ambient < 0.0 {
+                            return Err(error(
+                                number,
+                                column,
+                                "la luz ambiente no puede ser negativa",
+                            ));
+                        }
+                        level.ambient = Some(ambient);
+                    }
+                    "niebla" => {
+                        // `R G B` y, opcionalmente, la distancia en píxeles.
+                        let words: Vec<&str> = value.split_whitespace().collect();
+                        let distance = match words.len() {
+                            3 => Fog::default().distance,
+                            4 => parse_number(words[3], number, column)?,
+                            _ => {
+                                return Err(error(
+                                    number,
+                                    column,
+                                    "se esperaba `R G B [distancia]`",
+                                ));
+                            }
+                        };
+                        if distance <= 0.0 {
+                            return Err(error(number, column, "la distancia debe ser positiva"));
+                        }
+                        level.fog = Some(Fog {
+                            color: parse_color(&words[..3], number, column)?,
+                            distance,
+                        });
+                    }
+                    "minimapa" => {
+                        level.minimap = Some(MinimapMode::parse(value).ok_or_else(|| {
+                            error(
+                                number,
+                                column,
+                                format!("`{}` no es `siempre`, `oculto` ni `limitado N`", value),
+                            )
+                        })?);
+                    }
+                    key @ ("piso" | "cielo" | "meta" | "sonido") => {
+                        if !Path::new(value).exists() {
+                            return Err(error(number, column, format!("no existe `{}`", value)));
+                        }
+                        let slot = match key {
+                            "piso" => &mut level.textures.floor,
+                            "cielo" => &mut level.textures.sky,
+                            "meta" => &mut level.textures.goal,
+                            _ => &mut level.sound,
+                        };
+                        *slot = Some(value.to_string());
+                    }
+                    key @ ("copa" | "rival") => {
+                        let kind = if key == "copa" {
+                            EntityKind::Cup
+                        } else {
+                            EntityKind::Enemy
+                        };
+                        let cell = parse_cell(value, number, column)?;
+                        level.entities.push(Entity { kind, cell });
+                        entities_at.push((number, column));
+                    }
+                    other => {
+                        return Err(error(
+                            number,
+                            key_column,
+                            format!("clave desconocida `{}`", other),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    // Las líneas vacías al final del mapa no son filas.
+    while rows.last().is_some_and(|(_, row)| row.is_empty()) {
+        rows.pop();
+    }
+    let Some((first_line, first_row)) = rows.first() else {
+        return Err(error(map_line, 1, "el nivel no tiene mapa"));
+    };
+    let width = first_row.len();
+    for (number, row) in &rows {
+        if row.len() != width {
+            return Err(error(
+                *number,
+                row.len().min(width) + 1,
+                format!("la fila mide {} columnas y la primera {}", row.len(), width),
+            ));
+        }
+    }
+    let has_goal = rows
+        .iter()
+        .any(|(_, row)| row.iter().any(|&c| level.legend.tile(c) == Tile::Goal));
+    if !has_goal {
+        return Err(error(*first_line, 1, "el mapa no tiene meta"));
+    }
+    level.maze = rows.into_iter().map(|(_, row)| row).collect();
+
+    while height_rows.last().is_some_and(|(_, row)| row.is_empty()) {
+        height_rows.pop();
+    }
+    if let Some((number, _)) = height_rows.get(level.maze.len()) {
+        return Err(error(*number, 1, "`[alturas]` tiene más filas que el mapa"));
+    }
+    for (number, row) in &height_rows {
+        if row.len() > width {
+            return Err(error(
+                *number,
+                width + 1,
+                format!(
+                    "la fila de alturas mide {} columnas y el mapa {}",
+                    row.len(),
+                    width
+                ),
+            ));
+        }
+    }
+    level.heights = height_rows.into_iter().map(|(_, row)| row).collect();
+
+    let is_free = |(x, y): (usize, usize)| {
+        level
+            .maze
+            .get(y)
+            .and_then(|row| row.get(x))
+            .is_some_and(|&c| level.legend.tile(c) == Tile::Empty)
+    };
+    if let Some(start) = level.start
+        && !is_free(start)
+    {
+        return Err(error(
+            start_at.0,
+            start_at.1,
+            format!("la celda inicial {:?} no es piso libre", start),
+        ));
+    }
+    let cells = level.entities.iter().map(|e| e.cell);
+    let positions = entities_at.iter().chain(&lights_at);
+    for (cell, &(line, column)) in cells
+        .chain(level.lights.iter().map(|l| l.cell))
+        .zip(positions)
+    {
+        if !is_free(cell) {
+            return Err(error(
+                line,
+                column,
+                format!("la celda {:?} no es piso libre", cell),
+            ));
+        }
+    }
+    Ok(level)
+}
+
+/// Lee un archivo de nivel; el error trae `archivo:línea:columna`.
+pub fn load_level(path: &Path) -> Result<LevelFile, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    // Sin `nombre:` se usa el del archivo, sin el prefijo numérico que fija el orden.
+    let stem = path
+        .file_stem()
+        .map_or("Nivel".to_string(), |s| s.to_string_lossy().into_owned());
+    let default_name = stem
+        .trim_start_matches(|c: char| c.is_ascii_digit())
+        .trim_start_matches('_')
+        .replace('_', " ");
+    let mut level = parse_level(&text, &default_name)
+        .map_err(|e| format!("{}:{}:{}: {}", path.display(), e.line, e.column, e.message))?;
+    level.path = Some(path.to_path_buf());
+    Ok(level)
+}
+
+/// Lee todos los `.txt` de `dir` en orden alfabético. Los niveles con errores se
+/// informan por la consola y se omiten.
+pub fn load_campaign(dir: &str) -> Vec<LevelFile> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("No se pudo abrir la campaña {}: {}", dir, e);
+            return Vec::new();
+        }
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    paths.sort();
+
+    paths
+        .iter()
+        .filter_map(|path| match load_level(path) {
+            Ok(level) => Some(level),
+            Err(e) => {
+                eprintln!("Nivel omitido: {}", e);
+                None
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,26 @@
+// lib.rs
+
+// Núcleo del juego, sin raylib: geometría, laberinto, jugador y colisiones, raycasting,
+// simulación de la partida, editor de niveles y dibujo por software en un framebuffer. La
+// ventana, la entrada, el sonido y la carga de imágenes los pone el front end (main.rs).
+pub mod caster;
+pub mod color;
+pub mod editor;
+pub mod enemy;
+pub mod framebuffer;
+pub mod generator;
+pub mod level;
+pub mod level_file;
+pub mod lighting;
+pub mod line;
+pub mod math;
+pub mod maze;
+pub mod minimap;
+pub mod player;
+pub mod render;
+pub mod replay;
+pub mod scores;
+pub mod session;
+pub mod sprite;
+pub mod textures;
+pub mod timing;
@@ -0,0 +1,247 @@
+// lighting.rs
+
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+
+use crate::caster::cast_ray_from;
+use crate::color::Rgba;
+use crate::level::Level;
+use crate::math::Vec2;
+use crate::maze::Tile;
+use crate::sprite::{Sprite, SpriteKind};
+
+/// Brillo de las paredes norte/sur respecto de las este/oeste, como en Wolfenstein 3D.
+pub const SIDE_SHADE: f32 = 0.7;
+/// Fracción máxima de niebla: lo más lejano conserva un 25 % de su color.
+const MAX_FOG: f32 = 0.75;
+/// Alcance de una luz en celdas.
+const LIGHT_RADIUS: f32 = 4.5;
+/// Brillo que agrega una luz en su propia celda.
+const LIGHT_INTENSITY: f32 = 1.4;
+/// Color de las antorchas que no indican otro.
+pub const TORCH_COLOR: Rgba = Rgba::new(255, 170, 90, 255);
+/// Luz ambiente de un nivel con antorchas que no la define.
+pub const DARK_AMBIENT: f32 = 0.35;
+/// Separación mínima (en celdas, sumando ambos ejes) entre antorchas repartidas al azar.
+const TORCH_SPACING: usize = 4;
+
+/// Niebla hacia la que se funde lo lejano.
+#[derive(Clone, Copy, Debug)]
+pub struct Fog {
+    pub color: Rgba,
+    /// Distancia (en píxeles del mundo) a la que la niebla llega a su máximo.
+    pub distance: f32,
+}
+
+impl Default for Fog {
+    /// Oscuridad que llega al máximo a 1500 píxeles, el sombreado de siempre.
+    fn default() -> Self {
+        Self {
+            color: Rgba::BLACK,
+            distance: 1500.0,
+        }
+    }
+}
+
+/// Luz puntual en el centro de una celda.
+#[derive(Clone, Copy, Debug)]
+pub struct Light {
+    pub cell: (usize, usize),
+    pub color: Rgba,
+}
+
+/// Iluminación del nivel: niebla y un mapa de luz por celda calculado al cargarlo.
+#[derive(Clone)]
+pub struct Lighting {
+    pub fog: Fog,
+    pub lights: Vec<Light>,
+    /// Luz RGB de cada celda libre (`None` en las paredes). Vacío si todo el nivel recibe
+    /// sólo la luz ambiente.
+    cells: Vec<Vec<Option<[f32; 3]>>>,
+    ambient: f32,
+    /// Tamaño de celda del mundo, para consultar la luz en píxeles.
+    block: f32,
+}
+
+impl Default for Lighting {
+    /// Sin luces y con luz ambiente plena: sólo la niebla oscurece.
+    fn default() -> Self {
+        Self {
+            fog: Fog::default(),
+            lights: Vec::new(),
+            cells: Vec::new(),
+            ambient: 1.0,
+            block: 1.0,
+        }
+    }
+}
+
+impl Lighting {
+    /// Calcula el mapa de luz: cada celda libre recibe la luz ambiente más la de cada fuente
+    /// que la ve directamente, atenuada con la distancia. Puertas cerradas y muros móviles
+    /// tapan la luz tal como están al cargar.
+    pub fn new(
+        level: &Level,
+        block_size: usize,
+        lights: Vec<Light>,
+        ambient: f32,
+        fog: Fog,
+    ) -> Self {
+        let block = block_size as f32;
+        if lights.is_empty() {
+            return Self {
+                fog,
+                lights,
+                cells: Vec::new(),
+                ambient,
+                block,
+            };
+        }
+        let mut cells: Vec<Vec<Option<[f32; 3]>>> = level
+            .maze
+            .iter()
+            .enumerate()
+            .map(|(j, row)| {
+                (0..row.len())
+                    .map(|i| level.is_passable(i, j).then_some([ambient; 3]))
+                    .collect()
+            })
+            .collect();
+
+        let reach = LIGHT_RADIUS.ceil() as usize;
+        for light in &lights {
+            let (li, lj) = light.cell;
+            let origin = Vec2::new(li as f32 + 0.5, lj as f32 + 0.5);
+            let tint = [light.color.r, light.color.g, light.color.b].map(|c| c as f32 / 255.0);
+            for j in lj.saturating_sub(reach)..=lj + reach {
+                for i in li.saturating_sub(reach)..=li + reach {
+                    let Some(Some(cell)) = cells.get_mut(j).and_then(|row| row.get_mut(i)) else {
+                        continue;
+                    };
+                    let (dx, dy) = (i as f32 + 0.5 - origin.x, j as f32 + 0.5 - origin.y);
+                    let distance = dx.hypot(dy);
+                    if distance > LIGHT_RADIUS {
+                        continue;
+                    }
+                    // Un rayo en unidades de celda (bloque de 1) comprueba que no haya paredes.
+                    let a = dy.atan2(dx);
+                    if distance > 0.0 && cast_ray_from(level, origin, a, a, 1).distance < distance {
+                        continue;
+                    }
+                    let falloff = (1.0 - distance / LIGHT_RADIUS).powi(2) * LIGHT_INTENSITY;
+                    for (c, t) in cell.iter_mut().zip(tint) {
+                        *c += falloff * t;
+                    }
+                }
+            }
+        }
+        Self {
+            fog,
+            lights,
+            cells,
+            ambient,
+            block,
+        }
+    }
+
+    /// Luz RGB en un punto del mundo, interpolada entre los centros de las celdas libres
+    /// vecinas para que no se noten los bordes de cada celda. Las paredes no cuentan, así
+    /// que la luz no atraviesa muros.
+    pub fn at(&self, x: f32, y: f32) -> [f32; 3] {
+        if self.cells.is_empty() {
+            return [self.ambient; 3];
+        }
+        let (fx, fy) = (x / self.block - 0.5, y / self.block - 0.5);
+        let (i0, j0) = (fx.floor(), fy.floor());
+        let (tx, ty) = (fx - i0, fy - j0);
+        let mut sum = [0.0; 3];
+        let mut weight = 0.0;
+        for (di, dj, w) in [
+            (0, 0, (1.0 - tx) * (1.0 - ty)),
+            (1, 0, tx * (1.0 - ty)),
+            (0, 1, (1.0 - tx) * ty),
+            (1, 1, tx * ty),
+        ] {
+            let (i, j) = (i0 as isize + di, j0 as isize + dj);
+            if i < 0 || j < 0 {
+                continue;
+            }
+            if let Some(Some(light)) = self.cells.get(j as usize).and_then(|r| r.get(i as usize))
+                && w > 0.0
+            {
+                for (s, l) in sum.iter_mut().zip(light) {
+                    *s += w * l;
+                }
+                weight += w;
+            }
+        }
+        if weight == 0.0 {
+            return [self.ambient; 3];
+        }
+        sum.map(|s| s / weight)
+    }
+
+    pub fn ambient(&self) -> f32 {
+        self.ambient
+    }
+
+    /// Aplica la luz `light` al color y lo funde con la niebla según la distancia.
+    pub fn shade(&self, color: Rgba, distance: f32, light: [f32; 3]) -> Rgba {
+        let fog = (distance / self.fog.distance).clamp(0.0, MAX_FOG);
+        let mix = |c: u8, l: f32, f: u8| {
+            let lit = (c as f32 * l).min(255.0);
+            (lit * (1.0 - fog) + f as f32 * fog) as u8
+        };
+        Rgba::new(
+            mix(color.r, light[0], self.fog.color.r),
+            mix(color.g, light[1], self.fog.color.g),
+            mix(color.b, light[2], self.fog.color.b),
+            color.a,
+        )
+    }
+
+    /// Una antorcha visible en cada fuente de luz.
+    pub fn torch_sprites(&self) -> Vec<Sprite> {
+        self.lights
+            .iter()
+            .map(|light| Sprite {
+                pos: Vec2::new(
+                    (light.cell.0 as f32 + 0.5) * self.block,
+                    (light.cell.1 as f32 + 0.5) * self.block,
+                ),
+                kind: SpriteKind::Torch,
+            })
+            .collect()
+    }
+}
+
+/// Reparte hasta `count` antorchas en celdas libres separadas entre sí, fuera de la celda
+/// inicial.
+pub fn scatter_torches(level: &Level, count: usize, rng: &mut ChaCha8Rng) -> Vec<Light> {
+    let mut free: Vec<(usize, usize)> = Vec::new();
+    for (j, row) in level.maze.iter().enumerate() {
+        for i in 0..row.len() {
+            if level.tile(i, j) == Tile::Empty && (i, j) != level.start {
+                free.push((i, j));
+            }
+        }
+    }
+    free.shuffle(rng);
+
+    let mut lights: Vec<Light> = Vec::new();
+    for cell in free {
+        if lights.len() >= count {
+            break;
+        }
+        let far = lights
+            .iter()
+            .all(|l| l.cell.0.abs_diff(cell.0) + l.cell.1.abs_diff(cell.1) >= TORCH_SPACING);
+        if far {
+            lights.push(Light {
+                cell,
+                color: TORCH_COLOR,
+            });
+        }
+    }
+    lights
+}
@@ -1,9 +1,9 @@
 // line.rs
 
 use crate::framebuffer::Framebuffer;
-use raylib::prelude::*;
+use crate::math::Vec2;
 
-pub fn line(framebuffer: &mut Framebuffer, start: Vector2, end: Vector2) {
+pub fn line(framebuffer: &mut Framebuffer, start: Vec2, end: Vec2) {
     let mut x0 = start.x as i32;
     let mut y0 = start.y as i32;
     let x1 = end.x as i32;
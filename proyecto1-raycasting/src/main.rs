@@ -1,673 +1,764 @@
 // main.rs
-#![allow(unused_imports)]
-#![allow(dead_code)]
-
-mod caster;
-mod framebuffer;
-mod line;
-mod maze;
-mod player;
-
-use caster::{Intersect, cast_ray};
-use framebuffer::Framebuffer;
-use line::line;
-use maze::generate_maze_with_goal; // importa la nueva función
-use player::{Player, process_events};
-
-use raylib::audio::{RaylibAudio, Sound};
+// Front end con raylib: ventana, teclado y mouse, sonido, carga de imágenes y pantallas.
+// El núcleo del juego está en la biblioteca `raycaster` (lib.rs).
+mod audio;
+mod config;
+mod display;
+mod input;
+mod race;
+mod screens;
+
+use audio::GameAudio;
+use config::{CONFIG_FILE, Config, SETTINGS_ROWS, SFX_ROW, is_toggle};
+use display::{Display, load_image};
+use input::{KeyBindings, MouseCapture, key_list, key_name};
+use race::Race;
+use screens::{
+    WinSummary, draw_background, draw_level_select, draw_menu, draw_race_hud, draw_timings,
+    draw_title, draw_win, move_selection,
+};
+
+use raycaster::color::Rgba;
+use raycaster::editor::EditorView;
+use raycaster::framebuffer::Framebuffer;
+use raycaster::level_file::{CAMPAIGN_DIR, LevelFile, load_campaign};
+use raycaster::scores::{HighScores, SCORES_FILE};
+use raycaster::session::{Outcome, Session, Sounds};
+use raycaster::timing::{FrameTimer, FrameTimes, lap};
+use raylib::audio::RaylibAudio;
 use raylib::prelude::*;
-use std::f32::consts::PI;
-use std::thread;
-use std::time::Duration;
+use std::time::Instant;
 
-use crate::maze::Maze;
-
-fn cell_to_color(cell: char) -> Color {
-    match cell {
-        '+' => {
-            return Color::BLUEVIOLET;
-        }
-        '-' => {
-            return Color::VIOLET;
-        }
-        '|' => {
-            return Color::VIOLET;
-        }
-        'g' => {
-            return Color::GREEN;
-        }
-        _ => {
-            return Color::WHITE;
-        }
-    }
-}
-
-fn draw_cell(
-    framebuffer: &mut Framebuffer,
-    xo: usize,
-    yo: usize,
-    block_size: usize,
-    cell: char,
-    goal_tex: Option<&CpuImage>,
+fn draw_fps(
+    d: &mut RaylibDrawHandle,
+    fps: u32,
+    nivel_texto: &str,
+    estado_texto: &str,
+    window_height: i32,
 ) {
-    if cell == ' ' {
-        return;
-    }
-    if cell == 'g' {
-        if let Some(tex) = goal_tex {
-            // Dibuja la textura goal.jpg en el bloque
-            for x in 0..block_size {
-                for y in 0..block_size {
-                    let u = x as f32 / block_size as f32;
-                    let v = y as f32 / block_size as f32;
-                    let color = tex.sample(u, v);
-                    framebuffer.set_current_color(color);
-                    framebuffer.set_pixel((xo + x) as u32, (yo + y) as u32);
-                }
-            }
-            return;
-        }
-    }
-    let color = cell_to_color(cell);
-    framebuffer.set_current_color(color);
-
-    for x in xo..xo + block_size {
-        for y in yo..yo + block_size {
-            framebuffer.set_pixel(x as u32, y as u32);
-        }
-    }
+    d.draw_text(&format!("FPS: {}", fps), 10, 10, 20, Color::RAYWHITE);
+    d.draw_text(estado_texto, 10, 40, 28, Color::new(255, 215, 0, 255));
+    d.draw_text(nivel_texto, 20, window_height - 40, 32, Color::RAYWHITE);
 }
 
-pub fn render_maze(
-    framebuffer: &mut Framebuffer,
-    maze: &Maze,
-    block_size: usize,
-    player: &Player,
-    goal_tex: Option<&CpuImage>,
-) {
-    for (row_index, row) in maze.iter().enumerate() {
-        for (col_index, &cell) in row.iter().enumerate() {
-            let xo = col_index * block_size;
-            let yo = row_index * block_size;
-            draw_cell(framebuffer, xo, yo, block_size, cell, goal_tex);
-        }
-    }
-
-    framebuffer.set_current_color(Color::WHITESMOKE);
-
-    // draw what the player sees
-    let num_rays = 5;
-    for i in 0..num_rays {
-        let current_ray = i as f32 / num_rays as f32; // current ray divided by total rays
-        let a = player.a - (player.fov / 2.0) + (player.fov * current_ray);
-        cast_ray(framebuffer, &maze, &player, a, block_size, true);
-    }
+/// Pantallas del juego. Cada cuadro se atiende la actual y se pasa a otra según la
+/// entrada; la partida en curso vive aparte para que la pausa y las opciones la conserven.
+enum GameState {
+    Title,
+    LevelSelect {
+        selected: u8,
+    },
+    Playing,
+    Paused {
+        selected: usize,
+    },
+    Win(WinSummary),
+    /// Repetición de la partida que se acaba de ganar; al terminar vuelve a la victoria.
+    Watching(WinSummary),
+    GameOver {
+        reason: String,
+        selected: usize,
+    },
+    /// Carrera a pantalla dividida entre dos jugadores.
+    Racing,
+    /// Fin de la carrera con el resultado; permite la revancha en el mismo laberinto.
+    RaceOver {
+        result: String,
+        selected: usize,
+    },
+    /// Menú de opciones; `in_game` indica si se abrió desde la pausa.
+    Settings {
+        selected: usize,
+        in_game: bool,
+    },
+    /// Teclas de cada acción; con `listening` se espera la tecla nueva de la elegida.
+    Controls {
+        selected: usize,
+        listening: bool,
+        in_game: bool,
+    },
 }
 
-fn render_world(
-    framebuffer: &mut Framebuffer,
-    maze: &Maze,
-    block_size: usize,
-    player: &Player,
-    wall_tex: &CpuImage,
-    goal_tex: &CpuImage,
-) {
-    let num_rays = framebuffer.width;
-    let hh = framebuffer.height as f32 / 2.0;
-
-    // --- Fondo cielo y piso ---
-    let color_cielo = Color::new(180, 210, 255, 255); // azul claro
-    let color_piso = Color::new(210, 180, 140, 255); // arena/dorado
-
-    for y in 0..framebuffer.height {
-        let fondo_color = if (y as f32) < hh {
-            color_cielo
-        } else {
-            color_piso
-        };
-        for x in 0..framebuffer.width {
-            framebuffer.set_current_color(fondo_color);
-            framebuffer.set_pixel(x, y);
-        }
-    }
-    // --- Fin fondo cielo y piso ---
-
-    framebuffer.set_current_color(Color::WHITESMOKE);
-
-    for i in 0..num_rays {
-        let current_ray = i as f32 / num_rays as f32;
-        let a = player.a - (player.fov / 2.0) + (player.fov * current_ray);
-        let intersect = cast_ray(framebuffer, &maze, &player, a, block_size, false);
-
-        let distance_to_wall = intersect.distance.max(0.0001);
-        let distance_to_projection_plane = 70.0;
-        let stake_height = (hh / distance_to_wall) * distance_to_projection_plane;
-
-        let stake_top = (hh - (stake_height / 2.0)) as usize;
-        let stake_bottom = (hh + (stake_height / 2.0)) as usize;
-
-        let ray_length = distance_to_wall;
-        let hit_x = player.pos.x + ray_length * a.cos();
-        let hit_y = player.pos.y + ray_length * a.sin();
-
-        let wall_u = match intersect.impact {
-            '|' => (hit_y / block_size as f32).fract(),
-            '-' => (hit_x / block_size as f32).fract(),
-            _ => 0.0,
-        };
-
-        // Selecciona la textura según el tipo de pared
-        let tex = if intersect.impact == 'g' {
-            goal_tex
-        } else {
-            wall_tex
-        };
-
-        for y in stake_top..stake_bottom {
-            let v = (y - stake_top) as f32 / (stake_bottom - stake_top).max(1) as f32;
-            let color = tex.sample(wall_u, v);
-            framebuffer.set_current_color(color);
-            framebuffer.set_pixel(i, y as u32);
-        }
-    }
+const PAUSE_OPTIONS: [&str; 4] = ["Continuar", "Reiniciar nivel", "Opciones", "Salir al menú"];
+const GAME_OVER_OPTIONS: [&str; 2] = ["Reintentar", "Salir al menú"];
+const RACE_OVER_OPTIONS: [&str; 2] = ["Revancha", "Salir al menú"];
+
+/// Framebuffer de la vista 3D: una fracción `scale` de la ventana, que se estira al mostrarla.
+fn world_framebuffer(width: i32, height: i32, scale: f32) -> Framebuffer {
+    let mut framebuffer = Framebuffer::new(
+        ((width as f32 * scale) as u32).max(1),
+        ((height as f32 * scale) as u32).max(1),
+    );
+    framebuffer.set_background_color(Rgba::new(210, 180, 140, 255));
+    framebuffer
 }
 
-fn draw_fps(d: &mut RaylibDrawHandle, fps: u32, nivel_texto: &str, window_height: i32) {
-    d.draw_text(&format!("FPS: {}", fps), 10, 10, 20, Color::RAYWHITE);
-    d.draw_text(nivel_texto, 20, window_height - 40, 32, Color::RAYWHITE);
+/// Semilla del próximo laberinto generado: la de `--semilla N` o una al azar.
+fn next_seed(fixed: Option<u64>) -> u64 {
+    fixed.unwrap_or_else(rand::random)
 }
 
-pub fn render_minimap(
-    framebuffer: &mut Framebuffer,
-    maze: &Maze,
-    block_size: usize,
-    player: &Player,
-    offset_x: usize,
-    offset_y: usize,
-    goal_tex: Option<&CpuImage>,
-) {
-    // Dibuja el laberinto con offset
-    for (row_index, row) in maze.iter().enumerate() {
-        for (col_index, &cell) in row.iter().enumerate() {
-            let xo = offset_x + col_index * block_size;
-            let yo = offset_y + row_index * block_size;
-            draw_cell(framebuffer, xo, yo, block_size, cell, goal_tex);
-        }
-    }
-
-    // Dibuja el jugador en el minimapa
-    framebuffer.set_current_color(Color::YELLOW);
-    let px = offset_x + (player.pos.x / 100.0 * block_size as f32) as usize;
-    let py = offset_y + (player.pos.y / 100.0 * block_size as f32) as usize;
-    for x in px..px + block_size / 4 {
-        for y in py..py + block_size / 4 {
-            framebuffer.set_pixel(x as u32, y as u32);
-        }
-    }
+/// Semilla al elegir el nivel `number`: sin `--semilla N`, la de su mejor partida guardada
+/// para que su fantasma corra al lado, o una al azar si todavía no hay.
+fn level_seed(number: u8, campaign: &[LevelFile], fixed: Option<u64>) -> u64 {
+    fixed
+        .or_else(|| Session::best_replay(number, campaign).map(|best| best.seed))
+        .unwrap_or_else(rand::random)
 }
 
-fn mostrar_pantalla_bienvenida(
-    window: &mut RaylibHandle,
-    raylib_thread: &RaylibThread,
-    window_width: i32,
-    window_height: i32,
-) -> u8 {
-    let fondo = window
-        .load_texture(raylib_thread, "assets/fondo_bienvenida.jpg")
-        .expect("No se pudo cargar el fondo de bienvenida");
-
-    let fondo_width = fondo.width();
-    let fondo_height = fondo.height();
-
-    let mut nivel_seleccionado: u8 = 1;
-
-    loop {
-        let mut d = window.begin_drawing(raylib_thread);
-        d.clear_background(Color::BLACK);
-
-        // Dibuja el fondo
-        let scale_x = window_width as f32 / fondo_width as f32;
-        let scale_y = window_height as f32 / fondo_height as f32;
-        let scale = scale_x.max(scale_y);
-
-        d.draw_texture_ex(&fondo, Vector2::new(0.0, 0.0), 0.0, scale, Color::WHITE);
-
-        // Título y texto
-        d.draw_text(
-            "¡Bienvenido!",
-            window_width / 2 - 150,
-            window_height / 2 - 120,
-            50,
-            Color::new(255, 215, 0, 255),
-        );
-        d.draw_text(
-            "Selecciona nivel:",
-            window_width / 2 - 180,
-            window_height / 2 - 40,
-            40,
-            Color::RAYWHITE,
-        );
-
-        // Opciones de nivel
-        let color_facil = if nivel_seleccionado == 1 {
-            Color::YELLOW
-        } else {
-            Color::RAYWHITE
-        };
-        let color_medio = if nivel_seleccionado == 2 {
-            Color::YELLOW
-        } else {
-            Color::RAYWHITE
-        };
-        let color_dificil = if nivel_seleccionado == 3 {
-            Color::YELLOW
-        } else {
-            Color::RAYWHITE
-        };
-
-        d.draw_text(
-            "1 - Fácil",
-            window_width / 2 - 100,
-            window_height / 2 + 10,
-            32,
-            color_facil,
-        );
-        d.draw_text(
-            "2 - Medio",
-            window_width / 2 - 100,
-            window_height / 2 + 50,
-            32,
-            color_medio,
-        );
-        d.draw_text(
-            "3 - Difícil",
-            window_width / 2 - 100,
-            window_height / 2 + 90,
-            32,
-            color_dificil,
-        );
-
-        d.draw_text(
-            "Presiona ENTER para comenzar",
-            window_width / 2 - 200,
-            window_height / 2 + 150,
-            28,
-            Color::new(200, 30, 30, 255),
-        );
-
-        // Cambia selección con teclas
-        if d.is_key_pressed(KeyboardKey::KEY_ONE) {
-            nivel_seleccionado = 1;
-        }
-        if d.is_key_pressed(KeyboardKey::KEY_TWO) {
-            nivel_seleccionado = 2;
-        }
-        if d.is_key_pressed(KeyboardKey::KEY_THREE) {
-            nivel_seleccionado = 3;
-        }
-
-        if d.is_key_pressed(KeyboardKey::KEY_ENTER) {
-            break;
-        }
+/// Dibuja la vista inicial del nivel `number` sin abrir la ventana y la guarda como PNG.
+fn capture(path: &str, number: u8, seed: u64, (width, height): (i32, i32), block_size: usize) {
+    let campaign = load_campaign(CAMPAIGN_DIR);
+    let fov = Config::load(CONFIG_FILE).fov.to_radians();
+    let game = Session::new(number, &campaign, seed, block_size, fov, load_image);
+    let mut framebuffer = world_framebuffer(width, height, 1.0);
+    game.render(&mut framebuffer, block_size, 12, 0.0);
+    match framebuffer.save_png(path) {
+        Ok(()) => println!("Captura guardada en {}", path),
+        Err(err) => eprintln!("No se pudo guardar la captura en {}: {}", path, err),
     }
-    nivel_seleccionado
 }
 
-pub struct CpuImage {
-    pub w: usize,
-    pub h: usize,
-    pub pixels: Vec<Color>,
-}
+fn main() {
+    let window_width = 1300;
+    let window_height = 900;
+    let block_size = 100;
 
-impl CpuImage {
-    pub fn from_path(path: &str) -> Self {
-        let img = Image::load_image(path).expect("No se pudo cargar la imagen");
-        let w = img.width as usize;
-        let h = img.height as usize;
-        let pixels = img.get_image_data().to_vec();
-        Self { w, h, pixels }
+    // `--semilla N` repite un laberinto generado; sin ella se elige una al azar cada vez.
+    let fixed_seed: Option<u64> = std::env::args()
+        .skip_while(|arg| arg != "--semilla")
+        .nth(1)
+        .and_then(|s| s.parse().ok());
+
+    // `--captura archivo.png [--nivel N]` guarda la vista inicial sin abrir la ventana
+    if let Some(path) = std::env::args().skip_while(|arg| arg != "--captura").nth(1) {
+        let number = std::env::args()
+            .skip_while(|arg| arg != "--nivel")
+            .nth(1)
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(1);
+        let size = (window_width, window_height);
+        capture(&path, number, next_seed(fixed_seed), size, block_size);
+        return;
     }
 
-    pub fn sample(&self, u: f32, v: f32) -> Color {
-        let x = ((u.clamp(0.0, 1.0)) * (self.w as f32 - 1.0)) as usize;
-        let y = ((v.clamp(0.0, 1.0)) * (self.h as f32 - 1.0)) as usize;
-        self.pixels[y * self.w + x]
+    let (mut window, raylib_thread) = raylib::init()
+        .size(window_width, window_height)
+        .title("DOOM")
+        .vsync()
+        .log_level(TraceLogLevel::LOG_WARNING)
+        .build();
+    // ESC pausa y vuelve atrás en los menús; sólo sale del juego desde la pantalla inicial.
+    window.set_exit_key(None);
+
+    let mut config = Config::load(CONFIG_FILE);
+    // `--sensibilidad X` reemplaza la sensibilidad guardada en esta ejecución.
+    if let Some(sensitivity) = std::env::args()
+        .skip_while(|arg| arg != "--sensibilidad")
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+    {
+        config.controls.mouse.sensitivity = sensitivity;
     }
-}
 
-fn get_maze_for_level(level: u8) -> Maze {
-    match level {
-        1 => generate_maze_with_goal(11, 11), // Fácil
-        2 => generate_maze_with_goal(21, 21), // Medio
-        3 => generate_maze_with_goal(31, 31), // Difícil
-        _ => generate_maze_with_goal(11, 11), // Por defecto fácil
-    }
-}
+    // La vista 3D usa la resolución elegida; el editor, la de la ventana.
+    let mut framebuffer = world_framebuffer(window_width, window_height, config.resolution_scale);
+    let mut screen = world_framebuffer(window_width, window_height, 1.0);
+    let mut display = Display::default();
+    let mut timer = FrameTimer::default();
+    let mut mouse_capture = MouseCapture::default();
 
-fn player_reached_goal(player: &Player, maze: &Maze, block_size: usize) -> bool {
-    // Busca la posición de la meta
-    let mut goal_pos = None;
-    for (j, row) in maze.iter().enumerate() {
-        for (i, &cell) in row.iter().enumerate() {
-            if cell == 'g' {
-                goal_pos = Some((i, j));
-                break;
-            }
-        }
-        if goal_pos.is_some() {
-            break;
-        }
-    }
-    if let Some((goal_i, goal_j)) = goal_pos {
-        let goal_x = goal_i as f32 * block_size as f32 + block_size as f32 / 2.0;
-        let goal_y = goal_j as f32 * block_size as f32 + block_size as f32 / 2.0;
-        let dx = player.pos.x - goal_x;
-        let dy = player.pos.y - goal_y;
-        let distancia = (dx * dx + dy * dy).sqrt();
-        // Cambia el rango aquí, por ejemplo, al tamaño completo del bloque
-        return distancia < block_size as f32;
-    }
-    false
-}
+    let fondo_bienvenida = window
+        .load_texture(&raylib_thread, "assets/fondo_bienvenida.jpg")
+        .expect("No se pudo cargar el fondo de bienvenida");
+    let fondo_victoria = window
+        .load_texture(&raylib_thread, "assets/fondo_victoria.jpg")
+        .expect("No se pudo cargar el fondo de victoria");
 
-fn mostrar_pantalla_win(
-    window: &mut RaylibHandle,
-    raylib_thread: &RaylibThread,
-    window_width: i32,
-    window_height: i32,
-    win_sfx: &Sound,
-) -> bool {
-    win_sfx.play();
+    let campaign = load_campaign(CAMPAIGN_DIR);
+    // Los niveles de la campaña ocupan las teclas 4 a 9.
+    let level_keys = [
+        KeyboardKey::KEY_ONE,
+        KeyboardKey::KEY_TWO,
+        KeyboardKey::KEY_THREE,
+        KeyboardKey::KEY_FOUR,
+        KeyboardKey::KEY_FIVE,
+        KeyboardKey::KEY_SIX,
+        KeyboardKey::KEY_SEVEN,
+        KeyboardKey::KEY_EIGHT,
+        KeyboardKey::KEY_NINE,
+    ];
+    let campaign = &campaign[..campaign.len().min(level_keys.len() - 3)];
+    let mut high_scores = HighScores::load(SCORES_FILE);
+
+    // Inicializa el dispositivo de audio; sin él (o sin algún archivo) el juego sigue mudo
+    let audio_device = match RaylibAudio::init_audio_device() {
+        Ok(device) => Some(device),
+        Err(e) => {
+            eprintln!("Sin audio: {}", e);
+            None
+        }
+    };
+    let mut audio = GameAudio::new(audio_device.as_ref());
+    audio.settings = config.audio;
+    audio.apply_volume();
 
-    let fondo = window
-        .load_texture(raylib_thread, "assets/fondo_victoria.jpg")
-        .expect("No se pudo cargar el fondo de victoria");
+    let mut state = GameState::Title;
+    let mut session: Option<Session> = None;
+    let mut race: Option<Race> = None;
 
-    let fondo_width = fondo.width();
-    let fondo_height = fondo.height();
-
-    loop {
-        let mut d = window.begin_drawing(raylib_thread);
-        d.clear_background(Color::BLACK);
-
-        let scale_x = window_width as f32 / fondo_width as f32;
-        let scale_y = window_height as f32 / fondo_height as f32;
-        let scale = scale_x.max(scale_y);
-
-        d.draw_texture_ex(&fondo, Vector2::new(0.0, 0.0), 0.0, scale, Color::WHITE);
-
-        d.draw_text(
-            "¡Felicidades, has ganado!",
-            window_width / 2 - 250,
-            window_height / 2 - 40,
-            40,
-            Color::new(255, 215, 0, 255),
-        );
-        d.draw_text(
-            "PRESIONA ENTER para repetir el nivel",
-            window_width / 2 - 220,
-            window_height / 2 + 10,
-            30,
-            Color::RAYWHITE,
-        );
-        d.draw_text(
-            "PRESIONA ESC para salir",
-            window_width / 2 - 200,
-            window_height / 2 + 50,
-            30,
-            Color::RAYWHITE,
-        );
-
-        if d.is_key_pressed(KeyboardKey::KEY_ENTER) {
-            return true; // Volver al menú
+    while !window.window_should_close() {
+        audio.update_music();
+        let delta_time = window.get_frame_time();
+        let escape = input::back_pressed(&window);
+        let enter = input::accept_pressed(&window);
+        let mut next: Option<GameState> = None;
+        if window.is_key_pressed(KeyboardKey::KEY_F3) {
+            timer.visible = !timer.visible;
         }
-        if d.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
-            return false; // Salir
+        // F2 suelta o vuelve a atrapar el cursor; sólo queda atrapado mientras se juega
+        if window.is_key_pressed(KeyboardKey::KEY_F2) {
+            config.controls.capture_mouse = !config.controls.capture_mouse;
+            config.save(CONFIG_FILE);
         }
-    }
-}
+        let in_3d = matches!(state, GameState::Playing)
+            && session.as_ref().is_some_and(|game| game.editor.is_none());
+        mouse_capture.update(&mut window, in_3d && config.controls.capture_mouse);
+
+        match &mut state {
+            GameState::Title => {
+                if escape {
+                    break;
+                }
+                if enter {
+                    next = Some(GameState::LevelSelect { selected: 1 });
+                } else if window.is_key_pressed(KeyboardKey::KEY_O) {
+                    next = Some(GameState::Settings {
+                        selected: 0,
+                        in_game: false,
+                    });
+                }
+                let mut d = window.begin_drawing(&raylib_thread);
+                draw_title(&mut d, &fondo_bienvenida, window_width, window_height);
+            }
 
-fn find_starting_position(maze: &Maze, block_size: usize) -> Vector2 {
-    for (j, row) in maze.iter().enumerate() {
-        for (i, &cell) in row.iter().enumerate() {
-            if cell == ' ' {
-                return Vector2::new(
-                    i as f32 * block_size as f32 + block_size as f32 / 2.0,
-                    j as f32 * block_size as f32 + block_size as f32 / 2.0,
+            GameState::LevelSelect { selected } => {
+                for (k, &key) in level_keys.iter().take(3 + campaign.len()).enumerate() {
+                    if window.is_key_pressed(key) {
+                        *selected = k as u8 + 1;
+                    }
+                }
+                // ENTER juega con la semilla de la mejor partida, así su fantasma corre al
+                // lado; N juega un laberinto nuevo
+                let fresh = window.is_key_pressed(KeyboardKey::KEY_N);
+                if escape {
+                    next = Some(GameState::Title);
+                } else if enter || fresh {
+                    let seed = if fresh {
+                        next_seed(fixed_seed)
+                    } else {
+                        level_seed(*selected, campaign, fixed_seed)
+                    };
+                    let started = Session::new(
+                        *selected,
+                        campaign,
+                        seed,
+                        block_size,
+                        config.fov.to_radians(),
+                        load_image,
+                    );
+                    let sound = started.file.as_ref().and_then(|f| f.sound.as_deref());
+                    audio.set_ambient(sound, started.number);
+                    session = Some(started);
+                    next = Some(GameState::Playing);
+                } else if window.is_key_pressed(KeyboardKey::KEY_V) {
+                    let started = Race::new(
+                        *selected,
+                        campaign,
+                        next_seed(fixed_seed),
+                        block_size,
+                        config.fov.to_radians(),
+                        load_image,
+                    );
+                    let file = started.racers[0].file.as_ref();
+                    audio.set_ambient(file.and_then(|f| f.sound.as_deref()), *selected);
+                    race = Some(started);
+                    next = Some(GameState::Racing);
+                }
+                let mut d = window.begin_drawing(&raylib_thread);
+                draw_level_select(
+                    &mut d,
+                    &fondo_bienvenida,
+                    window_width,
+                    window_height,
+                    campaign,
+                    *selected,
                 );
             }
-        }
-    }
-    // Si no encuentra, usa el centro
-    Vector2::new(150.0, 150.0)
-}
-
-fn mostrar_pantalla_inicio(
-    window: &mut RaylibHandle,
-    raylib_thread: &RaylibThread,
-    window_width: i32,
-    window_height: i32,
-) {
-    let fondo = window
-        .load_texture(raylib_thread, "assets/fondo_bienvenida.jpg")
-        .expect("No se pudo cargar el fondo de bienvenida");
-
-    let fondo_width = fondo.width();
-    let fondo_height = fondo.height();
-
-    loop {
-        let mut d = window.begin_drawing(raylib_thread);
-        d.clear_background(Color::BLACK);
-
-        // Dibuja el fondo
-        let scale_x = window_width as f32 / fondo_width as f32;
-        let scale_y = window_height as f32 / fondo_height as f32;
-        let scale = scale_x.max(scale_y);
-
-        d.draw_texture_ex(&fondo, Vector2::new(0.0, 0.0), 0.0, scale, Color::WHITE);
-
-        // Título y datos
-        d.draw_text(
-            "Proyecto Raycaster - Rayo McQueen",
-            window_width / 2 - 450,
-            window_height / 2 - 180,
-            48,
-            Color::new(255, 215, 0, 255),
-        );
-        d.draw_text(
-            "Autor: Esteban Cárcamo",
-            window_width / 2 - 180,
-            window_height / 2 - 120,
-            32,
-            Color::RAYWHITE,
-        );
-        d.draw_text(
-            "Instrucciones:",
-            window_width / 2 - 180,
-            window_height / 2 - 60,
-            32,
-            Color::RAYWHITE,
-        );
-        d.draw_text(
-            "- Usa las flechas del teclado para moverte y girar",
-            window_width / 2 - 180,
-            window_height / 2 - 20,
-            28,
-            Color::RAYWHITE,
-        );
-        d.draw_text(
-            "- Mouse para mirar horizontalmente",
-            window_width / 2 - 180,
-            window_height / 2 + 10,
-            28,
-            Color::RAYWHITE,
-        );
-        d.draw_text(
-            "- M para cambiar entre 2D/3D",
-            window_width / 2 - 180,
-            window_height / 2 + 40,
-            28,
-            Color::RAYWHITE,
-        );
-        d.draw_text(
-            "Presiona ENTER para seleccionar nivel",
-            window_width / 2 - 220,
-            window_height / 2 + 100,
-            28,
-            Color::new(200, 30, 30, 255),
-        );
-
-        if d.is_key_pressed(KeyboardKey::KEY_ENTER) {
-            break;
-        }
-    }
-}
-
-fn main() {
-    let window_width = 1300;
-    let window_height = 900;
-    let block_size = 100;
 
-    let (mut window, raylib_thread) = raylib::init()
-        .size(window_width, window_height)
-        .title("DOOM")
-        .log_level(TraceLogLevel::LOG_WARNING)
-        .build();
-
-    let mut framebuffer = Framebuffer::new(window_width as u32, window_height as u32);
-    framebuffer.set_background_color(Color::new(210, 180, 140, 255));
+            GameState::Playing => {
+                let Some(game) = session.as_mut() else {
+                    state = GameState::LevelSelect { selected: 1 };
+                    continue;
+                };
+
+                let editing = game.editor.is_some();
+                if editing {
+                    let view = EditorView::fit(
+                        &game.level.maze,
+                        20,
+                        90,
+                        window_width as usize - 40,
+                        window_height as usize - 150,
+                    );
+                    game.update_editor(&input::poll_editor(&window), view, block_size);
+                    screen.clear();
+                    game.render_editor(&mut screen, view);
+                    let (nivel_texto, estado_texto) = game.hud();
+                    let fps = window.get_fps();
+                    display.present(
+                        &mut window,
+                        &raylib_thread,
+                        &screen,
+                        |d, fps| draw_fps(d, fps, &nivel_texto, &estado_texto, window_height),
+                        fps,
+                    );
+                } else {
+                    let mut clock = Instant::now();
+                    let controls = &config.controls;
+                    let input = input::poll(&window, controls, delta_time);
+                    game.player
+                        .look_vertical(input::poll_pitch(&window, controls, delta_time));
+                    match game.advance(delta_time, input, block_size, &mut audio) {
+                        Some(Outcome::Won(stats)) => {
+                            audio.stop_sources();
+                            audio.win();
+                            // Un nivel editado ya no es el del número ni se puede
+                            // reproducir, así que no entra en la tabla
+                            let record = game.replay().and_then(|replay| {
+                                let record = high_scores.record(game.number, stats);
+                                high_scores.save();
+                                // El primer puesto guarda también su repetición
+                                if record == Some(0) {
+                                    replay.save_best();
+                                }
+                                record
+                            });
+                            next = Some(GameState::Win(WinSummary {
+                                stats,
+                                best: high_scores.best(game.number).to_vec(),
+                                record,
+                            }));
+                        }
+                        Some(Outcome::Lost(reason)) => {
+                            audio.stop_sources();
+                            next = Some(GameState::GameOver {
+                                reason,
+                                selected: 0,
+                            });
+                        }
+                        None => {}
+                    }
+                    let simulation = lap(&mut clock);
+
+                    let minimap_cell = ((12.0 * config.resolution_scale) as usize).max(3);
+                    game.render(
+                        &mut framebuffer,
+                        block_size,
+                        minimap_cell,
+                        window.get_time() as f32,
+                    );
+                    let render = lap(&mut clock);
+                    let (nivel_texto, estado_texto) = game.hud();
+                    let fps = window.get_fps();
+                    let resolution = (framebuffer.width, framebuffer.height);
+                    display.present(
+                        &mut window,
+                        &raylib_thread,
+                        &framebuffer,
+                        |d, fps| {
+                            draw_fps(d, fps, &nivel_texto, &estado_texto, window_height);
+                            if timer.visible {
+                                let scale = config.resolution_scale;
+                                draw_timings(d, &timer, window_width, resolution, scale);
+                            }
+                        },
+                        fps,
+                    );
+                    let present = lap(&mut clock);
+                    let times = FrameTimes {
+                        simulation,
+                        render,
+                        present,
+                    };
+                    timer.record(times, delta_time * 1000.0);
+                }
 
-    // Pantalla inicial con instrucciones y datos
-    mostrar_pantalla_inicio(&mut window, &raylib_thread, window_width, window_height);
+                // ESC (o P) pausa, O abre las opciones y M el editor
+                if next.is_none() {
+                    if !editing && window.is_key_pressed(KeyboardKey::KEY_M) {
+                        audio.stop_sources();
+                        game.open_editor(block_size);
+                    } else if escape || window.is_key_pressed(KeyboardKey::KEY_P) {
+                        audio.stop_sources();
+                        next = Some(GameState::Paused { selected: 0 });
+                    } else if window.is_key_pressed(KeyboardKey::KEY_O) {
+                        audio.stop_sources();
+                        next = Some(GameState::Settings {
+                            selected: 0,
+                            in_game: true,
+                        });
+                    }
+                }
+            }
 
-    // Pantalla de selección de nivel
-    let nivel_inicial =
-        mostrar_pantalla_bienvenida(&mut window, &raylib_thread, window_width, window_height);
-    let current_level: u8 = nivel_inicial;
-    let maze = get_maze_for_level(current_level);
+            GameState::Paused { selected } => {
+                move_selection(&window, selected, PAUSE_OPTIONS.len());
+                let Some(game) = session.as_mut() else {
+                    state = GameState::Title;
+                    continue;
+                };
+                if escape || window.is_key_pressed(KeyboardKey::KEY_P) {
+                    next = Some(GameState::Playing);
+                } else if enter {
+                    next = Some(match *selected {
+                        0 => GameState::Playing,
+                        1 => {
+                            game.editor = None;
+                            game.restart(block_size);
+                            GameState::Playing
+                        }
+                        2 => GameState::Settings {
+                            selected: 0,
+                            in_game: true,
+                        },
+                        _ => GameState::LevelSelect {
+                            selected: game.number,
+                        },
+                    });
+                }
 
-    let mut player = Player {
-        pos: find_starting_position(&maze, block_size), // Usa la nueva función
-        a: PI / 3.0,
-        fov: PI / 3.0,
-    };
+                // El último cuadro queda congelado detrás del menú
+                let frozen = if game.editor.is_some() {
+                    &screen
+                } else {
+                    &framebuffer
+                };
+                let options = PAUSE_OPTIONS.map(String::from);
+                let selected = *selected;
+                display.present(
+                    &mut window,
+                    &raylib_thread,
+                    frozen,
+                    |d, _| draw_menu(d, window_width, window_height, "Pausa", &options, selected),
+                    0,
+                );
+            }
 
-    let wall_tex = CpuImage::from_path("assets/texturas/wall.jpg");
-    let goal_tex = CpuImage::from_path("assets/texturas/goal.jpg");
+            GameState::Win(summary) => {
+                // R mira la partida recién ganada (o la repetición que se estaba mirando)
+                if window.is_key_pressed(KeyboardKey::KEY_R)
+                    && let Some(replay) = session.as_ref().and_then(|game| game.replay()).cloned()
+                {
+                    session = Some(Session::watch(
+                        replay,
+                        campaign,
+                        block_size,
+                        config.fov.to_radians(),
+                        load_image,
+                    ));
+                    next = Some(GameState::Watching(summary.clone()));
+                } else if enter || escape {
+                    let number = session.as_ref().map_or(1, |game| game.number);
+                    if enter {
+                        // Otro laberinto del mismo nivel (o el mismo nivel de la campaña)
+                        let started = Session::new(
+                            number,
+                            campaign,
+                            next_seed(fixed_seed),
+                            block_size,
+                            config.fov.to_radians(),
+                            load_image,
+                        );
+                        session = Some(started);
+                        next = Some(GameState::Playing);
+                    } else {
+                        next = Some(GameState::LevelSelect { selected: number });
+                    }
+                }
+                let mut d = window.begin_drawing(&raylib_thread);
+                draw_win(
+                    &mut d,
+                    &fondo_victoria,
+                    window_width,
+                    window_height,
+                    summary,
+                );
+            }
 
-    let mut mode = "3D"; // Mueve esto fuera del bucle principal
+            GameState::Watching(summary) => {
+                let Some(game) = session.as_mut() else {
+                    state = GameState::Title;
+                    continue;
+                };
+                let mut clock = Instant::now();
+                let input = input::poll(&window, &config.controls, delta_time);
+                let outcome = game.advance(delta_time, input, block_size, &mut audio);
+                if escape || outcome.is_some() {
+                    audio.stop_sources();
+                    next = Some(GameState::Win(summary.clone()));
+                }
+                let simulation = lap(&mut clock);
+
+                let minimap_cell = ((12.0 * config.resolution_scale) as usize).max(3);
+                game.render(
+                    &mut framebuffer,
+                    block_size,
+                    minimap_cell,
+                    window.get_time() as f32,
+                );
+                let render = lap(&mut clock);
+                let (nivel_texto, estado_texto) = game.hud();
+                let fps = window.get_fps();
+                let resolution = (framebuffer.width, framebuffer.height);
+                display.present(
+                    &mut window,
+                    &raylib_thread,
+                    &framebuffer,
+                    |d, fps| {
+                        draw_fps(d, fps, &nivel_texto, &estado_texto, window_height);
+                        if timer.visible {
+                            let scale = config.resolution_scale;
+                            draw_timings(d, &timer, window_width, resolution, scale);
+                        }
+                    },
+                    fps,
+                );
+                let present = lap(&mut clock);
+                let times = FrameTimes {
+                    simulation,
+                    render,
+                    present,
+                };
+                timer.record(times, delta_time * 1000.0);
+            }
 
-    // Inicializa el dispositivo de audio
-    let audio =
-        RaylibAudio::init_audio_device().expect("No se pudo inicializar el dispositivo de audio");
-    let bg_music = audio
-        .new_music("assets/sonidos/bg_music_taylor.wav")
-        .expect("No se pudo cargar la música de fondo");
-    bg_music.set_volume(1.0);
-    bg_music.play_stream();
+            GameState::GameOver { reason, selected } => {
+                move_selection(&window, selected, GAME_OVER_OPTIONS.len());
+                let Some(game) = session.as_mut() else {
+                    state = GameState::Title;
+                    continue;
+                };
+                if escape || (enter && *selected == 1) {
+                    next = Some(GameState::LevelSelect {
+                        selected: game.number,
+                    });
+                } else if enter {
+                    game.restart(block_size);
+                    next = Some(GameState::Playing);
+                }
+                let options = GAME_OVER_OPTIONS.map(String::from);
+                let selected = *selected;
+                display.present(
+                    &mut window,
+                    &raylib_thread,
+                    &framebuffer,
+                    |d, _| draw_menu(d, window_width, window_height, reason, &options, selected),
+                    0,
+                );
+            }
 
-    // Sonido de victoria
-    let win_sfx = audio
-        .new_sound("assets/sonidos/victoria.wav")
-        .expect("No se pudo cargar el sonido de victoria");
-    let steps_sfx = audio
-        .new_sound("assets/sonidos/steps.wav")
-        .expect("No se pudo cargar el sonido de pasos");
+            GameState::Racing => {
+                let Some(current) = race.as_mut() else {
+                    state = GameState::LevelSelect { selected: 1 };
+                    continue;
+                };
+                let inputs = current
+                    .controls
+                    .each_ref()
+                    .map(|controls| input::poll(&window, controls, delta_time));
+                let number = current.racers[0].number;
+                if let Some(result) = current.advance(inputs, delta_time, block_size, &mut audio) {
+                    audio.stop_sources();
+                    audio.win();
+                    next = Some(GameState::RaceOver {
+                        result,
+                        selected: 0,
+                    });
+                } else if escape {
+                    audio.stop_sources();
+                    next = Some(GameState::LevelSelect { selected: number });
+                }
 
-    while !window.window_should_close() {
-        bg_music.update_stream();
+                // Cada mitad tiene la mitad del ancho, así que el minimapa también se achica
+                let minimap_cell = ((6.0 * config.resolution_scale) as usize).max(3);
+                current.render(
+                    &mut framebuffer,
+                    block_size,
+                    minimap_cell,
+                    window.get_time() as f32,
+                );
+                let (nivel_texto, estados) = (current.level_name(), current.hud());
+                let fps = window.get_fps();
+                display.present(
+                    &mut window,
+                    &raylib_thread,
+                    &framebuffer,
+                    |d, fps| {
+                        draw_race_hud(d, fps, &nivel_texto, &estados, window_width, window_height)
+                    },
+                    fps,
+                );
+            }
 
-        // 1. clear framebuffer
-        framebuffer.clear();
+            GameState::RaceOver { result, selected } => {
+                move_selection(&window, selected, RACE_OVER_OPTIONS.len());
+                let Some(current) = race.as_mut() else {
+                    state = GameState::Title;
+                    continue;
+                };
+                if escape || (enter && *selected == 1) {
+                    next = Some(GameState::LevelSelect {
+                        selected: current.racers[0].number,
+                    });
+                } else if enter {
+                    current.restart(block_size);
+                    next = Some(GameState::Racing);
+                }
+                let options = RACE_OVER_OPTIONS.map(String::from);
+                let selected = *selected;
+                display.present(
+                    &mut window,
+                    &raylib_thread,
+                    &framebuffer,
+                    |d, _| draw_menu(d, window_width, window_height, result, &options, selected),
+                    0,
+                );
+            }
 
-        let delta_time = window.get_frame_time();
+            GameState::Settings { selected, in_game } => {
+                move_selection(&window, selected, SETTINGS_ROWS + 2);
+                let steps = match input::adjust_step(&window) {
+                    // ENTER alterna las opciones de sí o no; en las demás filas no hace nada
+                    0 if enter && *selected < SETTINGS_ROWS => i32::from(is_toggle(*selected)),
+                    steps => steps,
+                };
+                if steps != 0 {
+                    let previous_scale = config.resolution_scale;
+                    config.adjust(*selected, steps);
+                    if config.resolution_scale != previous_scale {
+                        framebuffer =
+                            world_framebuffer(window_width, window_height, config.resolution_scale);
+                    }
+                    if let Some(game) = session.as_mut() {
+                        game.player.fov = config.fov.to_radians();
+                    }
+                    audio.settings = config.audio;
+                    audio.apply_volume();
+                    // Un golpe de prueba al cambiar el volumen de los efectos
+                    if *selected == SFX_ROW {
+                        audio.wall_hit();
+                    }
+                }
+                if enter && *selected == SETTINGS_ROWS {
+                    next = Some(GameState::Controls {
+                        selected: 0,
+                        listening: false,
+                        in_game: *in_game,
+                    });
+                } else if escape || (enter && *selected == SETTINGS_ROWS + 1) {
+                    config.save(CONFIG_FILE);
+                    next = Some(if *in_game {
+                        GameState::Paused { selected: 2 }
+                    } else {
+                        GameState::Title
+                    });
+                }
 
-        // 2. move the player on user input
-        process_events(
-            &mut player,
-            &window,
-            delta_time,
-            &maze,
-            block_size,
-            &steps_sfx,
-        );
-
-        // Cambia el modo solo si se presiona la tecla
-        if window.is_key_pressed(KeyboardKey::KEY_M) {
-            mode = if mode == "2D" { "3D" } else { "2D" };
-        }
+                let mut options: Vec<String> = config.rows().to_vec();
+                options.push("Controles".to_string());
+                options.push("Volver".to_string());
+                let selected = *selected;
+                let mut d = window.begin_drawing(&raylib_thread);
+                if *in_game {
+                    d.clear_background(Color::BLACK);
+                } else {
+                    draw_background(&mut d, &fondo_bienvenida, window_width, window_height);
+                }
+                draw_menu(
+                    &mut d,
+                    window_width,
+                    window_height,
+                    "Opciones",
+                    &options,
+                    selected,
+                );
+            }
 
-        if mode == "2D" {
-            render_maze(
-                &mut framebuffer,
-                &maze,
-                block_size,
-                &player,
-                Some(&goal_tex),
-            );
-        } else {
-            render_world(
-                &mut framebuffer,
-                &maze,
-                block_size,
-                &player,
-                &wall_tex,
-                &goal_tex,
-            );
-        }
+            GameState::Controls {
+                selected,
+                listening,
+                in_game,
+            } => {
+                let actions = KeyBindings::ACTIONS.len();
+                if *listening {
+                    // La tecla que se aprieta reemplaza las de la acción; ESC cancela
+                    if escape {
+                        *listening = false;
+                    } else if let Some(key) = window.get_key_pressed()
+                        && key_name(key).is_some()
+                    {
+                        *config.controls.keys.actions_mut()[*selected] = vec![key];
+                        *listening = false;
+                    }
+                } else {
+                    move_selection(&window, selected, actions + 2);
+                    if escape || (enter && *selected == actions + 1) {
+                        config.save(CONFIG_FILE);
+                        next = Some(GameState::Settings {
+                            selected: SETTINGS_ROWS,
+                            in_game: *in_game,
+                        });
+                    } else if enter && *selected == actions {
+                        config.controls.keys = KeyBindings::default();
+                    } else if enter {
+                        *listening = true;
+                    }
+                }
 
-        let fps = window.get_fps();
-
-        // 3. draw stuff
-
-        // --- MINIMAPA ---
-        let minimap_block_size: usize = 12; // <-- tamaño reducido
-        let minimap_offset_x: usize = (window_width as usize)
-            .saturating_sub(maze[0].len() * minimap_block_size)
-            .saturating_sub(20);
-        let minimap_offset_y: usize = 20;
-        render_minimap(
-            &mut framebuffer,
-            &maze,
-            minimap_block_size,
-            &player,
-            minimap_offset_x,
-            minimap_offset_y,
-            Some(&goal_tex),
-        );
-        // --- FIN MINIMAPA ---
-
-        // --- Mostrar nivel actual ---
-        let nivel_texto = match current_level {
-            1 => "Nivel: Fácil",
-            2 => "Nivel: Medio",
-            3 => "Nivel: Difícil",
-            _ => "Nivel: Fácil",
-        };
-
-        framebuffer.swap_buffers(
-            &mut window,
-            &raylib_thread,
-            |d, fps| draw_fps(d, fps, nivel_texto, window_height),
-            fps,
-        );
-
-        // Verifica si el jugador ha alcanzado el objetivo
-        if player_reached_goal(&player, &maze, block_size) {
-            if !mostrar_pantalla_win(
-                &mut window,
-                &raylib_thread,
-                window_width,
-                window_height,
-                &win_sfx,
-            ) {
-                break; // Salir del juego si elige escapar
+                let bindings = KeyBindings::ACTIONS
+                    .iter()
+                    .zip(config.controls.keys.actions());
+                let mut options: Vec<String> = bindings
+                    .map(|((name, _), keys)| format!("{}: {}", name, key_list(keys)))
+                    .collect();
+                options.push("Restablecer".to_string());
+                options.push("Volver".to_string());
+                let title = if *listening {
+                    "Pulsa una tecla (ESC: cancelar)"
+                } else {
+                    "Controles"
+                };
+                let selected = *selected;
+                let mut d = window.begin_drawing(&raylib_thread);
+                if *in_game {
+                    d.clear_background(Color::BLACK);
+                } else {
+                    draw_background(&mut d, &fondo_bienvenida, window_width, window_height);
+                }
+                draw_menu(
+                    &mut d,
+                    window_width,
+                    window_height,
+                    title,
+                    &options,
+                    selected,
+                );
             }
-            // Si vuelve al menú, recarga el laberinto y reinicia el jugador
-            let maze = get_maze_for_level(current_level);
-            player.pos = find_starting_position(&maze, block_size);
         }
 
-        thread::sleep(Duration::from_millis(16));
+        if let Some(next) = next {
+            state = next;
+        }
     }
 }
@@ -1,61 +1,306 @@
 // maze.rs
 
-use rand::Rng;
 use rand::seq::SliceRandom;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::color::Rgba;
+use crate::level_file::load_level;
 
 pub type Maze = Vec<Vec<char>>;
 
-pub fn load_maze(filename: &str) -> Maze {
-    let file = File::open(filename).unwrap();
-    let reader = BufReader::new(file);
+/// Textura de pared por defecto; siempre ocupa el índice 0 de `Legend::textures`.
+pub const DEFAULT_WALL_TEXTURE: &str = "assets/texturas/wall.jpg";
 
-    reader
-        .lines()
-        .map(|line| line.unwrap().chars().collect())
-        .collect()
+/// Color de una llave y de las puertas que abre.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KeyColor {
+    Red,
+    Blue,
+    Green,
+    Yellow,
 }
 
-pub fn generate_random_maze(width: usize, height: usize) -> Vec<Vec<char>> {
-    let mut maze = vec![vec!['+'; width]; height];
-    let mut rng = rand::rng();
+impl KeyColor {
+    pub const ALL: [KeyColor; 4] = [
+        KeyColor::Red,
+        KeyColor::Blue,
+        KeyColor::Green,
+        KeyColor::Yellow,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "roja" => Some(KeyColor::Red),
+            "azul" => Some(KeyColor::Blue),
+            "verde" => Some(KeyColor::Green),
+            "amarilla" => Some(KeyColor::Yellow),
+            _ => None,
+        }
+    }
 
-    for y in 1..height - 1 {
-        for x in 1..width - 1 {
-            maze[y][x] = if rng.random_bool(0.7) { ' ' } else { '+' };
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyColor::Red => "roja",
+            KeyColor::Blue => "azul",
+            KeyColor::Green => "verde",
+            KeyColor::Yellow => "amarilla",
+        }
+    }
+
+    pub fn color(self) -> Rgba {
+        match self {
+            KeyColor::Red => Rgba::new(220, 40, 40, 255),
+            KeyColor::Blue => Rgba::new(50, 90, 230, 255),
+            KeyColor::Green => Rgba::new(40, 190, 70, 255),
+            KeyColor::Yellow => Rgba::new(240, 210, 40, 255),
         }
     }
-    maze
 }
 
-pub fn generate_maze_with_goal(width: usize, height: usize) -> Maze {
-    let mut maze = vec![vec!['+'; width]; height];
+/// Qué representa un carácter del laberinto.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tile {
+    Empty,
+    /// Pared sólida con la textura `Legend::textures[texture]`.
+    Wall {
+        texture: usize,
+    },
+    Goal,
+    /// Puerta corrediza; si tiene color, sólo se abre con la llave correspondiente.
+    Door {
+        key: Option<KeyColor>,
+    },
+    /// Llave en el piso; se recoge al pasar por la celda.
+    Key(KeyColor),
+    /// Pared con palanca que abre y cierra todos los muros móviles con el carácter `target`.
+    Switch {
+        target: char,
+    },
+    /// Muro que aparece o desaparece con un interruptor.
+    MovingWall {
+        texture: usize,
+    },
+}
 
-    fn carve(x: usize, y: usize, maze: &mut Vec<Vec<char>>) {
-        maze[y][x] = ' ';
-        let mut dirs = [(0, 1), (1, 0), (0, -1), (-1, 0)];
-        let mut rng = rand::rng();
-        dirs.shuffle(&mut rng);
-        for (dx, dy) in dirs {
-            let nx = x as isize + dx * 2;
-            let ny = y as isize + dy * 2;
-            if nx > 0
-                && ny > 0
-                && nx < (maze[0].len() as isize) - 1
-                && ny < (maze.len() as isize) - 1
-            {
-                if maze[ny as usize][nx as usize] == '+' {
-                    maze[(y as isize + dy) as usize][(x as isize + dx) as usize] = ' ';
-                    carve(nx as usize, ny as usize, maze);
-                }
+/// Significado de cada carácter del laberinto y texturas de pared que usa.
+#[derive(Clone, Debug)]
+pub struct Legend {
+    tiles: HashMap<char, Tile>,
+    pub textures: Vec<String>,
+}
+
+impl Default for Legend {
+    /// Leyenda de los laberintos generados: `+`, `-` y `|` son paredes y `g` la meta.
+    fn default() -> Self {
+        let mut tiles = HashMap::new();
+        for c in ['+', '-', '|'] {
+            tiles.insert(c, Tile::Wall { texture: 0 });
+        }
+        tiles.insert('g', Tile::Goal);
+        Self {
+            tiles,
+            textures: vec![DEFAULT_WALL_TEXTURE.to_string()],
+        }
+    }
+}
+
+impl Legend {
+    /// Tipo de un carácter; los que no aparecen en la leyenda son paredes normales.
+    pub fn tile(&self, cell: char) -> Tile {
+        if cell == ' ' {
+            return Tile::Empty;
+        }
+        self.tiles
+            .get(&cell)
+            .copied()
+            .unwrap_or(Tile::Wall { texture: 0 })
+    }
+
+    /// Primer carácter (en orden) que representa `tile`.
+    pub fn char_for(&self, tile: Tile) -> Option<char> {
+        self.tiles
+            .iter()
+            .filter(|&(_, t)| *t == tile)
+            .map(|(&c, _)| c)
+            .min()
+    }
+
+    /// Líneas `c = tipo [argumento]` que reconstruyen esta leyenda con `parse_entry`.
+    /// Omite las entradas que ya trae la leyenda por defecto.
+    pub fn entries(&self) -> Vec<String> {
+        let defaults = Legend::default();
+        let texture = |index: usize| match index {
+            0 => String::new(),
+            i => format!(" {}", self.textures[i]),
+        };
+        let mut cells: Vec<char> = self
+            .tiles
+            .iter()
+            .filter(|&(c, tile)| defaults.tiles.get(c) != Some(tile))
+            .map(|(&c, _)| c)
+            .collect();
+        cells.sort();
+        let mut lines = Vec::new();
+        for c in cells {
+            let definition = match self.tiles[&c] {
+                // El espacio no se guarda en la leyenda: siempre es piso.
+                Tile::Empty => continue,
+                Tile::Wall { texture: t } => format!("pared{}", texture(t)),
+                Tile::Goal => "meta".to_string(),
+                Tile::Door { key: None } => "puerta".to_string(),
+                Tile::Door { key: Some(color) } => format!("puerta {}", color.name()),
+                Tile::Key(color) => format!("llave {}", color.name()),
+                Tile::Switch { target } => format!("interruptor {}", target),
+                Tile::MovingWall { texture: t } => format!("movil{}", texture(t)),
+            };
+            lines.push(format!("{} = {}", c, definition));
+        }
+        lines
+    }
+
+    /// Índice de una textura, registrándola si es nueva.
+    fn texture_index(&mut self, path: &str) -> usize {
+        match self.textures.iter().position(|t| t == path) {
+            Some(i) => i,
+            None => {
+                self.textures.push(path.to_string());
+                self.textures.len() - 1
             }
         }
     }
 
-    carve(1, 1, &mut maze);
+    /// Interpreta una línea `c = tipo [argumento]` de la leyenda. El error incluye la
+    /// columna (desde 1) donde está el problema.
+    pub fn parse_entry(&mut self, line: &str) -> Result<(), (usize, String)> {
+        let Some((cell, definition)) = line.split_once('=') else {
+            return Err((1, format!("se esperaba `carácter = tipo`, no `{}`", line)));
+        };
+        let cell_column = column_of(line, cell.len() - cell.trim_start().len());
+        // El espacio no se puede redefinir: siempre es piso libre.
+        let mut chars = cell.trim().chars();
+        let (Some(cell), None) = (chars.next(), chars.next()) else {
+            return Err((
+                cell_column,
+                format!("`{}` debe ser un solo carácter", cell.trim()),
+            ));
+        };
 
-    maze[height - 2][width - 2] = 'g';
+        // Palabras de la definición con su columna en la línea.
+        let offset = line.len() - definition.len();
+        let words: Vec<(usize, &str)> = definition
+            .split_whitespace()
+            .map(|w| {
+                let start = w.as_ptr() as usize - definition.as_ptr() as usize;
+                (column_of(line, offset + start), w)
+            })
+            .collect();
+        let end = column_of(line, line.len());
+        let Some(&(kind_column, kind)) = words.first() else {
+            return Err((end, "falta el tipo".to_string()));
+        };
+        let argument = words.get(1).copied();
+        if let Some(&(column, extra)) = words.get(2) {
+            return Err((column, format!("sobra `{}`", extra)));
+        }
+        let color = |arg: Option<(usize, &str)>| -> Result<KeyColor, (usize, String)> {
+            let (column, name) = arg.ok_or((end, "falta el color".to_string()))?;
+            KeyColor::from_name(name).ok_or((column, format!("color desconocido `{}`", name)))
+        };
+        let texture = |legend: &mut Legend| {
+            legend.texture_index(argument.map_or(DEFAULT_WALL_TEXTURE, |(_, path)| path))
+        };
+        let tile = match kind {
+            "pared" => Tile::Wall {
+                texture: texture(self),
+            },
+            "meta" => Tile::Goal,
+            "puerta" => Tile::Door {
+                key: argument.map(|arg| color(Some(arg))).transpose()?,
+            },
+            "llave" => Tile::Key(color(argument)?),
+            "interruptor" => {
+                let (column, target) =
+                    argument.ok_or((end, "falta el carácter de los muros móviles".to_string()))?;
+                let mut chars = target.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(target), None) => Tile::Switch { target },
+                    _ => {
+                        return Err((column, format!("`{}` debe ser un solo carácter", target)));
+                    }
+                }
+            }
+            "movil" => Tile::MovingWall {
+                texture: texture(self),
+            },
+            other => {
+                return Err((
+                    kind_column,
+                    format!("tipo de celda desconocido `{}`", other),
+                ));
+            }
+        };
+        self.tiles.insert(cell, tile);
+        Ok(())
+    }
+}
+
+/// Columna (desde 1, contando caracteres) del byte `index` de `line`.
+pub fn column_of(line: &str, index: usize) -> usize {
+    line[..index].chars().count() + 1
+}
 
-    maze
+/// Lee un laberinto de texto: una cuadrícula simple, con o sin sección `[leyenda]`, o un
+/// archivo de nivel completo (ver `level_file`).
+pub fn load_maze(filename: &str) -> Result<(Maze, Legend), String> {
+    load_level(Path::new(filename)).map(|level| (level.maze, level.legend))
+}
+
+/// Elige `count` celdas libres para las copas coleccionables, priorizando los callejones
+/// sin salida. Nunca usa la celda inicial `start`.
+pub fn place_collectibles(
+    maze: &Maze,
+    count: usize,
+    start: (usize, usize),
+    rng: &mut ChaCha8Rng,
+) -> Vec<(usize, usize)> {
+    let is_open = |i: isize, j: isize| {
+        i >= 0 && j >= 0 && maze.get(j as usize).and_then(|row| row.get(i as usize)) == Some(&' ')
+    };
+
+    let mut dead_ends = Vec::new();
+    let mut others = Vec::new();
+    for (j, row) in maze.iter().enumerate() {
+        for (i, &cell) in row.iter().enumerate() {
+            if cell != ' ' || (i, j) == start {
+                continue;
+            }
+            let (x, y) = (i as isize, j as isize);
+            let exits = [(1, 0), (-1, 0), (0, 1), (0, -1)]
+                .iter()
+                .filter(|(dx, dy)| is_open(x + dx, y + dy))
+                .count();
+            if exits == 1 {
+                dead_ends.push((i, j));
+            } else {
+                others.push((i, j));
+            }
+        }
+    }
+    dead_ends.shuffle(rng);
+    others.shuffle(rng);
+    dead_ends.into_iter().chain(others).take(count).collect()
+}
+
+/// Nivel de prueba con una fila de texto por fila del laberinto y la leyenda por defecto más
+/// las líneas `c = tipo` de `legend_entries`.
+#[cfg(test)]
+pub fn test_level(rows: &[&str], legend_entries: &[&str]) -> crate::level::Level {
+    let maze = rows.iter().map(|row| row.chars().collect()).collect();
+    let mut legend = Legend::default();
+    for entry in legend_entries {
+        legend.parse_entry(entry).unwrap();
+    }
+    crate::level::Level::new(maze, legend)
 }
@@ -0,0 +1,186 @@
+// minimap.rs
+
+use crate::caster::cast_ray_from;
+use crate::enemy::Cell;
+use crate::level::Level;
+use crate::math::Vec2;
+use crate::maze::Maze;
+use crate::player::Player;
+
+/// Segundos que se muestra el minimapa cada vez que se pide en el modo limitado.
+pub const PEEK_SECONDS: f32 = 5.0;
+/// Rayos por cuadro que descubren celdas; bastan unos pocos para cubrir el campo de visión.
+const REVEAL_RAYS: usize = 48;
+/// Celdas recorridas que se recuerdan como migas de pan.
+const MAX_BREADCRUMBS: usize = 400;
+
+/// Cuándo se puede ver el minimapa.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MinimapMode {
+    /// Siempre visible.
+    Always,
+    /// Oculto salvo al presionar TAB, la cantidad de veces indicada por nivel.
+    Limited(u32),
+    /// Nunca se muestra.
+    Hidden,
+}
+
+impl MinimapMode {
+    /// Lee `siempre`, `oculto` o `limitado N`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut words = text.split_whitespace();
+        let mode = match (words.next()?, words.next()) {
+            ("siempre", None) => MinimapMode::Always,
+            ("oculto", None) => MinimapMode::Hidden,
+            ("limitado", Some(uses)) => MinimapMode::Limited(uses.parse().ok()?),
+            _ => return None,
+        };
+        words.next().is_none().then_some(mode)
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            MinimapMode::Always => "siempre".to_string(),
+            MinimapMode::Limited(uses) => format!("limitado {}", uses),
+            MinimapMode::Hidden => "oculto".to_string(),
+        }
+    }
+}
+
+/// Lo que el jugador ya descubrió del laberinto, el camino que siguió y el cono de visión
+/// del último cuadro.
+pub struct Minimap {
+    pub mode: MinimapMode,
+    seen: Vec<Vec<bool>>,
+    breadcrumbs: Vec<Cell>,
+    /// Posición del jugador en unidades de celda.
+    pub eye: Vec2,
+    /// Extremos de los rayos del último cuadro en unidades de celda, para dibujar el cono
+    /// de visión.
+    pub cone: Vec<Vec2>,
+    /// Posición del otro jugador en una carrera, en unidades de celda.
+    pub rival: Option<Vec2>,
+    uses_left: u32,
+    /// Segundos que le quedan visible en el modo limitado.
+    peek: f32,
+}
+
+impl Minimap {
+    pub fn new(mode: MinimapMode, maze: &Maze) -> Self {
+        let mut minimap = Self {
+            mode,
+            seen: Vec::new(),
+            breadcrumbs: Vec::new(),
+            eye: Vec2::zero(),
+            cone: Vec::new(),
+            rival: None,
+            uses_left: 0,
+            peek: 0.0,
+        };
+        minimap.reset(maze);
+        minimap
+    }
+
+    /// Vuelve a cubrir todo el laberinto y repone los usos del modo limitado.
+    pub fn reset(&mut self, maze: &Maze) {
+        self.seen = maze.iter().map(|row| vec![false; row.len()]).collect();
+        self.breadcrumbs.clear();
+        self.cone.clear();
+        self.uses_left = match self.mode {
+            MinimapMode::Limited(uses) => uses,
+            _ => 0,
+        };
+        self.peek = 0.0;
+    }
+
+    pub fn is_seen(&self, i: usize, j: usize) -> bool {
+        self.seen
+            .get(j)
+            .and_then(|row| row.get(i))
+            .copied()
+            .unwrap_or(false)
+    }
+
+    pub fn breadcrumbs(&self) -> &[Cell] {
+        &self.breadcrumbs
+    }
+
+    fn mark(&mut self, i: usize, j: usize) {
+        if let Some(seen) = self.seen.get_mut(j).and_then(|row| row.get_mut(i)) {
+            *seen = true;
+        }
+    }
+
+    /// Descubre la celda del jugador, sus cuatro vecinas y las que cruzan sus rayos, incluida la
+    /// pared donde terminan, y anota la celda actual en las migas de pan.
+    pub fn reveal(&mut self, level: &Level, player: &Player, block_size: usize) {
+        let block = block_size as f32;
+        let here = (
+            (player.pos.x / block) as usize,
+            (player.pos.y / block) as usize,
+        );
+        self.mark(here.0, here.1);
+        self.mark(here.0 + 1, here.1);
+        self.mark(here.0, here.1 + 1);
+        self.mark(here.0.saturating_sub(1), here.1);
+        self.mark(here.0, here.1.saturating_sub(1));
+        if self.breadcrumbs.last() != Some(&here) {
+            self.breadcrumbs.push(here);
+            if self.breadcrumbs.len() > MAX_BREADCRUMBS {
+                self.breadcrumbs.remove(0);
+            }
+        }
+
+        self.eye = Vec2::new(player.pos.x / block, player.pos.y / block);
+        self.cone.clear();
+        for r in 0..=REVEAL_RAYS {
+            let a = player.a - player.fov / 2.0 + player.fov * r as f32 / REVEAL_RAYS as f32;
+            let intersect = cast_ray_from(level, player.pos, player.a, a, block_size);
+            let (dx, dy) = (a.cos(), a.sin());
+            // Un poco más allá del impacto para marcar también la celda de la pared.
+            let end = intersect.distance + block * 0.05;
+            let mut t = 0.0;
+            while t <= end {
+                let (x, y) = (player.pos.x + dx * t, player.pos.y + dy * t);
+                if x >= 0.0 && y >= 0.0 {
+                    self.mark((x / block) as usize, (y / block) as usize);
+                }
+                t += block / 4.0;
+            }
+            let (x, y) = (player.pos.x + dx * end, player.pos.y + dy * end);
+            if x >= 0.0 && y >= 0.0 {
+                self.mark((x / block) as usize, (y / block) as usize);
+            }
+            self.cone
+                .push(Vec2::new(intersect.hit.x / block, intersect.hit.y / block));
+        }
+    }
+
+    /// Avanza el tiempo del modo limitado; `peek` pide mostrarlo si quedan usos.
+    pub fn update(&mut self, peek: bool, delta_time: f32) {
+        self.peek = (self.peek - delta_time).max(0.0);
+        if peek && self.peek == 0.0 && self.uses_left > 0 {
+            self.uses_left -= 1;
+            self.peek = PEEK_SECONDS;
+        }
+    }
+
+    pub fn visible(&self) -> bool {
+        match self.mode {
+            MinimapMode::Always => true,
+            MinimapMode::Limited(_) => self.peek > 0.0,
+            MinimapMode::Hidden => false,
+        }
+    }
+
+    /// Texto para el HUD en el modo limitado.
+    pub fn status(&self) -> Option<String> {
+        match self.mode {
+            MinimapMode::Limited(_) if self.peek > 0.0 => {
+                Some(format!("Minimapa: {:.0} s", self.peek.ceil()))
+            }
+            MinimapMode::Limited(_) => Some(format!("Minimapa (TAB): {}", self.uses_left)),
+            _ => None,
+        }
+    }
+}
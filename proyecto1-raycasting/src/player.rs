@@ -1,85 +1,340 @@
 // player.rs
 
-use raylib::prelude::*;
 use std::f32::consts::PI;
 
+use crate::level::Level;
+use crate::math::Vec2;
+
 pub struct Player {
-    pub pos: Vector2,
+    pub pos: Vec2,
     pub a: f32,
     pub fov: f32, // field of view
+    /// Radio del círculo con el que choca contra las paredes, en píxeles del mundo.
+    pub radius: f32,
+    /// Si en el cuadro anterior intentó avanzar contra una pared; el golpe cuenta sólo al
+    /// chocar, no mientras siga apoyado en ella.
+    pub against_wall: bool,
+    /// Elevación (en bloques) del piso sobre el que está parado; sigue a la del piso de su
+    /// celda con un poco de suavizado al subir o bajar escalones.
+    pub z: f32,
+    /// Cuánto mira hacia arriba (positivo) o abajo, como fracción de media pantalla que se
+    /// corre el horizonte. Sólo cambia la vista, no la simulación.
+    pub pitch: f32,
 }
 
-fn is_position_valid(x: f32, y: f32, maze: &crate::maze::Maze, block_size: usize) -> bool {
-    let i = (x / block_size as f32) as usize; // columna
-    let j = (y / block_size as f32) as usize; // fila
+/// Altura de los ojos sobre el piso, en bloques: la mitad de una pared normal.
+pub const EYE_HEIGHT: f32 = 0.5;
+/// Máximo que se puede mirar hacia arriba o abajo.
+const MAX_PITCH: f32 = 0.6;
+/// Rapidez con que la vista sigue a la elevación del piso, por segundo.
+const CLIMB_RATE: f32 = 12.0;
 
-    // Verifica que j (fila) esté dentro del rango de filas
-    if j >= maze.len() {
-        return false;
+impl Player {
+    /// Altura de los ojos sobre el suelo del nivel, en bloques.
+    pub fn eye(&self) -> f32 {
+        self.z + EYE_HEIGHT
     }
-    // Verifica que i (columna) esté dentro del rango de columnas
-    if i >= maze[j].len() {
-        return false;
+
+    /// Mira hacia arriba o abajo, sin pasar del límite.
+    pub fn look_vertical(&mut self, delta: f32) {
+        self.pitch = (self.pitch + delta).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+}
+
+/// Pasos por segundo de la simulación. Avanza siempre en pasos de la misma duración, sin
+/// importar los cuadros por segundo, para que una repetición dé el mismo resultado.
+pub const TICKS_PER_SECOND: u32 = 60;
+
+/// Velocidades con que se mueve y gira el jugador.
+#[derive(Clone, Copy, Debug)]
+pub struct Movement {
+    /// Píxeles del mundo por segundo.
+    pub move_speed: f32,
+    /// Radianes por segundo al girar con el teclado.
+    pub turn_speed: f32,
+}
+
+impl Default for Movement {
+    fn default() -> Self {
+        Self {
+            move_speed: 200.0,
+            turn_speed: PI,
+        }
+    }
+}
+
+/// Lo que pide el jugador en un paso de la simulación. Se lee del teclado y el mouse o de
+/// una repetición (ver `input::poll`); la simulación sólo ve esto, nunca la ventana.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct InputFrame {
+    pub forward: bool,
+    pub back: bool,
+    pub strafe_left: bool,
+    pub strafe_right: bool,
+    pub turn_left: bool,
+    pub turn_right: bool,
+    /// Abrir puertas y usar interruptores (E); sólo en el paso en que se aprieta.
+    pub interact: bool,
+    /// Mirar el minimapa en el modo limitado (TAB); sólo en el paso en que se aprieta.
+    pub peek_map: bool,
+    /// Giro con el mouse en radianes, ya multiplicado por la sensibilidad.
+    pub look: f32,
+}
+
+impl InputFrame {
+    /// Suma un cuadro más reciente a la entrada que espera el próximo paso: las teclas
+    /// mantenidas son las de `newer`, y los toques y el giro del mouse se acumulan para no
+    /// perderse si en un cuadro no toca ningún paso.
+    pub fn merge(&mut self, newer: InputFrame) {
+        let interact = self.interact || newer.interact;
+        let peek_map = self.peek_map || newer.peek_map;
+        let look = self.look + newer.look;
+        *self = Self {
+            interact,
+            peek_map,
+            look,
+            ..newer
+        };
+    }
+
+    /// La entrada que queda para el paso siguiente: las mismas teclas mantenidas, sin los
+    /// toques ni el giro que ya se usaron.
+    pub fn held(&self) -> Self {
+        Self {
+            interact: false,
+            peek_map: false,
+            look: 0.0,
+            ..*self
+        }
     }
 
-    maze[j][i] == ' ' // ' ' representa espacio vacío
+    /// Botones como bits, en el orden de los campos, para guardarlos en una repetición.
+    pub fn buttons(&self) -> u8 {
+        [
+            self.forward,
+            self.back,
+            self.strafe_left,
+            self.strafe_right,
+            self.turn_left,
+            self.turn_right,
+            self.interact,
+            self.peek_map,
+        ]
+        .iter()
+        .enumerate()
+        .fold(0, |bits, (i, &down)| bits | (u8::from(down) << i))
+    }
+
+    /// Inverso de `buttons`, con el giro del mouse aparte.
+    pub fn from_buttons(bits: u8, look: f32) -> Self {
+        let down = |i: u8| bits & (1 << i) != 0;
+        Self {
+            forward: down(0),
+            back: down(1),
+            strafe_left: down(2),
+            strafe_right: down(3),
+            turn_left: down(4),
+            turn_right: down(5),
+            interact: down(6),
+            peek_map: down(7),
+            look,
+        }
+    }
 }
 
+/// Empuja un círculo fuera de las celdas que bloquean el paso (fuera del laberinto, paredes,
+/// puertas cerradas y pisos más altos que un escalón) y devuelve si tocó alguna. La celda
+/// del centro no cuenta, para que un muro móvil que se cierra encima no deje al jugador
+/// atrapado.
+fn push_out(pos: Vec2, radius: f32, level: &Level, block_size: usize) -> (Vec2, bool) {
+    let block = block_size as f32;
+    let here = (
+        (pos.x / block).floor() as isize,
+        (pos.y / block).floor() as isize,
+    );
+    let from = (here.0.max(0) as usize, here.1.max(0) as usize);
+    let mut pos = pos;
+    let mut touched = false;
+    let (i0, i1) = ((pos.x - radius) / block, (pos.x + radius) / block);
+    let (j0, j1) = ((pos.y - radius) / block, (pos.y + radius) / block);
+    for j in j0.floor() as isize..=j1.floor() as isize {
+        for i in i0.floor() as isize..=i1.floor() as isize {
+            let blocked = i < 0 || j < 0 || !level.can_step(from, (i as usize, j as usize));
+            if !blocked || (i, j) == here {
+                continue;
+            }
+            // Punto de la celda más cercano al centro del círculo
+            let (left, top) = (i as f32 * block, j as f32 * block);
+            let closest = Vec2::new(
+                pos.x.clamp(left, left + block),
+                pos.y.clamp(top, top + block),
+            );
+            let away = pos - closest;
+            let distance = away.x.hypot(away.y);
+            if distance < radius && distance > 0.0 {
+                pos = pos + away * ((radius - distance) / distance);
+                touched = true;
+            }
+        }
+    }
+    (pos, touched)
+}
+
+/// Gira y mueve al jugador según la entrada del paso. Devuelve `true` en el paso en que
+/// choca con una pared, para que suene el golpe.
 pub fn process_events(
     player: &mut Player,
-    rl: &RaylibHandle,
+    input: &InputFrame,
     delta_time: f32,
-    maze: &crate::maze::Maze,
+    level: &Level,
     block_size: usize,
-    steps_sfx: &Sound,
-) {
-    const MOVE_SPEED: f32 = 200.0;
-    const ROTATION_SPEED: f32 = PI;
+    movement: &Movement,
+) -> bool {
+    // Rotación con teclado y con el movimiento horizontal del mouse
+    if input.turn_right {
+        player.a += movement.turn_speed * delta_time;
+    }
+    if input.turn_left {
+        player.a -= movement.turn_speed * delta_time;
+    }
+    player.a += input.look;
 
-    // Rotación con teclado (flechas y A/D)
-    if rl.is_key_down(KeyboardKey::KEY_RIGHT) || rl.is_key_down(KeyboardKey::KEY_D) {
-        player.a += ROTATION_SPEED * delta_time;
+    // Dirección pedida: adelante/atrás según la vista y de lado perpendicular a ella
+    let forward = Vec2::new(player.a.cos(), player.a.sin());
+    let right = Vec2::new(-player.a.sin(), player.a.cos());
+    let mut direction = Vec2::new(0.0, 0.0);
+    if input.forward {
+        direction = direction + forward;
     }
-    if rl.is_key_down(KeyboardKey::KEY_LEFT) || rl.is_key_down(KeyboardKey::KEY_A) {
-        player.a -= ROTATION_SPEED * delta_time;
+    if input.back {
+        direction = direction - forward;
     }
+    if input.strafe_right {
+        direction = direction + right;
+    }
+    if input.strafe_left {
+        direction = direction - right;
+    }
+    // La vista sube o baja hacia el piso de la celda en que está
+    let floor = level.floor_at(player.pos, block_size);
+    player.z += (floor - player.z) * (CLIMB_RATE * delta_time).min(1.0);
 
-    // Rotación con mouse (horizontal)
-    let mouse_delta = rl.get_mouse_delta().x;
-    player.a += mouse_delta * 0.01; // Ajusta el factor para sensibilidad
+    let length = direction.x.hypot(direction.y);
+    if length == 0.0 {
+        player.against_wall = false;
+        return false;
+    }
+
+    // Avanza en pasos menores que el radio para no atravesar esquinas; lo que se empuja
+    // fuera de las paredes conserva la componente paralela, así que se desliza por ellas.
+    let distance = movement.move_speed * delta_time;
+    let steps = (distance / (player.radius / 2.0)).ceil().max(1.0);
+    let step = direction * (distance / length / steps);
+    let mut touched = false;
+    for _ in 0..steps as usize {
+        let (pos, hit) = push_out(player.pos + step, player.radius, level, block_size);
+        player.pos = pos;
+        touched |= hit;
+    }
+
+    // Choca una vez, no en cada cuadro que se sigue empujando contra la pared
+    let bumped = touched && !player.against_wall;
+    player.against_wall = touched;
+    bumped
+}
 
-    let mut new_x = player.pos.x;
-    let mut new_y = player.pos.y;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::test_level;
 
-    let mut intento_mover = false;
-    let mut choco_pared = false;
+    const BLOCK: usize = 100;
+    const TICK: f32 = 1.0 / TICKS_PER_SECOND as f32;
 
-    // Movimiento con teclado (flechas y W/S)
-    if rl.is_key_down(KeyboardKey::KEY_DOWN) || rl.is_key_down(KeyboardKey::KEY_S) {
-        new_x -= MOVE_SPEED * player.a.cos() * delta_time;
-        new_y -= MOVE_SPEED * player.a.sin() * delta_time;
-        intento_mover = true;
+    /// Nivel de prueba; `D` es una puerta sin llave.
+    fn level(rows: &[&str]) -> Level {
+        test_level(rows, &["D = puerta"])
     }
-    if rl.is_key_down(KeyboardKey::KEY_UP) || rl.is_key_down(KeyboardKey::KEY_W) {
-        new_x += MOVE_SPEED * player.a.cos() * delta_time;
-        new_y += MOVE_SPEED * player.a.sin() * delta_time;
-        intento_mover = true;
+
+    fn player_at(x: f32, y: f32, a: f32) -> Player {
+        Player {
+            pos: Vec2::new(x, y),
+            a,
+            fov: PI / 3.0,
+            radius: BLOCK as f32 * 0.25,
+            against_wall: false,
+            z: 0.0,
+            pitch: 0.0,
+        }
+    }
+
+    /// Camina hacia adelante `ticks` pasos y devuelve cuántas veces chocó.
+    fn walk(player: &mut Player, level: &Level, ticks: usize, movement: &Movement) -> usize {
+        let input = InputFrame {
+            forward: true,
+            ..InputFrame::default()
+        };
+        (0..ticks)
+            .filter(|_| process_events(player, &input, TICK, level, BLOCK, movement))
+            .count()
+    }
+
+    #[test]
+    fn push_out_leaves_the_circle_touching_the_wall() {
+        let level = level(&["+++", "+ +", "+++"]);
+        let (pos, touched) = push_out(Vec2::new(110.0, 150.0), 25.0, &level, BLOCK);
+        assert!(touched);
+        assert!((pos.x - 125.0).abs() < 1e-3);
+        assert_eq!(pos.y, 150.0);
+
+        let (pos, touched) = push_out(Vec2::new(150.0, 150.0), 25.0, &level, BLOCK);
+        assert!(!touched);
+        assert_eq!(pos, Vec2::new(150.0, 150.0));
     }
 
-    // Validación de movimiento
-    if is_position_valid(new_x, player.pos.y, maze, block_size) {
-        player.pos.x = new_x;
-    } else if intento_mover {
-        choco_pared = true;
+    #[test]
+    fn slides_along_a_wall() {
+        let level = level(&["+++++++", "+     +", "+++++++"]);
+        // En diagonal contra la pared de arriba: avanza por el pasillo sin atravesarla
+        let mut player = player_at(150.0, 150.0, -PI / 4.0);
+        let bumps = walk(&mut player, &level, 30, &Movement::default());
+        assert_eq!(bumps, 1, "choca una vez, no en cada paso");
+        assert!(player.against_wall);
+        assert!(player.pos.x > 210.0, "x = {}", player.pos.x);
+        assert!(player.pos.y >= 125.0 - 1e-3);
     }
-    if is_position_valid(player.pos.x, new_y, maze, block_size) {
-        player.pos.y = new_y;
-    } else if intento_mover {
-        choco_pared = true;
+
+    #[test]
+    fn does_not_pass_through_corners() {
+        // Dos celdas libres que sólo se tocan por la esquina
+        let level = level(&["+++++", "+ +++", "++ ++", "+++++"]);
+        let mut player = player_at(150.0, 150.0, PI / 4.0);
+        walk(&mut player, &level, 120, &Movement::default());
+        assert!(player.pos.x <= 175.0 + 1e-3 && player.pos.y <= 175.0 + 1e-3);
     }
 
-    // Si intentó moverse y chocó con una pared, reproduce el sonido
-    if choco_pared {
-        steps_sfx.play();
+    #[test]
+    fn fast_steps_do_not_tunnel_through_walls() {
+        let level = level(&["+++++", "+ + +", "+++++"]);
+        let mut player = player_at(150.0, 150.0, 0.0);
+        let fast = Movement {
+            move_speed: 20000.0,
+            ..Movement::default()
+        };
+        walk(&mut player, &level, 1, &fast);
+        assert!(player.pos.x <= 175.0 + 1e-3, "x = {}", player.pos.x);
+    }
+
+    #[test]
+    fn closed_door_blocks_until_opened() {
+        let mut level = level(&["+++++", "+ D +", "+++++"]);
+        let mut player = player_at(150.0, 150.0, 0.0);
+        walk(&mut player, &level, 60, &Movement::default());
+        assert!(player.pos.x <= 175.0 + 1e-3, "x = {}", player.pos.x);
+
+        level.interact(player.pos, player.a, BLOCK);
+        level.update(1.0);
+        walk(&mut player, &level, 60, &Movement::default());
+        assert!(player.pos.x > 300.0, "x = {}", player.pos.x);
     }
 }
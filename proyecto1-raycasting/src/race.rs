@@ -0,0 +1,156 @@
+// race.rs
+
+use raycaster::color::Rgba;
+use raycaster::framebuffer::Framebuffer;
+use raycaster::level_file::LevelFile;
+use raycaster::player::InputFrame;
+use raycaster::session::{Outcome, Session};
+use raycaster::textures::LoadImage;
+
+use crate::audio::GameAudio;
+use crate::input::{Controls, KeyBindings};
+
+/// Ancho en píxeles de la línea que separa las dos mitades de la pantalla.
+const DIVIDER: u32 = 2;
+
+/// Carrera local a pantalla dividida: dos jugadores en el mismo laberinto, cada uno con su
+/// partida (puertas, copas, vidas y rivales propios) y su mitad de la ventana. Gana el
+/// primero que llega a la salida.
+pub struct Race {
+    pub racers: [Session; 2],
+    /// Teclas de cada jugador, el primero a la izquierda del teclado y el segundo a la
+    /// derecha, y un gamepad para cada uno si hay dos conectados.
+    pub controls: [Controls; 2],
+    /// Vista de cada jugador antes de copiarla a su mitad.
+    views: [Framebuffer; 2],
+}
+
+impl Race {
+    /// Arma el nivel `number` con la misma semilla para los dos. `fov` es el de la pantalla
+    /// completa: cada mitad usa uno más angosto para que las paredes no se vean aplastadas.
+    pub fn new(
+        number: u8,
+        campaign: &[LevelFile],
+        seed: u64,
+        block_size: usize,
+        fov: f32,
+        load: LoadImage,
+    ) -> Self {
+        let half_fov = 2.0 * ((fov / 2.0).tan() / 2.0).atan();
+        // Sólo el primero mueve los sonidos; con dos oyentes la meta y los rivales se
+        // oirían de los dos lados a la vez
+        let racer = |listener| {
+            Session::for_race(number, campaign, seed, block_size, half_fov, listener, load)
+        };
+        let mut race = Self {
+            racers: [racer(true), racer(false)],
+            controls: [
+                Controls::racer(KeyBindings::left_racer(), 0),
+                Controls::racer(KeyBindings::right_racer(), 1),
+            ],
+            views: [Framebuffer::new(1, 1), Framebuffer::new(1, 1)],
+        };
+        race.show_rivals(block_size);
+        race
+    }
+
+    /// Vuelve a largar la carrera en el mismo laberinto.
+    pub fn restart(&mut self, block_size: usize) {
+        for racer in self.racers.iter_mut() {
+            racer.restart(block_size);
+        }
+        self.show_rivals(block_size);
+    }
+
+    /// Cada jugador ve al otro como un sprite y como un punto en su minimapa.
+    fn show_rivals(&mut self, block_size: usize) {
+        let positions = self.racers.each_ref().map(|racer| racer.player.pos);
+        self.racers[0].set_rival(positions[1], block_size);
+        self.racers[1].set_rival(positions[0], block_size);
+    }
+
+    /// Avanza las dos partidas `delta_time` segundos. Devuelve el resultado si la carrera
+    /// terminó: gana quien llega primero o el que queda en pie si el otro pierde.
+    pub fn advance(
+        &mut self,
+        inputs: [InputFrame; 2],
+        delta_time: f32,
+        block_size: usize,
+        audio: &mut GameAudio,
+    ) -> Option<String> {
+        let [first, second] = inputs;
+        let outcomes = [
+            self.racers[0].advance(delta_time, first, block_size, audio),
+            self.racers[1].advance(delta_time, second, block_size, audio),
+        ];
+        self.show_rivals(block_size);
+
+        match outcomes {
+            [None, None] => None,
+            [Some(Outcome::Won(a)), Some(Outcome::Won(b))] if a.time == b.time => {
+                Some(format!("¡Empate en {:.1} s!", a.time))
+            }
+            [Some(Outcome::Won(a)), Some(Outcome::Won(b))] => {
+                let winner = if a.time < b.time { 1 } else { 2 };
+                Some(format!(
+                    "¡Gana el jugador {} en {:.1} s!",
+                    winner,
+                    a.time.min(b.time)
+                ))
+            }
+            [Some(Outcome::Won(stats)), _] => {
+                Some(format!("¡Gana el jugador 1 en {:.1} s!", stats.time))
+            }
+            [_, Some(Outcome::Won(stats))] => {
+                Some(format!("¡Gana el jugador 2 en {:.1} s!", stats.time))
+            }
+            [Some(Outcome::Lost(_)), Some(Outcome::Lost(_))] => {
+                Some("Nadie llegó a la salida".to_string())
+            }
+            [Some(Outcome::Lost(reason)), None] => {
+                Some(format!("Jugador 1: {} Gana el jugador 2", reason))
+            }
+            [None, Some(Outcome::Lost(reason))] => {
+                Some(format!("Jugador 2: {} Gana el jugador 1", reason))
+            }
+        }
+    }
+
+    /// Dibuja la vista de cada jugador en su mitad de `framebuffer`, con una línea negra en
+    /// el medio.
+    pub fn render(
+        &mut self,
+        framebuffer: &mut Framebuffer,
+        block_size: usize,
+        minimap_cell: usize,
+        time: f32,
+    ) {
+        let (width, height) = (framebuffer.width.div_ceil(2), framebuffer.height);
+        for (racer, view) in self.racers.iter().zip(self.views.iter_mut()) {
+            if view.width != width || view.height != height {
+                *view = Framebuffer::new(width, height);
+            }
+            racer.render(view, block_size, minimap_cell, time);
+        }
+        framebuffer.blit(&self.views[0], 0, 0);
+        framebuffer.blit(&self.views[1], framebuffer.width - width, 0);
+
+        framebuffer.set_current_color(Rgba::BLACK);
+        let middle = framebuffer.width / 2;
+        for x in middle.saturating_sub(DIVIDER / 2)..middle + DIVIDER / 2 {
+            for y in 0..framebuffer.height {
+                framebuffer.set_pixel(x, y);
+            }
+        }
+    }
+
+    /// Estado de la partida de cada jugador, para su mitad del HUD.
+    pub fn hud(&self) -> [String; 2] {
+        self.racers.each_ref().map(|racer| racer.hud().1)
+    }
+
+    /// Nombre del nivel, el mismo para los dos.
+    pub fn level_name(&self) -> String {
+        self.racers[0].hud().0
+    }
+}
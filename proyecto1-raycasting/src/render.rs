@@ -0,0 +1,599 @@
+// render.rs
+
+use std::f32::consts::PI;
+use std::ops::Range;
+use std::thread;
+
+use crate::caster::{Intersect, RayWalk, Side};
+use crate::color::Rgba;
+use crate::editor::{Editor, EditorView};
+use crate::framebuffer::Framebuffer;
+use crate::level::{Level, RayCell, WALL_HEIGHT};
+use crate::level_file::EntityKind;
+use crate::lighting::SIDE_SHADE;
+use crate::line::line;
+use crate::math::Vec2;
+use crate::maze::{KeyColor, Tile};
+use crate::minimap::Minimap;
+use crate::player::Player;
+use crate::sprite::{Sprite, render_sprites};
+use crate::textures::{CpuImage, WorldTextures};
+
+fn cell_to_color(cell: char) -> Rgba {
+    match cell {
+        '+' => Rgba::BLUEVIOLET,
+        '-' | '|' => Rgba::VIOLET,
+        'g' => Rgba::GREEN,
+        _ => Rgba::WHITE,
+    }
+}
+
+/// Color de una celda en la vista 2D según su tipo; `None` si no se dibuja.
+fn tile_color(level: &Level, i: usize, j: usize) -> Option<Rgba> {
+    match level.tile(i, j) {
+        Tile::Empty => None,
+        Tile::Door { .. } if level.door_open(i, j) >= 1.0 => None,
+        Tile::Door { key } => Some(key.map_or(Rgba::BROWN, KeyColor::color)),
+        Tile::Key(color) => Some(color.color()),
+        Tile::Switch { .. } => Some(Rgba::ORANGE),
+        Tile::MovingWall { .. } if level.group_open(level.cell(i, j)) => None,
+        Tile::MovingWall { .. } => Some(Rgba::DARKPURPLE),
+        _ => Some(cell_to_color(level.cell(i, j))),
+    }
+}
+
+fn draw_cell(
+    framebuffer: &mut Framebuffer,
+    xo: usize,
+    yo: usize,
+    block_size: usize,
+    level: &Level,
+    (i, j): (usize, usize),
+    goal_tex: Option<&CpuImage>,
+) {
+    let tile = level.tile(i, j);
+    let Some(color) = tile_color(level, i, j) else {
+        return;
+    };
+    if tile == Tile::Goal
+        && let Some(tex) = goal_tex
+    {
+        // Dibuja la textura goal.jpg en el bloque
+        for x in 0..block_size {
+            for y in 0..block_size {
+                let u = x as f32 / block_size as f32;
+                let v = y as f32 / block_size as f32;
+                let color = tex.sample(u, v);
+                framebuffer.set_current_color(color);
+                framebuffer.set_pixel((xo + x) as u32, (yo + y) as u32);
+            }
+        }
+        return;
+    }
+    framebuffer.set_current_color(color);
+
+    // Las llaves son un cuadro pequeño en el centro de la celda.
+    let margin = if matches!(tile, Tile::Key(_)) {
+        block_size * 3 / 8
+    } else {
+        0
+    };
+    for x in xo + margin..xo + block_size - margin {
+        for y in yo + margin..yo + block_size - margin {
+            framebuffer.set_pixel(x as u32, y as u32);
+        }
+    }
+}
+
+/// Vista del editor: el laberinto escalado a `view`, las entidades y la celda bajo el mouse.
+pub fn render_editor(
+    framebuffer: &mut Framebuffer,
+    level: &Level,
+    editor: &Editor,
+    view: EditorView,
+    goal_tex: Option<&CpuImage>,
+) {
+    let cell = view.cell;
+    for (j, row) in level.maze.iter().enumerate() {
+        for i in 0..row.len() {
+            draw_cell(
+                framebuffer,
+                view.x + i * cell,
+                view.y + j * cell,
+                cell,
+                level,
+                (i, j),
+                goal_tex,
+            );
+        }
+    }
+
+    // Inicio y entidades como cuadros en el centro de su celda
+    let mut marker = |(i, j): (usize, usize), color: Rgba| {
+        framebuffer.set_current_color(color);
+        let margin = cell / 4;
+        for x in margin..cell - margin {
+            for y in margin..cell - margin {
+                framebuffer.set_pixel(
+                    (view.x + i * cell + x) as u32,
+                    (view.y + j * cell + y) as u32,
+                );
+            }
+        }
+    };
+    marker(level.start, Rgba::LIME);
+    for entity in editor.entities() {
+        let color = match entity.kind {
+            EntityKind::Cup => Rgba::GOLD,
+            EntityKind::Enemy => Rgba::RED,
+        };
+        marker(entity.cell, color);
+    }
+    for light in editor.lights() {
+        marker(light.cell, light.color);
+    }
+
+    if let Some((i, j)) = editor.hover {
+        framebuffer.set_current_color(Rgba::WHITE);
+        let x0 = (view.x + i * cell) as f32;
+        let y0 = (view.y + j * cell) as f32;
+        let x1 = x0 + cell as f32 - 1.0;
+        let y1 = y0 + cell as f32 - 1.0;
+        line(framebuffer, Vec2::new(x0, y0), Vec2::new(x1, y0));
+        line(framebuffer, Vec2::new(x1, y0), Vec2::new(x1, y1));
+        line(framebuffer, Vec2::new(x1, y1), Vec2::new(x0, y1));
+        line(framebuffer, Vec2::new(x0, y1), Vec2::new(x0, y0));
+    }
+}
+
+/// Distancia al plano de proyección usada para escalar paredes y piso.
+pub const DISTANCE_TO_PROJECTION_PLANE: f32 = 70.0;
+/// Veces que se repite la textura del cielo en una vuelta completa.
+const SKY_REPEAT: f32 = 2.0;
+
+/// Proyección de la vista 3D: desde dónde mira el jugador, dónde queda el horizonte y
+/// cuánto ocupa en pantalla un bloque de alto a cada distancia.
+pub struct Camera {
+    pub pos: Vec2,
+    pub angle: f32,
+    /// Tangente de medio campo de visión.
+    pub half_plane: f32,
+    /// Fila del horizonte; mirar arriba o abajo la corre (y-shearing) sin inclinar las paredes.
+    pub horizon: f32,
+    /// Altura de los ojos en bloques.
+    pub eye: f32,
+    /// Píxeles de pantalla que ocupa un bloque de alto a un píxel del mundo de distancia.
+    pub scale: f32,
+    pub block_size: usize,
+}
+
+impl Camera {
+    pub fn new(player: &Player, height: u32, block_size: usize) -> Self {
+        let hh = height as f32 / 2.0;
+        Self {
+            pos: player.pos,
+            angle: player.a,
+            half_plane: (player.fov / 2.0).tan(),
+            horizon: hh + player.pitch * hh,
+            eye: player.eye(),
+            scale: hh * DISTANCE_TO_PROJECTION_PLANE,
+            block_size,
+        }
+    }
+
+    /// Fila de la pantalla donde se ve la altura `h` a la distancia perpendicular `depth`.
+    pub fn row(&self, h: f32, depth: f32) -> f32 {
+        self.horizon + (self.eye - h) * self.scale / depth
+    }
+
+    /// Altura que ve la fila `y` sobre una cara vertical a la distancia `depth`.
+    fn height_at(&self, y: usize, depth: f32) -> f32 {
+        self.eye - (y as f32 + 0.5 - self.horizon) * depth / self.scale
+    }
+
+    /// Distancia perpendicular a la que la fila `y` ve una superficie plana a la altura `h`.
+    fn flat_distance(&self, h: f32, y: usize) -> f32 {
+        (self.eye - h) * self.scale / (y as f32 + 0.5 - self.horizon)
+    }
+}
+
+/// Lo que se ve en un tramo de filas de una columna.
+enum Surface<'a> {
+    /// Cara vertical de una pared, una puerta o un escalón.
+    Face {
+        depth: f32,
+        texture: &'a CpuImage,
+        u: f32,
+        light: [f32; 3],
+    },
+    /// Piso, o techo de un muro bajo, a la altura `height`.
+    Flat { height: f32, texture: &'a CpuImage },
+}
+
+struct Span<'a> {
+    rows: Range<usize>,
+    surface: Surface<'a>,
+}
+
+/// Lo que comparten todas las columnas de un cuadro.
+struct View<'a> {
+    level: &'a Level,
+    textures: &'a WorldTextures,
+    camera: &'a Camera,
+    rows: usize,
+    /// Lo más alto del nivel: cuando ya no puede asomar nada el rayo deja de avanzar.
+    max_height: f32,
+}
+
+/// Agrega el tramo de filas `[from, to)` recortado a las que siguen libres (`open`), y
+/// achica `open` si el tramo llega a uno de sus bordes.
+fn cover<'a>(
+    spans: &mut Vec<Span<'a>>,
+    open: &mut Range<usize>,
+    (from, to): (f32, f32),
+    surface: Surface<'a>,
+) {
+    let first = (from.max(0.0) as usize).max(open.start);
+    let last = (to.max(0.0) as usize).min(open.end);
+    if first >= last {
+        return;
+    }
+    if first <= open.start {
+        open.start = last;
+    }
+    if last >= open.end {
+        open.end = first;
+    }
+    spans.push(Span {
+        rows: first..last,
+        surface,
+    });
+}
+
+/// Recorre el rayo de una columna con ángulo `a` y devuelve lo que ve, de adelante hacia
+/// atrás. No se detiene en la primera pared: sigue por encima de los muros bajos y de los
+/// desniveles hasta que no queda fila libre, nada más alto puede asomar o sale del
+/// laberinto.
+fn cast_column<'a>(view: &View<'a>, a: f32) -> Vec<Span<'a>> {
+    let (level, textures, camera) = (view.level, view.textures, view.camera);
+    let block = camera.block_size as f32;
+    let correction = (a - camera.angle).cos();
+    let mut walk = RayWalk::new(camera.pos, a, camera.angle, block);
+    let mut spans = Vec::new();
+    let mut open = 0..view.rows;
+
+    // Superficie de la celda que recorre el rayo y distancia a la que empezó a verse
+    let here = (walk.map_x.max(0) as usize, walk.map_y.max(0) as usize);
+    let mut flat = (level.floor_height(here.0, here.1), &textures.floor);
+    let mut near = 0.0;
+
+    loop {
+        let (dist, side) = walk.advance();
+        let far = dist * block * correction;
+        let (height, texture) = flat;
+        if camera.eye > height {
+            let rows = (camera.row(height, far), camera.row(height, near));
+            cover(
+                &mut spans,
+                &mut open,
+                rows,
+                Surface::Flat { height, texture },
+            );
+        }
+
+        // Lo que está más lejos se ve más cerca del horizonte; si ni lo más alto del nivel
+        // llega a las filas libres, sólo queda cielo.
+        let lowest_visible = if view.max_height > camera.eye {
+            camera.row(view.max_height, far)
+        } else {
+            camera.horizon
+        };
+        if open.is_empty() || open.end as f32 <= lowest_visible {
+            break;
+        }
+        if walk.map_x < 0 || walk.map_y < 0 {
+            break;
+        }
+        let (i, j) = (walk.map_x as usize, walk.map_y as usize);
+        if !level.contains(i, j) {
+            break;
+        }
+
+        // Cara vertical desde `base` hasta `top`. Se ilumina con la luz de la celda libre
+        // frente a ella; las caras norte/sur se oscurecen un poco para distinguir la
+        // orientación.
+        let mut face = |base: f32, top: f32, hit: Intersect, texture| {
+            let back = hit.hit - Vec2::new(a.cos(), a.sin()) * (0.01 * block);
+            let mut light = level.lighting.at(back.x, back.y);
+            if hit.side == Side::Horizontal {
+                light = light.map(|l| l * SIDE_SHADE);
+            }
+            // La distancia perpendicular corrige el efecto ojo de pez.
+            let depth = hit.perpendicular_distance.max(0.0001);
+            let rows = (camera.row(top, depth), camera.row(base, depth));
+            let surface = Surface::Face {
+                depth,
+                texture,
+                u: hit.wall_u,
+                light,
+            };
+            cover(&mut spans, &mut open, rows, surface);
+        };
+
+        let impact = level.cell(i, j);
+        match level.ray_cell(i, j) {
+            RayCell::Empty => {
+                // Escalón hacia un piso más alto
+                let floor = level.floor_height(i, j);
+                if floor > height {
+                    let hit = walk.intersect(dist, side, impact);
+                    face(height, floor, hit, &textures.floor);
+                }
+                flat = (floor, &textures.floor);
+            }
+            RayCell::Solid => {
+                // Selecciona la textura según el tipo de pared; su techo se ve desde arriba
+                let hit = walk.intersect(dist, side, impact);
+                let texture = textures.for_wall(level, hit.impact);
+                let top = level.wall_height(i, j);
+                face(0.0, top, hit, texture);
+                flat = (top, texture);
+            }
+            RayCell::Door(fraction) => {
+                if let Some(hit) = walk.door_hit(dist, side, fraction, impact) {
+                    let texture = textures.for_wall(level, hit.impact);
+                    face(0.0, level.wall_height(i, j), hit, texture);
+                }
+                flat = (0.0, &textures.floor);
+            }
+        }
+        near = far;
+    }
+    spans
+}
+
+/// Color y profundidad de un punto de una superficie plana a la altura `height`, visto desde
+/// la fila `y` en la dirección `dir` (ya dividida por el coseno relativo a la vista).
+fn shade_flat(
+    view: &View,
+    height: f32,
+    texture: &CpuImage,
+    dir: (f32, f32),
+    y: usize,
+) -> (Rgba, f32) {
+    let camera = view.camera;
+    let block = camera.block_size as f32;
+    let depth = camera.flat_distance(height, y);
+    let point = camera.pos + Vec2::new(dir.0, dir.1) * depth;
+    let u = (point.x / block).rem_euclid(1.0);
+    let v = (point.y / block).rem_euclid(1.0);
+    let lighting = &view.level.lighting;
+    let color = lighting.shade(texture.sample(u, v), depth, lighting.at(point.x, point.y));
+    (color, depth)
+}
+
+/// Color y profundidad del píxel de la fila `y` según los tramos de su columna; el primero
+/// que la cubre es el más cercano. Lo que no cubre ninguno es cielo o, debajo del
+/// horizonte, suelo fuera del laberinto.
+fn shade_pixel(view: &View, spans: &[Span], dir: (f32, f32), sky_u: f32, y: usize) -> (Rgba, f32) {
+    let camera = view.camera;
+    match spans.iter().find(|span| span.rows.contains(&y)) {
+        Some(Span {
+            surface:
+                Surface::Face {
+                    depth,
+                    texture,
+                    u,
+                    light,
+                },
+            ..
+        }) => {
+            // `v` sale de la altura en el mundo, así la textura no se comprime al acercarse
+            // y se repite en las paredes de más de un bloque.
+            let v = (WALL_HEIGHT - camera.height_at(y, *depth)).rem_euclid(1.0);
+            let color = view
+                .level
+                .lighting
+                .shade(texture.sample(*u, v), *depth, *light);
+            (color, *depth)
+        }
+        Some(Span {
+            surface: Surface::Flat { height, texture },
+            ..
+        }) => shade_flat(view, *height, texture, dir, y),
+        None if (y as f32) < camera.horizon => {
+            let hh = view.rows as f32 / 2.0;
+            let v = (y as f32 - camera.horizon + hh) / hh;
+            (view.textures.sky.sample(sky_u, v), f32::INFINITY)
+        }
+        None => shade_flat(view, 0.0, &view.textures.floor, dir, y),
+    }
+}
+
+/// Hilos entre los que se reparte la vista 3D.
+fn worker_count() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Vista 3D en dos pasadas repartidas entre varios hilos: primero un rayo por columna, que
+/// anota qué se ve en cada tramo de filas, y después franjas de filas que escriben directo
+/// en los píxeles del framebuffer. Cada píxel se escribe una sola vez (pared, piso o cielo),
+/// así que no hace falta limpiar antes; su profundidad queda para recortar los sprites.
+pub fn render_world(
+    framebuffer: &mut Framebuffer,
+    level: &Level,
+    block_size: usize,
+    player: &Player,
+    textures: &WorldTextures,
+    sprites: &[Sprite],
+    time: f32,
+) {
+    let width = framebuffer.width as usize;
+    let camera = Camera::new(player, framebuffer.height, block_size);
+    let view = View {
+        level,
+        textures,
+        camera: &camera,
+        rows: framebuffer.height as usize,
+        max_height: level.max_height(),
+    };
+    let workers = worker_count();
+
+    // Ángulo de cada columna; lo comparten el cielo, el piso y las paredes. Las columnas se
+    // reparten uniformemente sobre el plano de proyección (no en ángulo) para que las
+    // paredes rectas se vean rectas.
+    let angles: Vec<f32> = (0..width)
+        .map(|i| {
+            let camera_x = 2.0 * (i as f32 + 0.5) / width as f32 - 1.0;
+            player.a + (camera_x * camera.half_plane).atan()
+        })
+        .collect();
+
+    // El cielo envuelve al jugador según el ángulo de cada columna
+    let sky_u: Vec<f32> = angles
+        .iter()
+        .map(|a| (a / (2.0 * PI) * SKY_REPEAT).rem_euclid(1.0))
+        .collect();
+
+    // Cada fila de un piso corresponde a una distancia perpendicular fija desde el jugador.
+    // La dirección se divide entre el coseno relativo para pasar de distancia perpendicular
+    // a distancia a lo largo del rayo de cada columna.
+    let dirs: Vec<(f32, f32)> = angles
+        .iter()
+        .map(|a| {
+            let correction = (a - player.a).cos();
+            (a.cos() / correction, a.sin() / correction)
+        })
+        .collect();
+
+    // --- Rayos: uno por columna, en tramos contiguos por hilo ---
+    let per_worker = width.div_ceil(workers).max(1);
+    let columns: Vec<Vec<Span>> = thread::scope(|scope| {
+        let view = &view;
+        let handles: Vec<_> = angles
+            .chunks(per_worker)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|&a| cast_column(view, a))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("falló un hilo de la vista 3D"))
+            .collect()
+    });
+
+    // --- Píxeles: cada hilo pinta una franja de filas completas ---
+    let stride = width.max(1);
+    let band_rows = (framebuffer.height as usize).div_ceil(workers).max(1);
+    let mut depth = vec![f32::INFINITY; framebuffer.pixels.len()];
+    thread::scope(|scope| {
+        let bands = framebuffer
+            .pixels
+            .chunks_mut(band_rows * stride)
+            .zip(depth.chunks_mut(band_rows * stride));
+        for (band, (pixels, depths)) in bands.enumerate() {
+            let (view, columns, sky_u, dirs) = (&view, &columns, &sky_u, &dirs);
+            scope.spawn(move || {
+                let rows = pixels.chunks_mut(stride).zip(depths.chunks_mut(stride));
+                for (row, (line, line_depth)) in rows.enumerate() {
+                    let y = band * band_rows + row;
+                    for x in 0..line.len() {
+                        (line[x], line_depth[x]) =
+                            shade_pixel(view, &columns[x], dirs[x], sky_u[x], y);
+                    }
+                }
+            });
+        }
+    });
+
+    render_sprites(framebuffer, sprites, textures, level, &camera, &depth, time);
+}
+
+/// Color de las celdas que el jugador todavía no ha visto.
+const FOG_COLOR: Rgba = Rgba::new(25, 25, 30, 255);
+
+/// Minimapa con niebla de guerra: sólo las celdas descubiertas, las migas de pan del camino
+/// recorrido, el cono de visión y el jugador.
+pub fn render_minimap(
+    framebuffer: &mut Framebuffer,
+    level: &Level,
+    minimap: &Minimap,
+    block_size: usize,
+    offset_x: usize,
+    offset_y: usize,
+    goal_tex: Option<&CpuImage>,
+) {
+    // Celdas descubiertas; el resto queda cubierto
+    for (row_index, row) in level.maze.iter().enumerate() {
+        for col_index in 0..row.len() {
+            let xo = offset_x + col_index * block_size;
+            let yo = offset_y + row_index * block_size;
+            if minimap.is_seen(col_index, row_index) {
+                draw_cell(
+                    framebuffer,
+                    xo,
+                    yo,
+                    block_size,
+                    level,
+                    (col_index, row_index),
+                    goal_tex,
+                );
+            } else {
+                framebuffer.set_current_color(FOG_COLOR);
+                for x in xo..xo + block_size {
+                    for y in yo..yo + block_size {
+                        framebuffer.set_pixel(x as u32, y as u32);
+                    }
+                }
+            }
+        }
+    }
+
+    let to_minimap = |p: Vec2| {
+        Vec2::new(
+            offset_x as f32 + p.x * block_size as f32,
+            offset_y as f32 + p.y * block_size as f32,
+        )
+    };
+
+    // Migas de pan en el centro de cada celda recorrida
+    framebuffer.set_current_color(Rgba::SKYBLUE);
+    let dot = (block_size / 6).max(1);
+    for &(i, j) in minimap.breadcrumbs() {
+        let cx = offset_x + i * block_size + block_size / 2;
+        let cy = offset_y + j * block_size + block_size / 2;
+        for x in cx - dot / 2..cx - dot / 2 + dot {
+            for y in cy - dot / 2..cy - dot / 2 + dot {
+                framebuffer.set_pixel(x as u32, y as u32);
+            }
+        }
+    }
+
+    // Cono de visión: los bordes y el contorno donde chocan los rayos
+    let origin = to_minimap(minimap.eye);
+    framebuffer.set_current_color(Rgba::new(255, 240, 150, 255));
+    if let (Some(&first), Some(&last)) = (minimap.cone.first(), minimap.cone.last()) {
+        line(framebuffer, origin, to_minimap(first));
+        line(framebuffer, origin, to_minimap(last));
+    }
+    for pair in minimap.cone.windows(2) {
+        line(framebuffer, to_minimap(pair[0]), to_minimap(pair[1]));
+    }
+
+    // Jugador centrado en su posición y, en una carrera, el otro jugador
+    let half = (block_size / 8) as f32;
+    let rival = minimap.rival.map(|pos| (to_minimap(pos), Rgba::LIME));
+    for (center, color) in rival.into_iter().chain([(origin, Rgba::YELLOW)]) {
+        framebuffer.set_current_color(color);
+        for x in (center.x - half) as u32..(center.x + half) as u32 {
+            for y in (center.y - half) as u32..(center.y + half) as u32 {
+                framebuffer.set_pixel(x, y);
+            }
+        }
+    }
+}
@@ -0,0 +1,180 @@
+// replay.rs
+
+use std::fs;
+
+use crate::player::{InputFrame, TICKS_PER_SECOND};
+
+/// Primera línea de un archivo de repetición.
+const HEADER: &str = "repeticion 3";
+
+/// Versión de la simulación: hay que subirla cuando cambian los generadores, el reparto de
+/// copas y rivales, el movimiento o el generador de números al azar, porque con la misma
+/// semilla y la misma entrada la partida ya no sale igual y las repeticiones viejas no sirven.
+pub const SIMULATION_VERSION: u32 = 1;
+
+/// Huella FNV-1a de 64 bits de `text`; a diferencia del `Hasher` de la biblioteca estándar
+/// no cambia entre versiones de Rust, así que sirve para un archivo guardado.
+pub fn fingerprint(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Entrada de cada paso de una partida, con el nivel y la semilla que la hacen repetible:
+/// con la misma semilla el laberinto, las copas y los rivales salen iguales, así que volver
+/// a aplicar los pasos reproduce la partida exacta.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    pub level: u8,
+    /// Huella del nivel con que se jugó (ver `Session::level_fingerprint`): si el archivo
+    /// del nivel cambia, la repetición ya no corresponde.
+    pub fingerprint: u64,
+    pub seed: u64,
+    /// Una entrada por paso de la simulación; el índice es el momento en que se aplicó.
+    pub frames: Vec<InputFrame>,
+}
+
+impl Replay {
+    pub fn new(level: u8, fingerprint: u64, seed: u64) -> Self {
+        Self {
+            level,
+            fingerprint,
+            seed,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Archivo de la mejor partida del nivel, junto al de los puntajes. La huella va en el
+    /// nombre para que cada versión del nivel tenga el suyo.
+    pub fn best_path(level: u8, fingerprint: u64) -> String {
+        format!("repeticion_{}_{:016x}.txt", level, fingerprint)
+    }
+
+    /// Mejor partida guardada del nivel; `None` si no hay, el archivo no se entiende o se
+    /// grabó en otro nivel.
+    pub fn load_best(level: u8, fingerprint: u64) -> Option<Self> {
+        let text = fs::read_to_string(Self::best_path(level, fingerprint)).ok()?;
+        Self::parse(&text)
+            .filter(|replay| replay.level == level && replay.fingerprint == fingerprint)
+    }
+
+    /// Guarda la partida como la mejor del nivel; un error de escritura sólo se informa
+    /// por consola.
+    pub fn save_best(&self) {
+        let path = Self::best_path(self.level, self.fingerprint);
+        if let Err(err) = fs::write(&path, self.to_text()) {
+            eprintln!("No se pudo guardar la repetición en {}: {}", path, err);
+        }
+    }
+
+    /// Cabecera `clave: valor` y luego una línea `paso botones giro` por cada paso en que la
+    /// entrada cambia; los pasos sin línea repiten las teclas del anterior sin girar.
+    fn to_text(&self) -> String {
+        let mut text = format!(
+            "{}\nsimulacion: {}\nnivel: {}\nhuella: {:016x}\nsemilla: {}\npasos_por_segundo: {}\npasos: {}\n",
+            HEADER,
+            SIMULATION_VERSION,
+            self.level,
+            self.fingerprint,
+            self.seed,
+            TICKS_PER_SECOND,
+            self.frames.len()
+        );
+        let mut previous = InputFrame::default();
+        for (tick, frame) in self.frames.iter().enumerate() {
+            if *frame != previous.held() {
+                // `{}` escribe el `f32` más corto que se vuelve a leer igual
+                text.push_str(&format!("{} {} {}\n", tick, frame.buttons(), frame.look));
+            }
+            previous = *frame;
+        }
+        text
+    }
+
+    /// Lee un archivo escrito por `to_text`; `None` si está incompleto o se grabó con otra
+    /// versión o paso de simulación.
+    fn parse(text: &str) -> Option<Self> {
+        let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+        if lines.next()? != HEADER {
+            return None;
+        }
+        let mut header = |key: &str| {
+            let (k, value) = lines.next()?.split_once(':')?;
+            (k.trim() == key).then(|| value.trim().to_string())
+        };
+        if header("simulacion")?.parse::<u32>().ok()? != SIMULATION_VERSION {
+            return None;
+        }
+        let level = header("nivel")?.parse().ok()?;
+        let fingerprint = u64::from_str_radix(&header("huella")?, 16).ok()?;
+        let seed = header("semilla")?.parse().ok()?;
+        if header("pasos_por_segundo")?.parse::<u32>().ok()? != TICKS_PER_SECOND {
+            return None;
+        }
+        let count: usize = header("pasos")?.parse().ok()?;
+
+        let mut frames = vec![InputFrame::default(); count];
+        let mut changes = Vec::new();
+        for line in lines {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let [tick, buttons, look] = parts[..] else {
+                return None;
+            };
+            let tick: usize = tick.parse().ok()?;
+            let frame = InputFrame::from_buttons(buttons.parse().ok()?, look.parse().ok()?);
+            if tick >= count || !frame.look.is_finite() {
+                return None;
+            }
+            changes.push((tick, frame));
+        }
+
+        // Entre cambios se mantienen las teclas del último, sin toques ni giro
+        let mut changes = changes.into_iter().peekable();
+        let mut current = InputFrame::default();
+        for (tick, slot) in frames.iter_mut().enumerate() {
+            match changes.next_if(|&(at, _)| at == tick) {
+                Some((_, frame)) => current = frame,
+                None => current = current.held(),
+            }
+            *slot = current;
+        }
+        Some(Self {
+            level,
+            fingerprint,
+            seed,
+            frames,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Replay {
+        let mut replay = Replay::new(2, 0xabcd, 1234);
+        replay.frames = vec![
+            InputFrame::from_buttons(1, 0.0),
+            InputFrame::from_buttons(1, 0.25),
+            InputFrame::from_buttons(0, 0.0),
+        ];
+        replay
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let replay = sample();
+        let read = Replay::parse(&replay.to_text()).expect("se lee lo que se escribe");
+        assert_eq!((read.level, read.fingerprint, read.seed), (2, 0xabcd, 1234));
+        assert_eq!(read.frames, replay.frames);
+    }
+
+    #[test]
+    fn rejects_another_simulation_version() {
+        let text = sample().to_text().replace(
+            &format!("simulacion: {}", SIMULATION_VERSION),
+            &format!("simulacion: {}", SIMULATION_VERSION + 1),
+        );
+        assert!(Replay::parse(&text).is_none());
+    }
+}
@@ -0,0 +1,121 @@
+// scores.rs
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs;
+
+/// Archivo local donde se guardan los mejores puntajes.
+pub const SCORES_FILE: &str = "puntajes.txt";
+/// Cantidad de marcas que se conservan por nivel.
+const MAX_ENTRIES: usize = 5;
+
+/// Resultado de una partida terminada.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RunStats {
+    pub time: f32,
+    pub items: u32,
+    pub total_items: u32,
+    pub score: u32,
+}
+
+/// Tiempo de referencia por nivel: terminar antes suma bonificación.
+fn par_time(level: u8) -> f32 {
+    match level {
+        2 => 150.0,
+        3 => 300.0,
+        _ => 60.0,
+    }
+}
+
+/// Puntaje: 500 por copa, más 10 por cada segundo por debajo del tiempo de referencia.
+pub fn compute_score(level: u8, time: f32, items: u32) -> u32 {
+    let bonus = ((par_time(level) - time).max(0.0) * 10.0) as u32;
+    items * 500 + bonus
+}
+
+/// Orden de la tabla: primero el mayor puntaje y, a igual puntaje, el menor tiempo.
+fn rank(a: &RunStats, b: &RunStats) -> Ordering {
+    b.score.cmp(&a.score).then(a.time.total_cmp(&b.time))
+}
+
+/// Mejores puntajes por nivel de dificultad (1, 2 o 3).
+pub struct HighScores {
+    path: String,
+    levels: HashMap<u8, Vec<RunStats>>,
+}
+
+impl HighScores {
+    /// Lee el archivo si existe; las líneas mal formadas se ignoran.
+    pub fn load(path: &str) -> Self {
+        let mut levels: HashMap<u8, Vec<RunStats>> = HashMap::new();
+        if let Ok(text) = fs::read_to_string(path) {
+            for line in text.lines() {
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                if let [level, time, items, total, score] = parts[..]
+                    && let (Ok(level), Ok(time), Ok(items), Ok(total_items), Ok(score)) = (
+                        level.parse::<u8>(),
+                        time.parse::<f32>(),
+                        items.parse::<u32>(),
+                        total.parse::<u32>(),
+                        score.parse::<u32>(),
+                    )
+                {
+                    levels.entry(level).or_default().push(RunStats {
+                        time,
+                        items,
+                        total_items,
+                        score,
+                    });
+                }
+            }
+        }
+        for entries in levels.values_mut() {
+            entries.sort_by(rank);
+            entries.truncate(MAX_ENTRIES);
+        }
+        Self {
+            path: path.to_string(),
+            levels,
+        }
+    }
+
+    pub fn best(&self, level: u8) -> &[RunStats] {
+        self.levels.get(&level).map_or(&[], |v| v.as_slice())
+    }
+
+    /// Registra una partida y devuelve su posición en la tabla (0 = mejor puntaje), si entró.
+    pub fn record(&mut self, level: u8, stats: RunStats) -> Option<usize> {
+        let entries = self.levels.entry(level).or_default();
+        let pos = entries
+            .iter()
+            .position(|e| rank(&stats, e) == Ordering::Less)
+            .unwrap_or(entries.len());
+        if pos >= MAX_ENTRIES {
+            return None;
+        }
+        entries.insert(pos, stats);
+        entries.truncate(MAX_ENTRIES);
+        Some(pos)
+    }
+
+    /// Guarda todas las tablas; un error de escritura sólo se informa por consola.
+    pub fn save(&self) {
+        let mut levels: Vec<_> = self.levels.iter().collect();
+        levels.sort_by_key(|(level, _)| **level);
+        let mut text = String::new();
+        for (level, entries) in levels {
+            for e in entries {
+                text.push_str(&format!(
+                    "{} {:.2} {} {} {}\n",
+                    level, e.time, e.items, e.total_items, e.score
+                ));
+            }
+        }
+        if let Err(err) = fs::write(&self.path, text) {
+            eprintln!(
+                "No se pudieron guardar los puntajes en {}: {}",
+                self.path, err
+            );
+        }
+    }
+}
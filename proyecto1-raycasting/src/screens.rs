@@ -0,0 +1,341 @@
+// screens.rs
+
+use raycaster::level_file::LevelFile;
+use raycaster::scores::RunStats;
+use raycaster::timing::{FrameTimer, HISTORY_LEN};
+use raylib::prelude::*;
+
+use crate::input::menu_step;
+
+const GOLD: Color = Color::new(255, 215, 0, 255);
+const HINT: Color = Color::new(200, 30, 30, 255);
+
+/// Datos de la partida que se muestran en la pantalla de victoria.
+#[derive(Clone)]
+pub struct WinSummary {
+    pub stats: RunStats,
+    pub best: Vec<RunStats>,
+    /// Posición de esta partida en la tabla de mejores tiempos, si entró.
+    pub record: Option<usize>,
+}
+
+/// Sube o baja la opción elegida con las flechas o la cruceta, dando la vuelta en los
+/// extremos.
+pub fn move_selection(rl: &RaylibHandle, selected: &mut usize, len: usize) {
+    match menu_step(rl) {
+        -1 => *selected = (*selected + len - 1) % len,
+        1 => *selected = (*selected + 1) % len,
+        _ => {}
+    }
+}
+
+/// Imagen de fondo escalada para cubrir toda la ventana.
+pub fn draw_background(d: &mut RaylibDrawHandle, fondo: &Texture2D, width: i32, height: i32) {
+    d.clear_background(Color::BLACK);
+    let scale_x = width as f32 / fondo.width() as f32;
+    let scale_y = height as f32 / fondo.height() as f32;
+    let scale = scale_x.max(scale_y);
+    d.draw_texture_ex(fondo, Vector2::new(0.0, 0.0), 0.0, scale, Color::WHITE);
+}
+
+/// Pantalla inicial con instrucciones y datos.
+pub fn draw_title(d: &mut RaylibDrawHandle, fondo: &Texture2D, width: i32, height: i32) {
+    draw_background(d, fondo, width, height);
+
+    // Título y datos
+    d.draw_text(
+        "Proyecto Raycaster - Rayo McQueen",
+        width / 2 - 450,
+        height / 2 - 180,
+        48,
+        GOLD,
+    );
+    d.draw_text(
+        "Autor: Esteban Cárcamo",
+        width / 2 - 180,
+        height / 2 - 120,
+        32,
+        Color::RAYWHITE,
+    );
+    d.draw_text(
+        "Instrucciones:",
+        width / 2 - 180,
+        height / 2 - 60,
+        32,
+        Color::RAYWHITE,
+    );
+    let instrucciones = [
+        "- W/S para avanzar, A/D de lado, flechas para girar",
+        "- Mouse para mirar horizontalmente",
+        "- M para cambiar entre 2D/3D",
+        "- E para abrir puertas y usar interruptores",
+        "- ESC para pausar",
+    ];
+    for (i, texto) in instrucciones.iter().enumerate() {
+        d.draw_text(
+            texto,
+            width / 2 - 180,
+            height / 2 - 20 + i as i32 * 30,
+            28,
+            Color::RAYWHITE,
+        );
+    }
+    d.draw_text(
+        "ENTER: seleccionar nivel   O: opciones   ESC: salir",
+        width / 2 - 330,
+        height / 2 + 160,
+        28,
+        HINT,
+    );
+}
+
+/// Menú de niveles: los tres generados y los de la campaña (teclas 4 a 9).
+pub fn draw_level_select(
+    d: &mut RaylibDrawHandle,
+    fondo: &Texture2D,
+    width: i32,
+    height: i32,
+    campaign: &[LevelFile],
+    selected: u8,
+) {
+    draw_background(d, fondo, width, height);
+
+    // Título y texto
+    d.draw_text("¡Bienvenido!", width / 2 - 150, height / 2 - 120, 50, GOLD);
+    d.draw_text(
+        "Selecciona nivel:",
+        width / 2 - 180,
+        height / 2 - 40,
+        40,
+        Color::RAYWHITE,
+    );
+
+    // Opciones de nivel
+    let generated = ["1 - Fácil", "2 - Medio", "3 - Difícil"].map(String::from);
+    let campaign_names = campaign.iter().enumerate().map(|(k, level)| {
+        let number = k + 4;
+        match &level.author {
+            Some(author) => format!("{} - {} ({})", number, level.name, author),
+            None => format!("{} - {}", number, level.name),
+        }
+    });
+    let options: Vec<String> = generated.into_iter().chain(campaign_names).collect();
+    for (k, texto) in options.iter().enumerate() {
+        let color = if selected as usize == k + 1 {
+            Color::YELLOW
+        } else {
+            Color::RAYWHITE
+        };
+        d.draw_text(
+            texto,
+            width / 2 - 100,
+            height / 2 + 10 + k as i32 * 40,
+            32,
+            color,
+        );
+    }
+
+    d.draw_text(
+        "ENTER: comenzar   N: laberinto nuevo   V: carrera a dos   ESC: volver",
+        width / 2 - 470,
+        height / 2 + 30 + options.len() as i32 * 40,
+        28,
+        HINT,
+    );
+}
+
+/// Pantalla de victoria con las estadísticas y la tabla de mejores tiempos del nivel.
+pub fn draw_win(
+    d: &mut RaylibDrawHandle,
+    fondo: &Texture2D,
+    width: i32,
+    height: i32,
+    summary: &WinSummary,
+) {
+    draw_background(d, fondo, width, height);
+    let stats = summary.stats;
+
+    d.draw_text(
+        "¡Felicidades, has ganado!",
+        width / 2 - 250,
+        height / 2 - 260,
+        40,
+        GOLD,
+    );
+
+    // Estadísticas de la partida
+    d.draw_text(
+        &format!(
+            "Tiempo: {:.1} s   Copas: {}/{}   Puntaje: {}",
+            stats.time, stats.items, stats.total_items, stats.score
+        ),
+        width / 2 - 300,
+        height / 2 - 200,
+        30,
+        Color::RAYWHITE,
+    );
+    if summary.record == Some(0) {
+        d.draw_text(
+            "¡Nuevo récord!",
+            width / 2 - 110,
+            height / 2 - 160,
+            30,
+            GOLD,
+        );
+    }
+
+    // Tabla de mejores puntajes del nivel, con una columna por dato
+    d.draw_text(
+        "Mejores puntajes:",
+        width / 2 - 300,
+        height / 2 - 110,
+        30,
+        Color::RAYWHITE,
+    );
+    let columns = [
+        width / 2 - 280,
+        width / 2 - 230,
+        width / 2 - 100,
+        width / 2 + 40,
+    ];
+    for (x, title) in columns.iter().zip(["#", "Puntaje", "Tiempo", "Copas"]) {
+        d.draw_text(title, *x, height / 2 - 72, 22, Color::LIGHTGRAY);
+    }
+    for (i, e) in summary.best.iter().enumerate() {
+        let color = if summary.record == Some(i) {
+            Color::YELLOW
+        } else {
+            Color::RAYWHITE
+        };
+        let cells = [
+            format!("{}.", i + 1),
+            format!("{} pts", e.score),
+            format!("{:.1} s", e.time),
+            format!("{}/{}", e.items, e.total_items),
+        ];
+        let y = height / 2 - 40 + i as i32 * 32;
+        for (x, text) in columns.iter().zip(&cells) {
+            d.draw_text(text, *x, y, 26, color);
+        }
+    }
+
+    d.draw_text(
+        "PRESIONA ENTER para jugar otro laberinto",
+        width / 2 - 260,
+        height / 2 + 130,
+        30,
+        Color::RAYWHITE,
+    );
+    d.draw_text(
+        "PRESIONA ESC para volver al menú",
+        width / 2 - 230,
+        height / 2 + 170,
+        30,
+        Color::RAYWHITE,
+    );
+    d.draw_text(
+        "PRESIONA R para ver la repetición",
+        width / 2 - 235,
+        height / 2 + 210,
+        30,
+        Color::RAYWHITE,
+    );
+}
+
+/// Menú sobre lo que ya está dibujado (el último cuadro del juego o un fondo): oscurece la
+/// pantalla y lista las opciones con la elegida en amarillo.
+pub fn draw_menu(
+    d: &mut RaylibDrawHandle,
+    width: i32,
+    height: i32,
+    title: &str,
+    options: &[String],
+    selected: usize,
+) {
+    d.draw_rectangle(0, 0, width, height, Color::new(0, 0, 0, 170));
+    let top = height / 2 - 40 - options.len() as i32 * 22;
+    d.draw_text(title, width / 2 - 200, top - 80, 50, GOLD);
+    for (i, option) in options.iter().enumerate() {
+        let color = if i == selected {
+            Color::YELLOW
+        } else {
+            Color::RAYWHITE
+        };
+        d.draw_text(option, width / 2 - 200, top + i as i32 * 45, 32, color);
+    }
+    d.draw_text(
+        "Flechas: elegir   ENTER: aceptar",
+        width / 2 - 200,
+        top + options.len() as i32 * 45 + 30,
+        24,
+        Color::RAYWHITE,
+    );
+}
+
+/// HUD de una carrera: el estado de cada jugador arriba de su mitad y el nivel abajo.
+pub fn draw_race_hud(
+    d: &mut RaylibDrawHandle,
+    fps: u32,
+    nivel_texto: &str,
+    estados: &[String; 2],
+    width: i32,
+    height: i32,
+) {
+    d.draw_text(&format!("FPS: {}", fps), 10, 10, 20, Color::RAYWHITE);
+    for (i, estado) in estados.iter().enumerate() {
+        let x = i as i32 * width / 2 + 10;
+        let jugador = format!("Jugador {}", i + 1);
+        d.draw_text(&jugador, x, 40, 28, GOLD);
+        d.draw_text(estado, x, 75, 18, Color::new(255, 215, 0, 255));
+    }
+    d.draw_text(nivel_texto, 20, height - 40, 32, Color::RAYWHITE);
+}
+
+/// Panel de F3 en la esquina superior derecha: tiempo de cada parte del cuadro, resolución
+/// de la vista 3D y un gráfico de los últimos cuadros con la marca de 60 FPS.
+pub fn draw_timings(
+    d: &mut RaylibDrawHandle,
+    timer: &FrameTimer,
+    window_width: i32,
+    (width, height): (u32, u32),
+    scale: f32,
+) {
+    let (panel_w, panel_h) = (300, 230);
+    let (x, y) = (window_width - panel_w - 10, 10);
+    d.draw_rectangle(x, y, panel_w, panel_h, Color::new(0, 0, 0, 170));
+
+    let average = timer.average;
+    let frame = timer.history.back().copied().unwrap_or(0.0);
+    let lines = [
+        format!("Cuadro: {:.1} ms", frame),
+        format!("Simulación: {:.2} ms", average.simulation),
+        format!("Vista 3D: {:.2} ms", average.render),
+        format!("Presentar: {:.2} ms", average.present),
+        format!("Resolución: {}x{} ({:.0} %)", width, height, scale * 100.0),
+    ];
+    for (i, line) in lines.iter().enumerate() {
+        d.draw_text(line, x + 10, y + 8 + i as i32 * 22, 20, Color::RAYWHITE);
+    }
+
+    // Una barra por cuadro; 33 ms llenan el alto del gráfico
+    let (graph_y, graph_h) = (y + panel_h - 10, 90.0);
+    let ms_to_px = graph_h / 33.3;
+    let bar_w = (panel_w - 20) as f32 / HISTORY_LEN as f32;
+    for (i, &ms) in timer.history.iter().enumerate() {
+        let bar_h = (ms * ms_to_px).min(graph_h);
+        let color = if ms > 1000.0 / 60.0 + 1.0 {
+            Color::ORANGE
+        } else {
+            Color::LIME
+        };
+        d.draw_rectangle(
+            x + 10 + (i as f32 * bar_w) as i32,
+            graph_y - bar_h as i32,
+            bar_w.ceil() as i32,
+            bar_h as i32,
+            color,
+        );
+    }
+    let target_y = graph_y - (1000.0 / 60.0 * ms_to_px) as i32;
+    d.draw_line(x + 10, target_y, x + panel_w - 10, target_y, GOLD);
+}
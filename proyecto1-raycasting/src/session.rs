@@ -0,0 +1,727 @@
+// session.rs
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::f32::consts::PI;
+
+use crate::editor::{Editor, EditorInput, EditorView};
+use crate::enemy::{Cell, Enemy, spawn_enemies};
+use crate::framebuffer::Framebuffer;
+use crate::generator::{MazeConfig, generate};
+use crate::level::Level;
+use crate::level_file::{EntityKind, LevelFile};
+use crate::lighting::{DARK_AMBIENT, Lighting, scatter_torches};
+use crate::math::Vec2;
+use crate::maze::{Legend, place_collectibles};
+use crate::minimap::{Minimap, MinimapMode};
+use crate::player::{InputFrame, Movement, Player, TICKS_PER_SECOND, process_events};
+use crate::render::{render_editor, render_minimap, render_world};
+use crate::replay::{Replay, fingerprint};
+use crate::scores::{RunStats, compute_score};
+use crate::sprite::{Sprite, SpriteKind, collect_near, place_sprites};
+use crate::textures::{LoadImage, WorldTextures};
+
+/// Duración de un paso de la simulación, en segundos.
+const TICK: f32 = 1.0 / TICKS_PER_SECOND as f32;
+/// Pasos que se pueden recuperar en un cuadro lento; más allá el juego se ralentiza.
+const MAX_TICKS_PER_FRAME: u32 = 5;
+
+/// Radio del jugador como fracción de una celda; deja medio bloque libre en los pasillos.
+const PLAYER_RADIUS: f32 = 0.25;
+
+/// Vidas con las que empieza cada nivel.
+const LIVES: u32 = 3;
+
+/// Antorchas que se reparten en el nivel difícil, que se juega en penumbra.
+const HARD_TORCHES: usize = 8;
+
+/// Archivo de la campaña para los niveles 4 en adelante.
+fn campaign_level(level: u8, campaign: &[LevelFile]) -> Option<&LevelFile> {
+    level
+        .checked_sub(4)
+        .and_then(|index| campaign.get(index as usize))
+}
+
+/// Nivel de la campaña o, para los niveles 1 a 3, el laberinto que genera `config`.
+fn get_maze_for_level(level: u8, campaign: &[LevelFile], config: &MazeConfig) -> Level {
+    if let Some(file) = campaign_level(level, campaign) {
+        let loaded =
+            Level::new(file.maze.clone(), file.legend.clone()).with_heights(file.heights.clone());
+        return match file.start {
+            Some(start) => loaded.with_start(start),
+            None => loaded,
+        };
+    }
+    let generated = generate(config).expect("no se pudo generar un laberinto con salida");
+    Level::new(generated.maze, Legend::default()).with_start(generated.start)
+}
+
+/// Copas del nivel: las que fija el archivo o, si no trae ninguna, repartidas al azar.
+fn level_collectibles(
+    level: &Level,
+    file: Option<&LevelFile>,
+    count: usize,
+    rng: &mut ChaCha8Rng,
+) -> Vec<(usize, usize)> {
+    match file.map(|f| f.cells(EntityKind::Cup)) {
+        Some(cells) if !cells.is_empty() => cells,
+        _ => place_collectibles(&level.maze, count, level.start, rng),
+    }
+}
+
+/// Rivales del nivel: en los puntos que fija el archivo o, si no trae, al azar.
+fn level_enemies(
+    level: &Level,
+    file: Option<&LevelFile>,
+    block_size: usize,
+    (count, speed): (usize, f32),
+    rng: &mut ChaCha8Rng,
+) -> Vec<Enemy> {
+    match file.map(|f| f.cells(EntityKind::Enemy)) {
+        Some(cells) if !cells.is_empty() => cells
+            .into_iter()
+            .map(|cell| Enemy::new(cell, block_size, speed))
+            .collect(),
+        _ => spawn_enemies(level, block_size, level.start, count, speed, rng),
+    }
+}
+
+/// Cantidad y velocidad (píxeles por segundo) de los rivales en cada nivel.
+fn enemies_for_level(level: u8) -> (usize, f32) {
+    match level {
+        2 => (3, 120.0),
+        3 => (5, 150.0),
+        _ => (1, 90.0),
+    }
+}
+
+/// Niebla y luces del nivel: las del archivo o, en el nivel difícil, antorchas al azar.
+fn level_lighting(
+    level: &Level,
+    file: Option<&LevelFile>,
+    difficulty: u8,
+    block_size: usize,
+    seed: u64,
+) -> Lighting {
+    let lights = match file {
+        Some(file) => file.lights.clone(),
+        None if difficulty == 3 => {
+            scatter_torches(level, HARD_TORCHES, &mut ChaCha8Rng::seed_from_u64(seed))
+        }
+        None => Vec::new(),
+    };
+    let default_ambient = if lights.is_empty() { 1.0 } else { DARK_AMBIENT };
+    let ambient = file.and_then(|f| f.ambient).unwrap_or(default_ambient);
+    let fog = file.and_then(|f| f.fog).unwrap_or_default();
+    Lighting::new(level, block_size, lights, ambient, fog)
+}
+
+/// Cuándo se ve el minimapa en cada dificultad; un archivo de nivel puede elegirlo con
+/// `minimapa:`.
+fn minimap_for_level(level: u8) -> MinimapMode {
+    match level {
+        2 => MinimapMode::Limited(3),
+        3 => MinimapMode::Hidden,
+        _ => MinimapMode::Always,
+    }
+}
+
+/// Copas coleccionables que reparte el generador en cada nivel.
+fn collectibles_for_level(level: u8) -> usize {
+    match level {
+        2 => 6,
+        3 => 10,
+        _ => 3,
+    }
+}
+
+/// Sonidos que pide la partida mientras avanza; los reproduce el front end
+/// (`audio::GameAudio`), así que la simulación no depende de raylib.
+pub trait Sounds {
+    /// El jugador chocó con una pared.
+    fn wall_hit(&self);
+    /// El jugador recorrió `moved` píxeles del mundo en un paso.
+    fn footsteps(&mut self, moved: f32, block_size: usize);
+    /// Posición de las fuentes que suenan desde el mundo (la meta y los rivales) respecto
+    /// del jugador, después de `delta_time` segundos simulados.
+    fn update_sources(
+        &mut self,
+        player: &Player,
+        goal: Option<Vec2>,
+        rivals: &[Vec2],
+        block_size: usize,
+        delta_time: f32,
+    );
+}
+
+/// Cómo terminó un nivel.
+pub enum Outcome {
+    Won(RunStats),
+    /// Sin vidas o sin tiempo; el texto dice cuál.
+    Lost(String),
+}
+
+/// Partida en curso de un nivel: el laberinto, el jugador, las copas, los rivales y el
+/// editor si está abierto.
+pub struct Session {
+    /// Nivel elegido en el menú: 1 a 3 generados, 4 en adelante de la campaña.
+    pub number: u8,
+    /// Semilla del laberinto generado y de todo lo que se reparte al azar en el nivel.
+    pub seed: u64,
+    /// Copia del archivo del nivel; el editor la reemplaza al probar un nivel editado.
+    pub file: Option<LevelFile>,
+    pub maze_config: MazeConfig,
+    pub level: Level,
+    pub player: Player,
+    start_angle: f32,
+    /// Azar de las copas y las patrullas; vuelve a la semilla en cada reinicio.
+    rng: ChaCha8Rng,
+    collectibles: Vec<Cell>,
+    sprites: Vec<Sprite>,
+    enemies: Vec<Enemy>,
+    minimap: Minimap,
+    elapsed: f32,
+    /// Pasos de simulación desde el inicio del nivel.
+    ticks: usize,
+    /// Tiempo que todavía no alcanza para un paso completo.
+    accumulator: f32,
+    /// Entrada juntada desde el último paso.
+    pending: InputFrame,
+    items_collected: u32,
+    lives: u32,
+    /// Aviso del HUD (captura, puertas, llaves) y los segundos que le quedan en pantalla.
+    notice: String,
+    notice_time: f32,
+    textures: WorldTextures,
+    /// Entrada de la partida; deja de grabarse si el nivel se edita.
+    recording: Option<Replay>,
+    /// Repetición que se está mirando en lugar de jugar.
+    playback: Option<Replay>,
+    /// Posición en cada paso de la mejor partida con esta semilla, si la hay.
+    ghost: Vec<Vec2>,
+    /// Posición del otro jugador en una carrera.
+    rival: Option<Vec2>,
+    /// Si los sonidos de la meta y los rivales se oyen desde este jugador; en una carrera,
+    /// sólo desde el primero.
+    listener: bool,
+    /// Editor del modo 2D; mientras está abierto el juego queda en pausa.
+    pub editor: Option<Editor>,
+}
+
+impl Session {
+    /// Huella que identifica el nivel `number` en sus repeticiones: el contenido de su
+    /// archivo si es de la campaña o, si es generado, su nombre, porque el laberinto sólo
+    /// depende de la semilla.
+    pub fn level_fingerprint(number: u8, campaign: &[LevelFile]) -> u64 {
+        match campaign_level(number, campaign) {
+            Some(file) => fingerprint(&file.to_text()),
+            None => fingerprint(&format!("generado {}", number)),
+        }
+    }
+
+    /// Mejor partida guardada del nivel `number`, si es de la versión actual del nivel.
+    pub fn best_replay(number: u8, campaign: &[LevelFile]) -> Option<Replay> {
+        Replay::load_best(number, Self::level_fingerprint(number, campaign))
+    }
+
+    /// Arma el nivel `number`; `seed` elige el laberinto si es generado y lo que se reparte
+    /// al azar, y `load` decodifica las texturas. Si la mejor partida guardada del nivel usó
+    /// la misma semilla, corre como fantasma.
+    pub fn new(
+        number: u8,
+        campaign: &[LevelFile],
+        seed: u64,
+        block_size: usize,
+        fov: f32,
+        load: LoadImage,
+    ) -> Self {
+        let mut session = Self::build(number, campaign, seed, block_size, fov, load);
+        if let Some(best) = Self::best_replay(number, campaign).filter(|best| best.seed == seed) {
+            session.ghost = Self::ghost_path(&best, campaign, block_size, load);
+        }
+        session
+    }
+
+    /// Reproduce `replay` tal como se jugó; la entrada del jugador se ignora.
+    pub fn watch(
+        replay: Replay,
+        campaign: &[LevelFile],
+        block_size: usize,
+        fov: f32,
+        load: LoadImage,
+    ) -> Self {
+        let mut session = Self::build(replay.level, campaign, replay.seed, block_size, fov, load);
+        session.recording = None;
+        session.playback = Some(replay);
+        session
+    }
+
+    /// Uno de los jugadores de una carrera: no se graba ni corre contra el fantasma, y si
+    /// no es `listener` no mueve los sonidos de la meta ni de los rivales.
+    pub fn for_race(
+        number: u8,
+        campaign: &[LevelFile],
+        seed: u64,
+        block_size: usize,
+        fov: f32,
+        listener: bool,
+        load: LoadImage,
+    ) -> Self {
+        let mut session = Self::build(number, campaign, seed, block_size, fov, load);
+        session.recording = None;
+        session.listener = listener;
+        session
+    }
+
+    /// Muestra al otro jugador de una carrera en `pos`, en la vista y en el minimapa.
+    pub fn set_rival(&mut self, pos: Vec2, block_size: usize) {
+        self.rival = Some(pos);
+        self.minimap.rival = Some(pos * (1.0 / block_size as f32));
+    }
+
+    fn build(
+        number: u8,
+        campaign: &[LevelFile],
+        seed: u64,
+        block_size: usize,
+        fov: f32,
+        load: LoadImage,
+    ) -> Self {
+        let file = campaign_level(number, campaign).cloned();
+        let maze_config = MazeConfig::for_difficulty(number, seed);
+        let mut level = get_maze_for_level(number, campaign, &maze_config);
+        level.lighting = level_lighting(&level, file.as_ref(), number, block_size, seed);
+        let start_angle = file.as_ref().and_then(|f| f.angle).unwrap_or(PI / 3.0);
+        let minimap_mode = file
+            .as_ref()
+            .and_then(|f| f.minimap)
+            .unwrap_or_else(|| minimap_for_level(number));
+        let mut session = Self {
+            number,
+            seed,
+            minimap: Minimap::new(minimap_mode, &level.maze),
+            textures: WorldTextures::for_level(&level, file.as_ref(), load),
+            file,
+            maze_config,
+            player: Player {
+                pos: level.start_position(block_size),
+                a: start_angle,
+                fov,
+                radius: block_size as f32 * PLAYER_RADIUS,
+                against_wall: false,
+                z: 0.0,
+                pitch: 0.0,
+            },
+            level,
+            start_angle,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            collectibles: Vec::new(),
+            sprites: Vec::new(),
+            enemies: Vec::new(),
+            elapsed: 0.0,
+            ticks: 0,
+            accumulator: 0.0,
+            pending: InputFrame::default(),
+            items_collected: 0,
+            lives: LIVES,
+            notice: String::new(),
+            notice_time: 0.0,
+            recording: Some(Replay::new(
+                number,
+                Self::level_fingerprint(number, campaign),
+                seed,
+            )),
+            playback: None,
+            ghost: Vec::new(),
+            rival: None,
+            listener: true,
+            editor: None,
+        };
+        session.restart(block_size);
+        session
+    }
+
+    /// Posición del jugador en cada paso de `replay`, simulando la partida aparte.
+    fn ghost_path(
+        replay: &Replay,
+        campaign: &[LevelFile],
+        block_size: usize,
+        load: LoadImage,
+    ) -> Vec<Vec2> {
+        let mut run = Self::build(
+            replay.level,
+            campaign,
+            replay.seed,
+            block_size,
+            PI / 3.0,
+            load,
+        );
+        let mut path = Vec::with_capacity(replay.frames.len());
+        for frame in &replay.frames {
+            let (_, outcome) = run.step(frame, block_size);
+            path.push(run.player.pos);
+            if outcome.is_some() {
+                break;
+            }
+        }
+        path
+    }
+
+    /// Empieza el nivel de nuevo con el mismo laberinto: reloj, vidas, copas, rivales y
+    /// jugador como al principio.
+    pub fn restart(&mut self, block_size: usize) {
+        self.level.reset();
+        self.minimap.reset(&self.level.maze);
+        let start_pos = self.level.start_position(block_size);
+        self.player.pos = start_pos;
+        self.player.a = self.start_angle;
+        self.player.against_wall = false;
+        self.player.z = self.level.floor_at(start_pos, block_size);
+        self.player.pitch = 0.0;
+        self.rng = ChaCha8Rng::seed_from_u64(self.seed);
+        self.collectibles = level_collectibles(
+            &self.level,
+            self.file.as_ref(),
+            collectibles_for_level(self.number),
+            &mut self.rng,
+        );
+        self.sprites = place_sprites(&self.level.maze, block_size, start_pos, &self.collectibles);
+        self.enemies = level_enemies(
+            &self.level,
+            self.file.as_ref(),
+            block_size,
+            enemies_for_level(self.number),
+            &mut self.rng,
+        );
+        self.elapsed = 0.0;
+        self.ticks = 0;
+        self.accumulator = 0.0;
+        self.pending = InputFrame::default();
+        self.items_collected = 0;
+        self.lives = LIVES;
+        self.notice_time = 0.0;
+        if let Some(recording) = self.recording.as_mut() {
+            recording.frames.clear();
+        }
+    }
+
+    fn time_limit(&self) -> Option<f32> {
+        self.file.as_ref().and_then(|f| f.time_limit)
+    }
+
+    /// La repetición que se está mirando o la partida grabada hasta ahora; `None` si el
+    /// nivel se editó, porque ya no se podría reproducir.
+    pub fn replay(&self) -> Option<&Replay> {
+        self.playback.as_ref().or(self.recording.as_ref())
+    }
+
+    pub fn is_playback(&self) -> bool {
+        self.playback.is_some()
+    }
+
+    /// Avanza la partida `delta_time` segundos en pasos fijos, con la entrada del jugador o,
+    /// al mirar una repetición, con la grabada. Devuelve cómo terminó el nivel, si terminó.
+    pub fn advance(
+        &mut self,
+        delta_time: f32,
+        input: InputFrame,
+        block_size: usize,
+        audio: &mut impl Sounds,
+    ) -> Option<Outcome> {
+        self.pending.merge(input);
+        self.accumulator = (self.accumulator + delta_time).min(TICK * MAX_TICKS_PER_FRAME as f32);
+        let mut simulated = 0.0;
+        while self.accumulator >= TICK {
+            self.accumulator -= TICK;
+            simulated += TICK;
+            let frame = match &self.playback {
+                Some(replay) => match replay.frames.get(self.ticks) {
+                    Some(frame) => *frame,
+                    None => return Some(Outcome::Lost("La repetición terminó".to_string())),
+                },
+                None => self.pending,
+            };
+            self.pending = self.pending.held();
+
+            let previous_pos = self.player.pos;
+            let (bumped, outcome) = self.step(&frame, block_size);
+            if bumped {
+                audio.wall_hit();
+            }
+            let moved =
+                (self.player.pos.x - previous_pos.x).hypot(self.player.pos.y - previous_pos.y);
+            audio.footsteps(moved, block_size);
+            if outcome.is_some() {
+                return outcome;
+            }
+        }
+
+        if !self.listener {
+            return None;
+        }
+        let rivals: Vec<Vec2> = self.enemies.iter().map(|e| e.pos).collect();
+        audio.update_sources(
+            &self.player,
+            self.level.goal_position(block_size),
+            &rivals,
+            block_size,
+            simulated,
+        );
+        None
+    }
+
+    /// Un paso de la simulación con la entrada `input`. Devuelve si el jugador chocó con
+    /// una pared y cómo terminó el nivel, si terminó en este paso.
+    fn step(&mut self, input: &InputFrame, block_size: usize) -> (bool, Option<Outcome>) {
+        self.elapsed += TICK;
+        self.ticks += 1;
+        if let Some(recording) = self.recording.as_mut() {
+            recording.frames.push(*input);
+        }
+
+        // Mueve al jugador
+        let bumped = process_events(
+            &mut self.player,
+            input,
+            TICK,
+            &self.level,
+            block_size,
+            &Movement::default(),
+        );
+
+        // Celdas descubiertas y TAB para mirar el minimapa en el modo limitado
+        self.minimap.update(input.peek_map, TICK);
+        self.minimap.reveal(&self.level, &self.player, block_size);
+
+        // Puertas, interruptores y llaves
+        self.level.update(TICK);
+        self.notice_time = (self.notice_time - TICK).max(0.0);
+        if input.interact
+            && let Some(message) = self
+                .level
+                .interact(self.player.pos, self.player.a, block_size)
+        {
+            self.notice = message;
+            self.notice_time = 2.0;
+        }
+        if let Some(color) = self.level.pick_up_key(self.player.pos, block_size) {
+            self.notice = format!("Tienes la llave {}", color.name());
+            self.notice_time = 2.0;
+        }
+
+        // Recoge las copas cercanas
+        self.items_collected +=
+            collect_near(&mut self.sprites, &self.player, block_size as f32 * 0.4);
+
+        // Mueve a los rivales; si alguno alcanza al jugador pierde una vida y vuelve a la
+        // salida. Sin vidas, o si se acaba el tiempo del nivel, el nivel termina.
+        for enemy in self.enemies.iter_mut() {
+            enemy.update(
+                &self.level,
+                self.player.pos,
+                block_size,
+                TICK,
+                &mut self.rng,
+            );
+        }
+        if self.time_limit().is_some_and(|limit| self.elapsed > limit) {
+            return (
+                bumped,
+                Some(Outcome::Lost("¡Se acabó el tiempo!".to_string())),
+            );
+        }
+        let caught = self
+            .enemies
+            .iter()
+            .any(|e| e.touches(self.player.pos, block_size as f32 * 0.4));
+        if caught {
+            self.lives -= 1;
+            if self.lives == 0 {
+                let reason = "¡Te atraparon los rivales!".to_string();
+                return (bumped, Some(Outcome::Lost(reason)));
+            }
+            self.notice = "¡Te atrapó un rival!".to_string();
+            self.notice_time = 2.0;
+            self.player.pos = self.level.start_position(block_size);
+            self.player.a = self.start_angle;
+            for enemy in self.enemies.iter_mut() {
+                enemy.reset();
+            }
+        }
+
+        if self.level.reached_goal(self.player.pos, block_size) {
+            let stats = RunStats {
+                time: self.elapsed,
+                items: self.items_collected,
+                total_items: self.collectibles.len() as u32,
+                score: compute_score(self.number, self.elapsed, self.items_collected),
+            };
+            return (bumped, Some(Outcome::Won(stats)));
+        }
+        (bumped, None)
+    }
+
+    /// Abre el editor con las copas y rivales de la partida actual.
+    pub fn open_editor(&mut self, block_size: usize) {
+        let rivals: Vec<_> = self
+            .enemies
+            .iter()
+            .map(|e| e.spawn_cell(block_size))
+            .collect();
+        self.editor = Some(Editor::new(
+            &mut self.level,
+            self.file.as_ref(),
+            &self.collectibles,
+            &rivals,
+        ));
+    }
+
+    /// Un cuadro del editor; `input.play` vuelve a 3D y empieza el nivel editado desde el
+    /// inicio. Un nivel editado ya no se graba ni corre contra el fantasma.
+    pub fn update_editor(&mut self, input: &EditorInput, view: EditorView, block_size: usize) {
+        let Some(editor) = self.editor.as_mut() else {
+            return;
+        };
+        editor.process_input(input, &mut self.level, view);
+        if input.play {
+            self.file = Some(editor.level_file(&self.level));
+            self.editor = None;
+            self.recording = None;
+            self.ghost.clear();
+            self.level.reset();
+            self.level.lighting = level_lighting(
+                &self.level,
+                self.file.as_ref(),
+                self.number,
+                block_size,
+                self.seed,
+            );
+            self.restart(block_size);
+        }
+    }
+
+    /// Vista 3D con el minimapa en la esquina superior derecha.
+    pub fn render(
+        &self,
+        framebuffer: &mut Framebuffer,
+        block_size: usize,
+        minimap_cell: usize,
+        time: f32,
+    ) {
+        let mut sprites = self.sprites.clone();
+        sprites.extend(self.level.key_sprites(block_size));
+        sprites.extend(self.enemies.iter().map(Enemy::sprite));
+        sprites.extend(self.level.lighting.torch_sprites());
+        // El fantasma desaparece al terminar su partida
+        if let Some(&pos) = self.ghost.get(self.ticks.saturating_sub(1)) {
+            sprites.push(Sprite {
+                pos,
+                kind: SpriteKind::Ghost,
+            });
+        }
+        if let Some(pos) = self.rival {
+            sprites.push(Sprite {
+                pos,
+                kind: SpriteKind::Racer,
+            });
+        }
+        render_world(
+            framebuffer,
+            &self.level,
+            block_size,
+            &self.player,
+            &self.textures,
+            &sprites,
+            time,
+        );
+
+        if self.minimap.visible() {
+            let margin = minimap_cell * 2;
+            let offset_x = (framebuffer.width as usize)
+                .saturating_sub(self.level.maze[0].len() * minimap_cell)
+                .saturating_sub(margin);
+            render_minimap(
+                framebuffer,
+                &self.level,
+                &self.minimap,
+                minimap_cell,
+                offset_x,
+                margin,
+                Some(&self.textures.goal),
+            );
+        }
+    }
+
+    pub fn render_editor(&self, framebuffer: &mut Framebuffer, view: EditorView) {
+        if let Some(editor) = &self.editor {
+            render_editor(
+                framebuffer,
+                &self.level,
+                editor,
+                view,
+                Some(&self.textures.goal),
+            );
+        }
+    }
+
+    /// Textos del HUD: el nombre del nivel (abajo) y el estado de la partida (arriba).
+    pub fn hud(&self) -> (String, String) {
+        if let Some(editor) = &self.editor {
+            let nivel_texto = if editor.status.is_empty() {
+                "Editor: clic izquierdo pinta, clic derecho borra".to_string()
+            } else {
+                editor.status.clone()
+            };
+            let estado_texto = format!(
+                "Pincel: {}   1-6: cambiar   Ctrl+Z: deshacer   Ctrl+S: guardar   M: probar",
+                editor.brush.name()
+            );
+            return (nivel_texto, estado_texto);
+        }
+
+        let nivel_texto = match (self.number, &self.file) {
+            (_, Some(file)) => format!("Nivel: {}", file.name),
+            (2, _) => "Nivel: Medio".to_string(),
+            (3, _) => "Nivel: Difícil".to_string(),
+            _ => "Nivel: Fácil".to_string(),
+        };
+        let nivel_texto = match &self.file {
+            Some(_) => nivel_texto,
+            None => format!(
+                "{} ({}, semilla {})",
+                nivel_texto,
+                self.maze_config.algorithm.name(),
+                self.maze_config.seed
+            ),
+        };
+        let tiempo_texto = match self.time_limit() {
+            Some(limit) => format!("{:.1} / {:.0} s", self.elapsed, limit),
+            None => format!("{:.1} s", self.elapsed),
+        };
+        let mut estado_texto = format!(
+            "Copas: {}/{}   Tiempo: {}   Vidas: {}",
+            self.items_collected,
+            self.collectibles.len(),
+            tiempo_texto,
+            self.lives
+        );
+        if !self.level.keys.is_empty() {
+            let names: Vec<&str> = self.level.keys.iter().map(|k| k.name()).collect();
+            estado_texto.push_str(&format!("   Llaves: {}", names.join(", ")));
+        }
+        if let Some(status) = self.minimap.status() {
+            estado_texto.push_str(&format!("   {}", status));
+        }
+        if !self.ghost.is_empty() {
+            let best = self.ghost.len() as f32 * TICK;
+            estado_texto.push_str(&format!("   Fantasma: {:.1} s", best));
+        }
+        if self.notice_time > 0.0 {
+            estado_texto.push_str(&format!("   {}", self.notice));
+        }
+        if self.is_playback() {
+            estado_texto.push_str("   Repetición (ESC: volver)");
+        }
+        (nivel_texto, estado_texto)
+    }
+}
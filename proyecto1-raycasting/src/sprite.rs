@@ -0,0 +1,276 @@
+// sprite.rs
+
+use crate::color::Rgba;
+use crate::framebuffer::Framebuffer;
+use crate::level::Level;
+use crate::math::Vec2;
+use crate::maze::{KeyColor, Maze};
+use crate::player::Player;
+use crate::render::Camera;
+use crate::textures::WorldTextures;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpriteKind {
+    /// Copa que se puede recoger.
+    Collectible,
+    /// Trofeo frente a la meta.
+    Trophy,
+    /// Copa plateada de adorno en los callejones sin salida.
+    Decoration,
+    /// Copa rival que patrulla y persigue al jugador.
+    Enemy,
+    /// Llave que abre las puertas de su color.
+    Key(KeyColor),
+    /// Antorcha en una fuente de luz; brilla con luz propia.
+    Torch,
+    /// Copa translúcida que repite la mejor partida del nivel.
+    Ghost,
+    /// El otro jugador en una carrera a pantalla dividida.
+    Racer,
+}
+
+impl SpriteKind {
+    /// Altura del sprite como fracción de la altura de una pared.
+    fn height(self) -> f32 {
+        match self {
+            SpriteKind::Collectible => 0.35,
+            SpriteKind::Trophy => 0.7,
+            SpriteKind::Decoration => 0.45,
+            SpriteKind::Enemy => 0.6,
+            SpriteKind::Key(_) => 0.25,
+            SpriteKind::Torch => 0.5,
+            SpriteKind::Ghost => 0.6,
+            SpriteKind::Racer => 0.6,
+        }
+    }
+
+    fn animated(self) -> bool {
+        !matches!(self, SpriteKind::Decoration | SpriteKind::Key(_))
+    }
+
+    /// Tinte propio del tipo: plateado para las decorativas, rojo para los rivales, celeste
+    /// para el fantasma, verde para el otro jugador y el color de la llave para las llaves.
+    fn tint(self, color: Rgba) -> Rgba {
+        match self {
+            SpriteKind::Decoration => silver(color),
+            SpriteKind::Enemy => crimson(color),
+            SpriteKind::Ghost => spectral(color),
+            SpriteKind::Racer => emerald(color),
+            SpriteKind::Key(key) => {
+                let k = key.color();
+                Rgba::new(
+                    (color.r as u16 * k.r as u16 / 255) as u8,
+                    (color.g as u16 * k.g as u16 / 255) as u8,
+                    (color.b as u16 * k.b as u16 / 255) as u8,
+                    color.a,
+                )
+            }
+            _ => color,
+        }
+    }
+}
+
+/// Recoge las copas a menos de `radius` del jugador y devuelve cuántas tomó.
+pub fn collect_near(sprites: &mut Vec<Sprite>, player: &Player, radius: f32) -> u32 {
+    let before = sprites.len();
+    sprites.retain(|s| {
+        s.kind != SpriteKind::Collectible
+            || (s.pos.x - player.pos.x).hypot(s.pos.y - player.pos.y) >= radius
+    });
+    (before - sprites.len()) as u32
+}
+
+#[derive(Clone, Copy)]
+pub struct Sprite {
+    pub pos: Vec2,
+    pub kind: SpriteKind,
+}
+
+fn cell_center(i: usize, j: usize, block_size: usize) -> Vec2 {
+    Vec2::new(
+        i as f32 * block_size as f32 + block_size as f32 / 2.0,
+        j as f32 * block_size as f32 + block_size as f32 / 2.0,
+    )
+}
+
+fn open_neighbors(maze: &Maze, i: usize, j: usize) -> Vec<(usize, usize)> {
+    let mut out = Vec::new();
+    for (di, dj) in [(1isize, 0isize), (-1, 0), (0, 1), (0, -1)] {
+        let ni = i as isize + di;
+        let nj = j as isize + dj;
+        if ni < 0 || nj < 0 {
+            continue;
+        }
+        let (ni, nj) = (ni as usize, nj as usize);
+        if maze.get(nj).and_then(|row| row.get(ni)) == Some(&' ') {
+            out.push((ni, nj));
+        }
+    }
+    out
+}
+
+/// Coloca el trofeo frente a la meta, las copas coleccionables en `collectibles` y copas
+/// decorativas en los demás callejones sin salida.
+pub fn place_sprites(
+    maze: &Maze,
+    block_size: usize,
+    start: Vec2,
+    collectibles: &[(usize, usize)],
+) -> Vec<Sprite> {
+    let start_cell = (
+        (start.x / block_size as f32) as usize,
+        (start.y / block_size as f32) as usize,
+    );
+    let mut sprites: Vec<Sprite> = collectibles
+        .iter()
+        .map(|&(i, j)| Sprite {
+            pos: cell_center(i, j, block_size),
+            kind: SpriteKind::Collectible,
+        })
+        .collect();
+    for (j, row) in maze.iter().enumerate() {
+        for (i, &cell) in row.iter().enumerate() {
+            if cell == 'g' {
+                if let Some(&(ni, nj)) = open_neighbors(maze, i, j).first() {
+                    sprites.push(Sprite {
+                        pos: cell_center(ni, nj, block_size),
+                        kind: SpriteKind::Trophy,
+                    });
+                }
+            } else if cell == ' ' && (i, j) != start_cell && open_neighbors(maze, i, j).len() == 1 {
+                sprites.push(Sprite {
+                    pos: cell_center(i, j, block_size),
+                    kind: SpriteKind::Decoration,
+                });
+            }
+        }
+    }
+    sprites
+}
+
+/// Tinte plateado para las copas decorativas.
+fn silver(color: Rgba) -> Rgba {
+    let gray = (color.r as u16 + color.g as u16 + color.b as u16) / 3;
+    let gray = (gray + 40).min(255) as u8;
+    Rgba::new(gray, gray, (gray as u16 + 15).min(255) as u8, color.a)
+}
+
+/// Tinte rojo para las copas rivales.
+fn crimson(color: Rgba) -> Rgba {
+    let gray = (color.r as u16 + color.g as u16 + color.b as u16) / 3;
+    Rgba::new(
+        (gray + 90).min(255) as u8,
+        gray as u8 / 3,
+        gray as u8 / 3,
+        color.a,
+    )
+}
+
+/// Tinte celeste y pálido para el fantasma.
+fn spectral(color: Rgba) -> Rgba {
+    let gray = (color.r as u16 + color.g as u16 + color.b as u16) / 3;
+    Rgba::new(
+        gray as u8 / 2,
+        (gray + 60).min(255) as u8,
+        (gray + 120).min(255) as u8,
+        color.a,
+    )
+}
+
+/// Tinte verde para el otro jugador de una carrera.
+fn emerald(color: Rgba) -> Rgba {
+    let gray = (color.r as u16 + color.g as u16 + color.b as u16) / 3;
+    Rgba::new(
+        gray as u8 / 3,
+        (gray + 90).min(255) as u8,
+        gray as u8 / 2,
+        color.a,
+    )
+}
+
+/// Mezcla `color` sobre `under` con opacidad `alpha` (0 a 1).
+fn blend(under: Rgba, color: Rgba, alpha: f32) -> Rgba {
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * alpha) as u8;
+    Rgba::new(
+        mix(under.r, color.r),
+        mix(under.g, color.g),
+        mix(under.b, color.b),
+        255,
+    )
+}
+
+/// Dibuja los sprites como billboards parados sobre el piso de su celda, de lejos a cerca,
+/// recortados contra `depth` (distancia perpendicular de lo ya dibujado en cada píxel).
+pub fn render_sprites(
+    framebuffer: &mut Framebuffer,
+    sprites: &[Sprite],
+    textures: &WorldTextures,
+    level: &Level,
+    camera: &Camera,
+    depth: &[f32],
+    time: f32,
+) {
+    let width = framebuffer.width as f32;
+    let height = framebuffer.height as f32;
+    let lighting = &level.lighting;
+    let (cos, sin) = (camera.angle.cos(), camera.angle.sin());
+
+    // Distancia perpendicular (profundidad) y desplazamiento lateral en el espacio de la cámara.
+    let mut visible: Vec<(&Sprite, f32, f32)> = sprites
+        .iter()
+        .filter_map(|sprite| {
+            let dx = sprite.pos.x - camera.pos.x;
+            let dy = sprite.pos.y - camera.pos.y;
+            let forward = dx * cos + dy * sin;
+            let side = -dx * sin + dy * cos;
+            (forward > 1.0).then_some((sprite, forward, side))
+        })
+        .collect();
+    visible.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    for (sprite, forward, side) in visible {
+        let image = match sprite.kind {
+            SpriteKind::Key(_) => &textures.key,
+            SpriteKind::Torch => textures.torch.frame(time),
+            kind if kind.animated() => textures.cup.frame(time),
+            _ => &textures.cup.frames[0],
+        };
+
+        let screen_x = (side / forward / camera.half_plane + 1.0) / 2.0 * width;
+        let wall_height = camera.scale / forward;
+        let sprite_h = wall_height * sprite.kind.height();
+        let sprite_w = sprite_h * image.w as f32 / image.h as f32;
+        let bottom = camera.row(level.floor_at(sprite.pos, camera.block_size), forward);
+        let top = bottom - sprite_h;
+        let left = screen_x - sprite_w / 2.0;
+        let light = match sprite.kind {
+            SpriteKind::Torch => [1.0; 3],
+            _ => lighting.at(sprite.pos.x, sprite.pos.y),
+        };
+
+        let first_x = left.max(0.0) as u32;
+        let last_x = (left + sprite_w).min(width) as u32;
+        let first_y = top.max(0.0) as u32;
+        let last_y = bottom.min(height) as u32;
+        for x in first_x..last_x {
+            let u = (x as f32 + 0.5 - left) / sprite_w;
+            for y in first_y..last_y {
+                let index = (y * framebuffer.width + x) as usize;
+                if depth.get(index).is_some_and(|&d| forward >= d) {
+                    continue;
+                }
+                let v = (y as f32 + 0.5 - top) / sprite_h;
+                let color = image.sample(u, v);
+                if color.a < 128 {
+                    continue;
+                }
+                let mut color = lighting.shade(sprite.kind.tint(color), forward, light);
+                if sprite.kind == SpriteKind::Ghost {
+                    color = blend(framebuffer.get_pixel(x, y), color, 0.45);
+                }
+                framebuffer.set_current_color(color);
+                framebuffer.set_pixel(x, y);
+            }
+        }
+    }
+}
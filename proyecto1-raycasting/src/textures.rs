@@ -0,0 +1,236 @@
+// textures.rs
+
+use std::collections::HashMap;
+
+use crate::color::Rgba;
+use crate::level::Level;
+use crate::level_file::LevelFile;
+use crate::maze::{DEFAULT_WALL_TEXTURE, KeyColor, Legend, Tile};
+
+/// Decodifica un archivo de imagen. Lo pone el front end (`display::load_image`, con raylib)
+/// para que las texturas no dependan de la ventana.
+pub type LoadImage = fn(&str) -> CpuImage;
+
+/// Secuencia de cuadros que se repite a velocidad constante.
+pub struct Animation {
+    pub frames: Vec<CpuImage>,
+    pub fps: f32,
+}
+
+impl Animation {
+    pub fn from_paths(paths: &[&str], fps: f32, load: LoadImage) -> Self {
+        let frames = paths
+            .iter()
+            .map(|path| load(path).with_color_key(40))
+            .collect();
+        Self { frames, fps }
+    }
+
+    pub fn frame(&self, time: f32) -> &CpuImage {
+        let index = (time * self.fps) as usize % self.frames.len();
+        &self.frames[index]
+    }
+}
+
+#[derive(Clone)]
+pub struct CpuImage {
+    pub w: usize,
+    pub h: usize,
+    pub pixels: Vec<Rgba>,
+}
+
+impl CpuImage {
+    /// Si la imagen no trae transparencia (todos los píxeles opacos), vuelve transparentes los
+    /// que se parecen al color de la esquina superior izquierda, que se toma como fondo.
+    pub fn with_color_key(mut self, tolerance: u8) -> Self {
+        if self.pixels.iter().any(|c| c.a < 255) {
+            return self;
+        }
+        let Some(&key) = self.pixels.first() else {
+            return self;
+        };
+        let near = |a: u8, b: u8| a.abs_diff(b) <= tolerance;
+        for c in self.pixels.iter_mut() {
+            if near(c.r, key.r) && near(c.g, key.g) && near(c.b, key.b) {
+                c.a = 0;
+            }
+        }
+        self
+    }
+
+    /// Puerta de tablones con marco del color de su llave.
+    pub fn door(frame: Rgba) -> Self {
+        let (w, h) = (64, 64);
+        let mut pixels = Vec::with_capacity(w * h);
+        for y in 0..h {
+            for x in 0..w {
+                let border = x < 4 || x >= w - 4 || y < 4 || y >= h - 4;
+                let color = if border {
+                    frame
+                } else if x % 16 == 0 {
+                    Rgba::new(70, 40, 20, 255)
+                } else {
+                    // Veta de la madera
+                    let grain = ((x * 7 + y * 3) % 11) as u8 * 3;
+                    Rgba::new(130 + grain, 80 + grain / 2, 40, 255)
+                };
+                pixels.push(color);
+            }
+        }
+        Self { w, h, pixels }
+    }
+
+    /// Llave blanca sobre fondo transparente, para teñirla según su color.
+    pub fn key_icon() -> Self {
+        let (w, h) = (32, 32);
+        let mut pixels = vec![Rgba::TRANSPARENT; w * h];
+        for y in 0..h {
+            for x in 0..w {
+                let (dx, dy) = (x as f32 - 8.0, y as f32 - 16.0);
+                let r = dx.hypot(dy);
+                let ring = (3.0..6.5).contains(&r);
+                let shaft = (13..30).contains(&x) && (14..18).contains(&y);
+                let teeth = (x == 22 || x == 23 || x == 27 || x == 28) && (18..23).contains(&y);
+                if ring || shaft || teeth {
+                    pixels[y * w + x] = Rgba::new(235, 235, 235, 255);
+                }
+            }
+        }
+        Self { w, h, pixels }
+    }
+
+    /// Antorcha: mango de madera y llama con un degradado del blanco al rojo. `frame`
+    /// cambia el alto y la inclinación de la llama para que parpadee.
+    pub fn torch(frame: usize) -> Self {
+        let (w, h) = (24, 48);
+        let mut pixels = vec![Rgba::TRANSPARENT; w * h];
+        let flicker = [0.0, 1.5, -1.0, 0.5][frame % 4];
+        let sway = [0.0, 0.8, -0.6, 0.3][frame % 4];
+        for y in 0..h {
+            for x in 0..w {
+                let handle = (10..14).contains(&x) && y >= 24;
+                let dx = (x as f32 + 0.5 - 12.0 - sway * (24.0 - y as f32) / 12.0) / 6.0;
+                let dy = (y as f32 + 0.5 - 14.0 - flicker / 2.0) / (10.0 + flicker);
+                let r = dx.hypot(dy);
+                pixels[y * w + x] = if r < 0.45 {
+                    Rgba::new(255, 245, 190, 255)
+                } else if r < 0.75 {
+                    Rgba::new(255, 190, 60, 255)
+                } else if r < 1.0 {
+                    Rgba::new(230, 90, 30, 255)
+                } else if handle {
+                    Rgba::new(100, 60, 30, 255)
+                } else {
+                    continue;
+                };
+            }
+        }
+        Self { w, h, pixels }
+    }
+
+    /// Copia de la pared con una palanca al centro, arriba si está apagada y abajo si no.
+    pub fn with_lever(&self, on: bool) -> Self {
+        let mut image = self.clone();
+        for y in 0..image.h {
+            for x in 0..image.w {
+                let u = x as f32 / image.w as f32;
+                let v = y as f32 / image.h as f32;
+                if !(0.35..0.65).contains(&u) || !(0.3..0.7).contains(&v) {
+                    continue;
+                }
+                let lever = (0.45..0.55).contains(&u)
+                    && if on {
+                        (0.5..0.65).contains(&v)
+                    } else {
+                        (0.35..0.5).contains(&v)
+                    };
+                image.pixels[y * image.w + x] = match (lever, on) {
+                    (true, true) => Rgba::new(40, 200, 60, 255),
+                    (true, false) => Rgba::new(210, 40, 40, 255),
+                    _ => Rgba::new(90, 90, 95, 255),
+                };
+            }
+        }
+        image
+    }
+
+    pub fn sample(&self, u: f32, v: f32) -> Rgba {
+        let x = ((u.clamp(0.0, 1.0)) * (self.w as f32 - 1.0)) as usize;
+        let y = ((v.clamp(0.0, 1.0)) * (self.h as f32 - 1.0)) as usize;
+        self.pixels[y * self.w + x]
+    }
+}
+
+/// Texturas usadas por la vista 3D.
+pub struct WorldTextures {
+    /// Texturas de pared de la leyenda del nivel, en el mismo orden que `Legend::textures`.
+    pub walls: Vec<CpuImage>,
+    pub goal: CpuImage,
+    pub floor: CpuImage,
+    pub sky: CpuImage,
+    /// Puerta sin llave y una por cada color de llave.
+    pub doors: HashMap<Option<KeyColor>, CpuImage>,
+    pub switch_off: CpuImage,
+    pub switch_on: CpuImage,
+    pub key: CpuImage,
+    /// Copa animada usada por los demás sprites.
+    pub cup: Animation,
+    pub torch: Animation,
+}
+
+impl WorldTextures {
+    /// Texturas de `level`; el archivo del nivel puede reemplazar piso, cielo y meta.
+    pub fn for_level(level: &Level, file: Option<&LevelFile>, load: LoadImage) -> Self {
+        let base_wall = load(DEFAULT_WALL_TEXTURE);
+        let mut doors = HashMap::new();
+        doors.insert(None, CpuImage::door(Rgba::new(90, 90, 95, 255)));
+        for color in KeyColor::ALL {
+            doors.insert(Some(color), CpuImage::door(color.color()));
+        }
+        let custom = file.map(|f| f.textures.clone()).unwrap_or_default();
+        let texture_or =
+            |path: &Option<String>, default: &str| load(path.as_deref().unwrap_or(default));
+        Self {
+            walls: WorldTextures::load_walls(&level.legend, load),
+            goal: texture_or(&custom.goal, "assets/texturas/goal.jpg"),
+            floor: texture_or(&custom.floor, "assets/texturas/floor.jpg"),
+            sky: texture_or(&custom.sky, "assets/texturas/cielo.jpg"),
+            doors,
+            switch_off: base_wall.with_lever(false),
+            switch_on: base_wall.with_lever(true),
+            key: CpuImage::key_icon(),
+            cup: Animation::from_paths(
+                &[
+                    "assets/copa_piston_1.png",
+                    "assets/copa_piston_2.png",
+                    "assets/copa_piston_3.png",
+                ],
+                6.0,
+                load,
+            ),
+            torch: Animation {
+                frames: (0..4).map(CpuImage::torch).collect(),
+                fps: 8.0,
+            },
+        }
+    }
+
+    /// Carga las texturas de pared que pide la leyenda.
+    fn load_walls(legend: &Legend, load: LoadImage) -> Vec<CpuImage> {
+        legend.textures.iter().map(|path| load(path)).collect()
+    }
+
+    /// Textura de la pared, puerta o interruptor representado por `cell`.
+    pub fn for_wall(&self, level: &Level, cell: char) -> &CpuImage {
+        match level.legend.tile(cell) {
+            Tile::Goal => &self.goal,
+            Tile::Door { key } => &self.doors[&key],
+            Tile::Switch { target } if level.group_open(target) => &self.switch_on,
+            Tile::Switch { .. } => &self.switch_off,
+            Tile::Wall { texture } | Tile::MovingWall { texture } => {
+                self.walls.get(texture).unwrap_or(&self.walls[0])
+            }
+            _ => &self.walls[0],
+        }
+    }
+}
@@ -0,0 +1,54 @@
+// timing.rs
+
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// Cuadros que se guardan para el gráfico de tiempos.
+pub const HISTORY_LEN: usize = 120;
+/// Peso de cada cuadro nuevo en los promedios; suaviza los saltos para poder leerlos.
+const SMOOTHING: f32 = 0.1;
+
+/// Partes de un cuadro que se miden por separado, en milisegundos.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrameTimes {
+    /// Pasos de la simulación.
+    pub simulation: f32,
+    /// Vista 3D, sprites y minimapa en el framebuffer.
+    pub render: f32,
+    /// Copia a la textura y dibujo en la ventana, incluida la espera del vsync.
+    pub present: f32,
+}
+
+/// Tiempos de los últimos cuadros para el panel que se abre con F3.
+#[derive(Default)]
+pub struct FrameTimer {
+    pub visible: bool,
+    /// Promedios suavizados de cada parte.
+    pub average: FrameTimes,
+    /// Duración total de los últimos cuadros, del más viejo al más nuevo.
+    pub history: VecDeque<f32>,
+}
+
+impl FrameTimer {
+    /// Suma un cuadro de `frame` milisegundos en total.
+    pub fn record(&mut self, times: FrameTimes, frame: f32) {
+        let smooth = |average: f32, new: f32| average + (new - average) * SMOOTHING;
+        self.average = FrameTimes {
+            simulation: smooth(self.average.simulation, times.simulation),
+            render: smooth(self.average.render, times.render),
+            present: smooth(self.average.present, times.present),
+        };
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(frame);
+    }
+}
+
+/// Milisegundos desde `clock`, que vuelve a empezar para medir la parte siguiente.
+pub fn lap(clock: &mut Instant) -> f32 {
+    let now = Instant::now();
+    let ms = (now - *clock).as_secs_f32() * 1000.0;
+    *clock = now;
+    ms
+}
@@ -0,0 +1,142 @@
+//! Carga de texturas y skyboxes desde `assets/` y su conversión a [`Assets`].
+
+use crate::math::Vec3;
+use crate::rendering::{Assets, Skybox, Tex};
+
+/// Imagen RGBA8 cruda junto con su ancho y alto.
+type RawImage = (Vec<u8>, u32, u32);
+
+/// Carga una imagen como bytes RGBA8 junto con sus dimensiones.
+fn load_texture_rgba(path: &str) -> Option<RawImage> {
+    if let Ok(img) = image::open(path) {
+        let rgba = img.to_rgba8();
+        let (w, h) = rgba.dimensions();
+        Some((rgba.into_raw(), w, h))
+    } else {
+        None
+    }
+}
+
+/// Intenta cargar un cubemap buscando primero `.jpg` y luego `.png` por cara.
+fn load_cubemap(base: &str) -> Option<Vec<RawImage>> {
+    let face_names = ["px", "nx", "py", "ny", "pz", "nz"];
+    let mut faces = Vec::with_capacity(6);
+    for n in face_names {
+        let p_jpg = format!("{}/{}.jpg", base, n);
+        let p_png = format!("{}/{}.png", base, n);
+        if let Some(t) = load_texture_rgba(&p_jpg).or_else(|| load_texture_rgba(&p_png)) {
+            faces.push(t);
+        } else {
+            return None;
+        }
+    }
+    Some(faces)
+}
+
+/// Presta una imagen cruda como [`Tex`].
+fn as_tex(img: &RawImage) -> Tex<'_> {
+    Tex {
+        pix: &img.0[..],
+        w: img.1,
+        h: img.2,
+    }
+}
+
+/// Construye un [`Skybox`] a partir de buffers crudos aplicando un tinte opcional.
+fn make_skybox(imgs: &[RawImage], tint: Vec3) -> Skybox<'_> {
+    Skybox {
+        px: as_tex(&imgs[0]),
+        nx: as_tex(&imgs[1]),
+        py: as_tex(&imgs[2]),
+        ny: as_tex(&imgs[3]),
+        pz: as_tex(&imgs[4]),
+        nz: as_tex(&imgs[5]),
+        tint,
+    }
+}
+
+/// Dueño de todos los buffers de imagen; mantiene vivas las texturas que presta [`Assets`].
+pub struct AssetStore {
+    grass: Option<RawImage>,
+    grass_cover: Option<RawImage>,
+    dirt: Option<RawImage>,
+    stone: Option<RawImage>,
+    wood: Option<RawImage>,
+    leaves: Option<RawImage>,
+    water: Option<RawImage>,
+    lava: Option<RawImage>,
+    obsidian: Option<RawImage>,
+    glowstone: Option<RawImage>,
+    diamond: Option<RawImage>,
+    iron: Option<RawImage>,
+    chest: Option<RawImage>,
+    ice: Option<RawImage>,
+    portal: Option<RawImage>,
+    overworld_cubemap: Option<Vec<RawImage>>,
+    nether_cubemap: Option<Vec<RawImage>>,
+}
+
+impl AssetStore {
+    /// Lee todas las texturas y skyboxes; las que falten quedan en `None`.
+    pub fn load() -> Self {
+        Self {
+            // grass: cesped.jpg o pasto.jpg (usa la primera que exista)
+            grass: load_texture_rgba("assets/cesped.jpg")
+                .or_else(|| load_texture_rgba("assets/pasto.jpg")),
+            grass_cover: load_texture_rgba("assets/hierba.jpg"),
+            dirt: load_texture_rgba("assets/tierra.jpg"),
+            stone: load_texture_rgba("assets/piedra.jpg"),
+            wood: load_texture_rgba("assets/madera.jpg"),
+            leaves: load_texture_rgba("assets/hojas.jpg"),
+            water: load_texture_rgba("assets/agua.jpg"),
+            lava: load_texture_rgba("assets/lava.jpg"),
+            obsidian: load_texture_rgba("assets/obsidiana.jpg"),
+            glowstone: load_texture_rgba("assets/glow.jpg"),
+            diamond: load_texture_rgba("assets/diamante.jpg"),
+            iron: load_texture_rgba("assets/hierro.jpg"),
+            chest: load_texture_rgba("assets/cofre.jpg"),
+            ice: load_texture_rgba("assets/hielo.png"),
+            portal: load_texture_rgba("assets/portal.gif"),
+            overworld_cubemap: load_cubemap("assets/skybox"),
+            nether_cubemap: load_cubemap("assets/skybox_nether"),
+        }
+    }
+
+    /// Presta los buffers como [`Assets`] listos para `build_scene`.
+    pub fn assets(&self) -> Assets<'_> {
+        let skybox_overworld = self
+            .overworld_cubemap
+            .as_ref()
+            .map(|faces| make_skybox(faces, Vec3::new(1.0, 1.0, 1.0)));
+        let skybox_nether = if let Some(faces) = self.nether_cubemap.as_ref() {
+            Some(make_skybox(faces, Vec3::new(1.0, 1.0, 1.0)))
+        } else {
+            self.overworld_cubemap
+                .as_ref()
+                .map(|faces| make_skybox(faces, Vec3::new(1.3, 0.4, 0.4)))
+        };
+
+        Assets {
+            grass_cover: self
+                .grass_cover
+                .as_ref()
+                .or(self.grass.as_ref())
+                .map(as_tex),
+            dirt: self.dirt.as_ref().map(as_tex),
+            stone: self.stone.as_ref().map(as_tex),
+            wood: self.wood.as_ref().map(as_tex),
+            leaves: self.leaves.as_ref().map(as_tex),
+            water: self.water.as_ref().map(as_tex),
+            lava: self.lava.as_ref().map(as_tex),
+            obsidian: self.obsidian.as_ref().map(as_tex),
+            glowstone: self.glowstone.as_ref().map(as_tex),
+            diamond: self.diamond.as_ref().map(as_tex),
+            iron: self.iron.as_ref().map(as_tex),
+            chest: self.chest.as_ref().map(as_tex),
+            ice: self.ice.as_ref().map(as_tex),
+            portal: self.portal.as_ref().map(as_tex),
+            skybox_overworld,
+            skybox_nether,
+        }
+    }
+}
@@ -2,6 +2,7 @@
 use crate::math::Vec3;
 use crate::ray::Ray;
 
+#[derive(Copy, Clone)]
 pub struct Camera {
     pub eye: Vec3,
     pub target: Vec3,
@@ -10,6 +11,21 @@ pub struct Camera {
 }
 
 impl Camera {
+    /// Cámara que orbita el origen (ángulos `yaw`/`pitch`, distancia `radius`) mirando hacia `target`.
+    pub fn orbit(yaw: f32, pitch: f32, radius: f32, target: Vec3) -> Self {
+        let eye = Vec3::new(
+            radius * yaw.sin() * pitch.cos(),
+            radius * pitch.sin(),
+            radius * yaw.cos() * pitch.cos(),
+        );
+        Camera {
+            eye,
+            target,
+            up: Vec3::new(0.0, 1.0, 0.0),
+            fov_y: 60.0,
+        }
+    }
+
     /// Genera un rayo que atraviesa el píxel definido por `(u, v)` en NDC.
     pub fn make_ray(&self, u: f32, v: f32, aspect: f32) -> Ray {
         let fov = self.fov_y.to_radians();
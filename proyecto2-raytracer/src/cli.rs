@@ -0,0 +1,192 @@
+//! Lectura de argumentos de línea de comandos para elegir el modo de ejecución.
+
+use std::time::Duration;
+
+use crate::camera::Camera;
+use crate::farm::{FarmOptions, FrameSpec, Transport};
+use crate::math::Vec3;
+use crate::rendering::WorldKind;
+
+/// Modo en que arranca el binario.
+pub enum Mode {
+    /// Ventana interactiva de raylib (comportamiento por defecto).
+    Interactive { photons: usize },
+    /// Worker de la granja de render.
+    Worker {
+        connect: Option<String>,
+        fail_after: Option<usize>,
+    },
+    /// Coordinador: renderiza un cuadro offline con workers y lo guarda en `out`.
+    Farm {
+        spec: FrameSpec,
+        options: FarmOptions,
+        out: String,
+    },
+}
+
+pub const USAGE: &str = "\
+uso:
+  proyecto2-raytracer [--photons N]        ventana interactiva
+  proyecto2-raytracer --farm N [opciones]  render offline con N workers locales
+  proyecto2-raytracer --worker [--connect HOST:PUERTO] [--fail-after N]
+
+opciones de --farm:
+  --transport pipe|tcp     canal con los workers (por defecto pipe)
+  --listen HOST:PUERTO     dirección de escucha TCP (por defecto 127.0.0.1:0)
+  --remote K               workers extra lanzados a mano que se conectarán por TCP
+  --tile PX                tamaño de tile (por defecto 64)
+  --size AxB               resolución (por defecto 1280x720)
+  --world overworld|nether|grove
+  --depth D                profundidad de rebotes (por defecto 4)
+  --photons N              fotones para cáusticas (por defecto 0 = desactivadas)
+  --out ARCHIVO            imagen de salida (por defecto render.png)
+  --fail-worker-after N    el worker 0 se cae tras N tiles (prueba de reasignación)
+  --worker-timeout S       segundos sin respuesta tras los que un worker se da por caído
+                           (por defecto 60)";
+
+/// Opciones que usa cada modo; las de otro modo se rechazan en lugar de ignorarse.
+const INTERACTIVE_FLAGS: &[&str] = &["--photons"];
+const WORKER_FLAGS: &[&str] = &["--worker", "--connect", "--fail-after"];
+const FARM_FLAGS: &[&str] = &[
+    "--farm",
+    "--transport",
+    "--listen",
+    "--remote",
+    "--tile",
+    "--size",
+    "--world",
+    "--depth",
+    "--photons",
+    "--out",
+    "--fail-worker-after",
+    "--worker-timeout",
+];
+
+/// Falla con la primera opción de `seen` que no está en `allowed`.
+fn check_flags(seen: &[&str], allowed: &[&str], mode: &str) -> Result<(), String> {
+    match seen.iter().find(|flag| !allowed.contains(flag)) {
+        Some(flag) => Err(format!("{} no se usa {}", flag, mode)),
+        None => Ok(()),
+    }
+}
+
+fn value<'a>(args: &'a [String], i: &mut usize, flag: &str) -> Result<&'a str, String> {
+    *i += 1;
+    args.get(*i)
+        .map(|s| s.as_str())
+        .ok_or_else(|| format!("falta el valor de {}", flag))
+}
+
+fn number<T: std::str::FromStr>(text: &str, flag: &str) -> Result<T, String> {
+    text.parse()
+        .map_err(|_| format!("valor inválido para {}: `{}`", flag, text))
+}
+
+/// Interpreta `args` (sin el nombre del programa).
+pub fn parse_mode(args: &[String]) -> Result<Mode, String> {
+    let mut worker = false;
+    let mut connect = None;
+    let mut fail_after = None;
+
+    let mut farm_workers = None;
+    let mut options = FarmOptions {
+        workers: 0,
+        transport: Transport::Pipe,
+        listen: "127.0.0.1:0".to_string(),
+        remote: 0,
+        tile_size: 64,
+        fail_worker_after: None,
+        worker_timeout: Duration::from_secs(60),
+    };
+    let (mut width, mut height) = (1280, 720);
+    let mut world = WorldKind::Overworld;
+    let mut max_depth = 4;
+    let mut photons = 0;
+    let mut out = "render.png".to_string();
+    let mut seen = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        seen.push(flag);
+        match flag {
+            "--worker" => worker = true,
+            "--connect" => connect = Some(value(args, &mut i, flag)?.to_string()),
+            "--fail-after" => fail_after = Some(number(value(args, &mut i, flag)?, flag)?),
+            "--farm" => farm_workers = Some(number(value(args, &mut i, flag)?, flag)?),
+            "--transport" => {
+                options.transport = match value(args, &mut i, flag)? {
+                    "pipe" => Transport::Pipe,
+                    "tcp" => Transport::Tcp,
+                    other => return Err(format!("transporte desconocido `{}`", other)),
+                }
+            }
+            "--listen" => options.listen = value(args, &mut i, flag)?.to_string(),
+            "--remote" => options.remote = number(value(args, &mut i, flag)?, flag)?,
+            "--tile" => options.tile_size = number(value(args, &mut i, flag)?, flag)?,
+            "--size" => {
+                let text = value(args, &mut i, flag)?;
+                let (w, h) = text
+                    .split_once('x')
+                    .ok_or_else(|| format!("--size espera AxB, no `{}`", text))?;
+                width = number(w, flag)?;
+                height = number(h, flag)?;
+            }
+            "--world" => {
+                let name = value(args, &mut i, flag)?;
+                world = WorldKind::from_name(name)
+                    .ok_or_else(|| format!("mundo desconocido `{}`", name))?;
+            }
+            "--depth" => max_depth = number(value(args, &mut i, flag)?, flag)?,
+            "--photons" => photons = number(value(args, &mut i, flag)?, flag)?,
+            "--out" => out = value(args, &mut i, flag)?.to_string(),
+            "--fail-worker-after" => {
+                options.fail_worker_after = Some(number(value(args, &mut i, flag)?, flag)?)
+            }
+            "--worker-timeout" => {
+                let secs: u64 = number(value(args, &mut i, flag)?, flag)?;
+                if secs == 0 {
+                    return Err("--worker-timeout debe ser positivo".to_string());
+                }
+                options.worker_timeout = Duration::from_secs(secs);
+            }
+            other => return Err(format!("argumento desconocido `{}`", other)),
+        }
+        i += 1;
+    }
+
+    if worker {
+        check_flags(&seen, WORKER_FLAGS, "con --worker")?;
+        return Ok(Mode::Worker {
+            connect,
+            fail_after,
+        });
+    }
+    let Some(workers) = farm_workers else {
+        check_flags(&seen, INTERACTIVE_FLAGS, "sin --farm ni --worker")?;
+        return Ok(Mode::Interactive { photons });
+    };
+    check_flags(&seen, FARM_FLAGS, "con --farm")?;
+    if width <= 0 || height <= 0 {
+        return Err("la resolución debe ser positiva".to_string());
+    }
+    if workers == 0 && options.remote == 0 {
+        return Err("se necesita al menos un worker".to_string());
+    }
+    if options.remote > 0 && options.transport == Transport::Pipe {
+        return Err("--remote requiere --transport tcp".to_string());
+    }
+    options.workers = workers;
+
+    // Misma vista inicial que la ventana interactiva.
+    let spec = FrameSpec {
+        width,
+        height,
+        camera: Camera::orbit(0.6, 0.25, 4.0, Vec3::new(1.0, 0.0, 1.0)),
+        light_pos: Vec3::new(2.5, 3.0, 2.5),
+        world,
+        max_depth,
+        photons,
+    };
+    Ok(Mode::Farm { spec, options, out })
+}
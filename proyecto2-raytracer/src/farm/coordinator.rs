@@ -0,0 +1,383 @@
+//! Coordinador: reparte los tiles entre workers, ensambla el cuadro y reasigna el trabajo
+//! de los workers que se caen.
+
+use std::collections::VecDeque;
+use std::env;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::net::TcpListener;
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::protocol::{self, FrameSpec, Message};
+use crate::assets::AssetStore;
+use crate::rendering::{TileRect, build_scene, render_tile, update_caustics};
+
+/// Canal por el que el coordinador habla con sus workers.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum Transport {
+    /// Procesos hijos conectados por stdin/stdout.
+    Pipe,
+    /// Workers que se conectan por TCP al puerto de escucha.
+    Tcp,
+}
+
+/// Configuración de la granja de render.
+pub struct FarmOptions {
+    /// Workers locales que el coordinador lanza como procesos hijos.
+    pub workers: usize,
+    pub transport: Transport,
+    /// Dirección de escucha para `Transport::Tcp`.
+    pub listen: String,
+    /// Workers adicionales (lanzados a mano) que se esperan por TCP.
+    pub remote: usize,
+    /// Lado en píxeles de cada tile.
+    pub tile_size: i32,
+    /// Hace que el primer worker local se caiga tras N tiles (prueba de tolerancia).
+    pub fail_worker_after: Option<usize>,
+    /// Espera máxima por cada mensaje de un worker (`READY` o los píxeles de un tile);
+    /// pasado ese tiempo se lo da por caído y su tile se reasigna.
+    pub worker_timeout: Duration,
+}
+
+/// Tiempo máximo para que todos los workers TCP se conecten.
+const ACCEPT_TIMEOUT: Duration = Duration::from_secs(60);
+/// Tiempo que tiene un worker local para salir tras el cuadro antes de matarlo; sólo lo
+/// agota uno que se colgó.
+const EXIT_GRACE: Duration = Duration::from_secs(2);
+
+/// Conexión abierta con un worker.
+struct WorkerLink {
+    id: usize,
+    reader: Box<dyn BufRead + Send>,
+    writer: Box<dyn Write + Send>,
+}
+
+/// Salida de un worker local leída desde un hilo aparte, para poder esperar con límite:
+/// las tuberías no tienen `set_read_timeout` como los sockets. Si el worker no envía nada
+/// en `timeout`, la lectura falla con `TimedOut`.
+struct TimedReader {
+    chunks: Receiver<Vec<u8>>,
+    pending: Vec<u8>,
+    pos: usize,
+    timeout: Duration,
+}
+
+impl TimedReader {
+    fn new(mut source: impl Read + Send + 'static, timeout: Duration) -> Self {
+        let (tx, rx) = mpsc::channel();
+        // El hilo termina cuando el worker cierra su salida (al salir o al matarlo).
+        thread::spawn(move || {
+            let mut buf = vec![0u8; 64 * 1024];
+            loop {
+                match source.read(&mut buf) {
+                    Ok(0) => return,
+                    Ok(n) => {
+                        if tx.send(buf[..n].to_vec()).is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(_) => return,
+                }
+            }
+        });
+        Self {
+            chunks: rx,
+            pending: Vec::new(),
+            pos: 0,
+            timeout,
+        }
+    }
+}
+
+impl Read for TimedReader {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.pending.len() {
+            match self.chunks.recv_timeout(self.timeout) {
+                Ok(chunk) => {
+                    self.pending = chunk;
+                    self.pos = 0;
+                }
+                // El hilo lector terminó: se ve como un EOF.
+                Err(RecvTimeoutError::Disconnected) => return Ok(0),
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "el worker no respondió a tiempo",
+                    ));
+                }
+            }
+        }
+        let n = out.len().min(self.pending.len() - self.pos);
+        out[..n].copy_from_slice(&self.pending[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// Espera a que un worker local termine y lo mata si no sale en `EXIT_GRACE`.
+fn reap(child: &mut Child) {
+    let deadline = Instant::now() + EXIT_GRACE;
+    while Instant::now() < deadline {
+        match child.try_wait() {
+            Ok(None) => thread::sleep(Duration::from_millis(20)),
+            Ok(Some(_)) | Err(_) => return,
+        }
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Divide el cuadro en tiles de `size`×`size` (los del borde pueden ser menores).
+fn split_tiles(width: i32, height: i32, size: i32) -> Vec<TileRect> {
+    let size = size.max(1);
+    let mut tiles = Vec::new();
+    let mut y = 0;
+    while y < height {
+        let mut x = 0;
+        while x < width {
+            tiles.push(TileRect {
+                x,
+                y,
+                w: size.min(width - x),
+                h: size.min(height - y),
+            });
+            x += size;
+        }
+        y += size;
+    }
+    tiles
+}
+
+/// Copia los píxeles de un tile dentro del cuadro completo.
+fn blit(frame: &mut [u8], width: i32, rect: TileRect, pixels: &[u8]) {
+    let row_bytes = (rect.w * 4) as usize;
+    for row in 0..rect.h {
+        let src = row as usize * row_bytes;
+        let dst = (((rect.y + row) * width + rect.x) * 4) as usize;
+        frame[dst..dst + row_bytes].copy_from_slice(&pixels[src..src + row_bytes]);
+    }
+}
+
+fn worker_command(id: usize, opts: &FarmOptions) -> io::Result<Command> {
+    let mut cmd = Command::new(env::current_exe()?);
+    cmd.arg("--worker").stderr(Stdio::inherit());
+    if id == 0
+        && let Some(n) = opts.fail_worker_after
+    {
+        cmd.arg("--fail-after").arg(n.to_string());
+    }
+    Ok(cmd)
+}
+
+/// Lanza los workers locales y establece todas las conexiones.
+fn connect_workers(opts: &FarmOptions) -> io::Result<(Vec<WorkerLink>, Vec<Child>)> {
+    let mut links = Vec::new();
+    let mut children = Vec::new();
+    match opts.transport {
+        Transport::Pipe => {
+            for id in 0..opts.workers {
+                let mut child = worker_command(id, opts)?
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .spawn()?;
+                let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+                    return Err(io::Error::other(
+                        "no se pudieron abrir las tuberías del worker",
+                    ));
+                };
+                links.push(WorkerLink {
+                    id,
+                    reader: Box::new(BufReader::new(TimedReader::new(
+                        stdout,
+                        opts.worker_timeout,
+                    ))),
+                    writer: Box::new(BufWriter::new(stdin)),
+                });
+                children.push(child);
+            }
+        }
+        Transport::Tcp => {
+            let listener = TcpListener::bind(&opts.listen)?;
+            let addr = listener.local_addr()?;
+            eprintln!("[farm] escuchando workers en {}", addr);
+            for id in 0..opts.workers {
+                let child = worker_command(id, opts)?
+                    .arg("--connect")
+                    .arg(addr.to_string())
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .spawn()?;
+                children.push(child);
+            }
+
+            let expected = opts.workers + opts.remote;
+            let deadline = Instant::now() + ACCEPT_TIMEOUT;
+            listener.set_nonblocking(true)?;
+            while links.len() < expected {
+                match listener.accept() {
+                    Ok((stream, peer)) => {
+                        stream.set_nonblocking(false)?;
+                        stream.set_nodelay(true)?;
+                        stream.set_read_timeout(Some(opts.worker_timeout))?;
+                        eprintln!("[farm] worker {} conectado desde {}", links.len(), peer);
+                        links.push(WorkerLink {
+                            id: links.len(),
+                            reader: Box::new(BufReader::new(stream.try_clone()?)),
+                            writer: Box::new(BufWriter::new(stream)),
+                        });
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                        if Instant::now() >= deadline {
+                            eprintln!(
+                                "[farm] sólo se conectaron {} de {} workers",
+                                links.len(),
+                                expected
+                            );
+                            break;
+                        }
+                        thread::sleep(Duration::from_millis(20));
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+    }
+    Ok((links, children))
+}
+
+/// Siguiente mensaje del worker. Un socket con `set_read_timeout` vencido falla con
+/// `WouldBlock` o `TimedOut` según la plataforma; ambos se informan igual que en las tuberías.
+fn read_reply(link: &mut WorkerLink) -> io::Result<Message> {
+    protocol::read_message(&mut link.reader).map_err(|e| match e.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => {
+            io::Error::new(io::ErrorKind::TimedOut, "el worker no respondió a tiempo")
+        }
+        _ => e,
+    })
+}
+
+/// Pide un tile a un worker y espera sus píxeles; vencer `worker_timeout` es un error
+/// como cualquier otro.
+fn request_tile(link: &mut WorkerLink, rect: TileRect) -> io::Result<Vec<u8>> {
+    protocol::write_tile(&mut link.writer, rect)?;
+    match read_reply(link)? {
+        Message::Pixels(got, pixels) if got == rect => Ok(pixels),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "respuesta inesperada del worker",
+        )),
+    }
+}
+
+/// Bucle de un hilo por worker: toma tiles de la cola hasta que el cuadro esté completo.
+/// Si el worker falla o deja de responder, su tile vuelve a la cola para que otro lo trace.
+fn drive_worker(
+    mut link: WorkerLink,
+    spec: &FrameSpec,
+    queue: &Mutex<VecDeque<TileRect>>,
+    completed: &AtomicUsize,
+    total: usize,
+    results: Sender<(TileRect, Vec<u8>)>,
+) {
+    let handshake = match read_reply(&mut link) {
+        Ok(Message::Ready) => protocol::write_frame(&mut link.writer, spec),
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "el worker no envió READY",
+        )),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = handshake {
+        eprintln!("[farm] worker {} no respondió: {}", link.id, e);
+        return;
+    }
+
+    while completed.load(Ordering::SeqCst) < total {
+        let next = queue.lock().unwrap().pop_front();
+        let Some(rect) = next else {
+            // Otros workers tienen tiles en vuelo; si alguno cae, su tile reaparece aquí.
+            thread::sleep(Duration::from_millis(10));
+            continue;
+        };
+        match request_tile(&mut link, rect) {
+            Ok(pixels) => {
+                if results.send((rect, pixels)).is_err() {
+                    return;
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "[farm] worker {} cayó ({}); reasignando tile {:?}",
+                    link.id, e, rect
+                );
+                queue.lock().unwrap().push_back(rect);
+                return;
+            }
+        }
+    }
+    let _ = protocol::write_quit(&mut link.writer);
+}
+
+/// Renderiza un cuadro completo repartiéndolo entre workers y devuelve el buffer RGBA.
+///
+/// Si todos los workers caen, el coordinador traza localmente los tiles pendientes.
+pub fn run_coordinator(spec: &FrameSpec, opts: &FarmOptions) -> io::Result<Vec<u8>> {
+    let tiles = split_tiles(spec.width, spec.height, opts.tile_size);
+    let total = tiles.len();
+    let queue = Mutex::new(VecDeque::from(tiles));
+    let completed = AtomicUsize::new(0);
+    let mut frame = vec![0u8; (spec.width * spec.height * 4) as usize];
+
+    let (links, mut children) = connect_workers(opts)?;
+    eprintln!("[farm] {} tiles para {} workers", total, links.len());
+
+    let (tx, rx) = mpsc::channel();
+    thread::scope(|scope| {
+        for link in links {
+            let tx = tx.clone();
+            let (queue, completed) = (&queue, &completed);
+            scope.spawn(move || drive_worker(link, spec, queue, completed, total, tx));
+        }
+        drop(tx);
+
+        // El canal se cierra cuando todos los hilos terminan (cuadro completo o workers caídos).
+        for (rect, pixels) in rx {
+            blit(&mut frame, spec.width, rect, &pixels);
+            completed.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+
+    for mut child in children.drain(..) {
+        reap(&mut child);
+    }
+
+    let leftovers: Vec<TileRect> = queue.into_inner().unwrap().into();
+    if !leftovers.is_empty() {
+        eprintln!(
+            "[farm] no quedan workers; trazando {} tiles localmente",
+            leftovers.len()
+        );
+        let store = AssetStore::load();
+        let mut scene = build_scene(&store.assets(), spec.world);
+        update_caustics(&mut scene, spec.light_pos, spec.photons);
+        for rect in leftovers {
+            let pixels = render_tile(
+                rect,
+                spec.width,
+                spec.height,
+                &spec.camera,
+                spec.light_pos,
+                &scene,
+                spec.max_depth,
+            );
+            blit(&mut frame, spec.width, rect, &pixels);
+        }
+    }
+
+    Ok(frame)
+}
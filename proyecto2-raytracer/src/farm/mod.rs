@@ -0,0 +1,10 @@
+//! Render distribuido por tiles: un coordinador reparte el cuadro entre procesos worker
+//! que cargan la misma escena, por tuberías (stdin/stdout) o por TCP.
+
+mod coordinator;
+mod protocol;
+mod worker;
+
+pub use coordinator::{FarmOptions, Transport, run_coordinator};
+pub use protocol::FrameSpec;
+pub use worker::run_worker;
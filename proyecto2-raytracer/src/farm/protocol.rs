@@ -0,0 +1,160 @@
+//! Protocolo de texto + binario entre coordinador y workers.
+//!
+//! Cada mensaje es una línea ASCII; `PIXELS` va seguido de `w*h*4` bytes RGBA crudos.
+//!
+//! ```text
+//! worker -> coord : READY
+//! coord -> worker : FRAME w h ex ey ez tx ty tz ux uy uz fov lx ly lz mundo profundidad fotones
+//! coord -> worker : TILE x y w h
+//! worker -> coord : PIXELS x y w h  + bytes
+//! coord -> worker : QUIT
+//! ```
+
+use std::io::{self, BufRead, Write};
+
+use crate::camera::Camera;
+use crate::math::Vec3;
+use crate::rendering::{TileRect, WorldKind};
+
+/// Parámetros de un cuadro que todos los workers necesitan para trazar sus tiles.
+#[derive(Copy, Clone)]
+pub struct FrameSpec {
+    pub width: i32,
+    pub height: i32,
+    pub camera: Camera,
+    pub light_pos: Vec3,
+    pub world: WorldKind,
+    pub max_depth: i32,
+    /// Fotones cáusticos a emitir (`0` desactiva las cáusticas).
+    pub photons: usize,
+}
+
+/// Mensajes que pueden viajar en cualquiera de las dos direcciones.
+pub enum Message {
+    Ready,
+    Frame(FrameSpec),
+    Tile(TileRect),
+    Pixels(TileRect, Vec<u8>),
+    Quit,
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn field<T: std::str::FromStr>(parts: &[&str], i: usize, line: &str) -> io::Result<T> {
+    parts
+        .get(i)
+        .and_then(|p| p.parse().ok())
+        .ok_or_else(|| invalid(format!("campo {} inválido en `{}`", i, line)))
+}
+
+fn parse_rect(parts: &[&str], line: &str) -> io::Result<TileRect> {
+    Ok(TileRect {
+        x: field(parts, 1, line)?,
+        y: field(parts, 2, line)?,
+        w: field(parts, 3, line)?,
+        h: field(parts, 4, line)?,
+    })
+}
+
+fn parse_frame(parts: &[&str], line: &str) -> io::Result<FrameSpec> {
+    let v = |i: usize| -> io::Result<Vec3> {
+        Ok(Vec3::new(
+            field(parts, i, line)?,
+            field(parts, i + 1, line)?,
+            field(parts, i + 2, line)?,
+        ))
+    };
+    let world_name: String = field(parts, 16, line)?;
+    let world = WorldKind::from_name(&world_name)
+        .ok_or_else(|| invalid(format!("mundo desconocido `{}`", world_name)))?;
+    Ok(FrameSpec {
+        width: field(parts, 1, line)?,
+        height: field(parts, 2, line)?,
+        camera: Camera {
+            eye: v(3)?,
+            target: v(6)?,
+            up: v(9)?,
+            fov_y: field(parts, 12, line)?,
+        },
+        light_pos: v(13)?,
+        world,
+        max_depth: field(parts, 17, line)?,
+        photons: field(parts, 18, line)?,
+    })
+}
+
+/// Lee el siguiente mensaje; un EOF se reporta como `UnexpectedEof` (worker caído).
+pub fn read_message(reader: &mut dyn BufRead) -> io::Result<Message> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "la conexión se cerró",
+        ));
+    }
+    let line = line.trim_end();
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    match parts.first().copied() {
+        Some("READY") => Ok(Message::Ready),
+        Some("QUIT") => Ok(Message::Quit),
+        Some("FRAME") => Ok(Message::Frame(parse_frame(&parts, line)?)),
+        Some("TILE") => Ok(Message::Tile(parse_rect(&parts, line)?)),
+        Some("PIXELS") => {
+            let rect = parse_rect(&parts, line)?;
+            let mut pixels = vec![0u8; (rect.w.max(0) * rect.h.max(0) * 4) as usize];
+            reader.read_exact(&mut pixels)?;
+            Ok(Message::Pixels(rect, pixels))
+        }
+        _ => Err(invalid(format!("mensaje desconocido `{}`", line))),
+    }
+}
+
+pub fn write_ready(writer: &mut dyn Write) -> io::Result<()> {
+    writeln!(writer, "READY")?;
+    writer.flush()
+}
+
+pub fn write_quit(writer: &mut dyn Write) -> io::Result<()> {
+    writeln!(writer, "QUIT")?;
+    writer.flush()
+}
+
+pub fn write_frame(writer: &mut dyn Write, spec: &FrameSpec) -> io::Result<()> {
+    let c = &spec.camera;
+    writeln!(
+        writer,
+        "FRAME {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {}",
+        spec.width,
+        spec.height,
+        c.eye.x,
+        c.eye.y,
+        c.eye.z,
+        c.target.x,
+        c.target.y,
+        c.target.z,
+        c.up.x,
+        c.up.y,
+        c.up.z,
+        c.fov_y,
+        spec.light_pos.x,
+        spec.light_pos.y,
+        spec.light_pos.z,
+        spec.world.name(),
+        spec.max_depth,
+        spec.photons,
+    )?;
+    writer.flush()
+}
+
+pub fn write_tile(writer: &mut dyn Write, rect: TileRect) -> io::Result<()> {
+    writeln!(writer, "TILE {} {} {} {}", rect.x, rect.y, rect.w, rect.h)?;
+    writer.flush()
+}
+
+pub fn write_pixels(writer: &mut dyn Write, rect: TileRect, pixels: &[u8]) -> io::Result<()> {
+    writeln!(writer, "PIXELS {} {} {} {}", rect.x, rect.y, rect.w, rect.h)?;
+    writer.write_all(pixels)?;
+    writer.flush()
+}
@@ -0,0 +1,92 @@
+//! Proceso worker: carga la misma escena que el coordinador y traza los tiles que recibe.
+
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::net::TcpStream;
+
+use super::protocol::{self, FrameSpec, Message};
+use crate::assets::AssetStore;
+use crate::rendering::{SceneData, WorldKind, build_scene, render_tile, update_caustics};
+
+/// Ejecuta el worker sobre stdin/stdout o, si se indica `connect`, sobre TCP.
+///
+/// `fail_after` simula una caída abrupta tras trazar ese número de tiles (útil para
+/// probar la reasignación en el coordinador).
+pub fn run_worker(connect: Option<&str>, fail_after: Option<usize>) -> io::Result<()> {
+    match connect {
+        Some(addr) => {
+            let stream = TcpStream::connect(addr)?;
+            stream.set_nodelay(true)?;
+            let mut reader = BufReader::new(stream.try_clone()?);
+            let mut writer = BufWriter::new(stream);
+            serve(&mut reader, &mut writer, fail_after)
+        }
+        None => {
+            let mut reader = io::stdin().lock();
+            let mut writer = BufWriter::new(io::stdout().lock());
+            serve(&mut reader, &mut writer, fail_after)
+        }
+    }
+}
+
+fn serve(
+    reader: &mut dyn BufRead,
+    writer: &mut dyn Write,
+    fail_after: Option<usize>,
+) -> io::Result<()> {
+    let store = AssetStore::load();
+    let assets = store.assets();
+    // Cada mundo se construye una sola vez, la primera vez que se pide.
+    let mut overworld: Option<SceneData> = None;
+    let mut nether: Option<SceneData> = None;
+    let mut grove: Option<SceneData> = None;
+
+    protocol::write_ready(writer)?;
+
+    let mut frame: Option<FrameSpec> = None;
+    let mut tiles_done = 0usize;
+    loop {
+        match protocol::read_message(reader)? {
+            Message::Frame(spec) => frame = Some(spec),
+            Message::Tile(rect) => {
+                let spec = frame.ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "TILE recibido antes de FRAME")
+                })?;
+                if fail_after == Some(tiles_done) {
+                    eprintln!("[worker] caída simulada tras {} tiles", tiles_done);
+                    std::process::exit(3);
+                }
+                let scene = match spec.world {
+                    WorldKind::Overworld => {
+                        overworld.get_or_insert_with(|| build_scene(&assets, WorldKind::Overworld))
+                    }
+                    WorldKind::Nether => {
+                        nether.get_or_insert_with(|| build_scene(&assets, WorldKind::Nether))
+                    }
+                    WorldKind::Grove => {
+                        grove.get_or_insert_with(|| build_scene(&assets, WorldKind::Grove))
+                    }
+                };
+                // El mapa de fotones es determinista: todos los workers obtienen el mismo.
+                update_caustics(scene, spec.light_pos, spec.photons);
+                let pixels = render_tile(
+                    rect,
+                    spec.width,
+                    spec.height,
+                    &spec.camera,
+                    spec.light_pos,
+                    scene,
+                    spec.max_depth,
+                );
+                protocol::write_pixels(writer, rect, &pixels)?;
+                tiles_done += 1;
+            }
+            Message::Quit => return Ok(()),
+            Message::Ready | Message::Pixels(..) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "mensaje inesperado para un worker",
+                ));
+            }
+        }
+    }
+}
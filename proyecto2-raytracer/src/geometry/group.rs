@@ -0,0 +1,388 @@
+//! Grupo de objetos con su propia BVH; es la geometría compartida de un prefab.
+
+use crate::math::Vec3;
+use crate::ray::Ray;
+use crate::scene::{Intersectable, LeafBounds, MaterialParams, SurfaceHit};
+
+type Child<'a> = Box<dyn Intersectable + Send + Sync + 'a>;
+
+/// Máximo de objetos por hoja de la BVH.
+const LEAF_SIZE: usize = 4;
+
+enum BvhKind {
+    Leaf { start: usize, count: usize },
+    Inner { left: usize, right: usize },
+}
+
+struct BvhNode {
+    min: Vec3,
+    max: Vec3,
+    kind: BvhKind,
+}
+
+/// Conjunto de objetos en un mismo espacio local, acelerado con una BVH.
+///
+/// Se comparte con `Arc` entre todas las instancias de un prefab, así que los bloques
+/// y la jerarquía de cajas existen una sola vez en memoria.
+pub struct Group<'a> {
+    objects: Vec<Child<'a>>,
+    /// Primer `leaf` de cada hijo: las primitivas del grupo se numeran seguidas, hijo por
+    /// hijo, y el último valor es el total.
+    first_leaf: Vec<usize>,
+    /// Índices de `objects` ordenados según las hojas de la BVH.
+    order: Vec<usize>,
+    nodes: Vec<BvhNode>,
+    /// Objetos sin caja envolvente: se prueban siempre.
+    unbounded: Vec<usize>,
+}
+
+fn union(a: (Vec3, Vec3), b: (Vec3, Vec3)) -> (Vec3, Vec3) {
+    (
+        Vec3::new(a.0.x.min(b.0.x), a.0.y.min(b.0.y), a.0.z.min(b.0.z)),
+        Vec3::new(a.1.x.max(b.1.x), a.1.y.max(b.1.y), a.1.z.max(b.1.z)),
+    )
+}
+
+fn axis_value(v: Vec3, axis: usize) -> f32 {
+    match axis {
+        0 => v.x,
+        1 => v.y,
+        _ => v.z,
+    }
+}
+
+/// Distancia de entrada del rayo a la caja, si la toca antes de `t_max`.
+fn ray_box(ray: &Ray, min: Vec3, max: Vec3, t_max: f32) -> Option<f32> {
+    let mut t0 = 0.0f32;
+    let mut t1 = t_max;
+    for axis in 0..3 {
+        let o = axis_value(ray.orig, axis);
+        let d = axis_value(ray.dir, axis);
+        let (lo, hi) = (axis_value(min, axis), axis_value(max, axis));
+        if d.abs() < 1e-12 {
+            if o < lo || o > hi {
+                return None;
+            }
+            continue;
+        }
+        let inv = 1.0 / d;
+        let (mut near, mut far) = ((lo - o) * inv, (hi - o) * inv);
+        if near > far {
+            std::mem::swap(&mut near, &mut far);
+        }
+        t0 = t0.max(near);
+        t1 = t1.min(far);
+        if t0 > t1 {
+            return None;
+        }
+    }
+    Some(t0)
+}
+
+impl<'a> Group<'a> {
+    pub fn new(objects: Vec<Child<'a>>) -> Self {
+        let mut bounded: Vec<(usize, (Vec3, Vec3))> = Vec::new();
+        let mut unbounded = Vec::new();
+        for (i, o) in objects.iter().enumerate() {
+            match o.bounds() {
+                Some(b) => bounded.push((i, b)),
+                None => unbounded.push(i),
+            }
+        }
+        let mut first_leaf = Vec::with_capacity(objects.len() + 1);
+        first_leaf.push(0);
+        for o in &objects {
+            first_leaf.push(first_leaf[first_leaf.len() - 1] + o.leaf_count());
+        }
+        let mut group = Group {
+            objects,
+            first_leaf,
+            order: Vec::with_capacity(bounded.len()),
+            nodes: Vec::new(),
+            unbounded,
+        };
+        if !bounded.is_empty() {
+            group.build(&mut bounded);
+        }
+        group
+    }
+
+    /// Construye recursivamente la BVH partiendo por la mediana del eje más largo.
+    fn build(&mut self, items: &mut [(usize, (Vec3, Vec3))]) -> usize {
+        let bounds = items
+            .iter()
+            .map(|(_, b)| *b)
+            .reduce(union)
+            .expect("nodo BVH vacío");
+        let node_index = self.nodes.len();
+        if items.len() <= LEAF_SIZE {
+            let start = self.order.len();
+            self.order.extend(items.iter().map(|(i, _)| *i));
+            self.nodes.push(BvhNode {
+                min: bounds.0,
+                max: bounds.1,
+                kind: BvhKind::Leaf {
+                    start,
+                    count: items.len(),
+                },
+            });
+            return node_index;
+        }
+
+        let ext = bounds.1.sub(bounds.0);
+        let axis = if ext.x >= ext.y && ext.x >= ext.z {
+            0
+        } else if ext.y >= ext.z {
+            1
+        } else {
+            2
+        };
+        let center = |b: &(Vec3, Vec3)| axis_value(b.0.add(b.1), axis);
+        let mid = items.len() / 2;
+        items.select_nth_unstable_by(mid, |a, b| center(&a.1).total_cmp(&center(&b.1)));
+
+        // Reserva el nodo antes de construir los hijos para conocer su índice.
+        self.nodes.push(BvhNode {
+            min: bounds.0,
+            max: bounds.1,
+            kind: BvhKind::Leaf { start: 0, count: 0 },
+        });
+        let (left_items, right_items) = items.split_at_mut(mid);
+        let left = self.build(left_items);
+        let right = self.build(right_items);
+        self.nodes[node_index].kind = BvhKind::Inner { left, right };
+        node_index
+    }
+
+    /// `exclude` en la numeración del hijo `i`, si la primitiva es suya.
+    fn child_exclude(&self, i: usize, exclude: Option<usize>) -> Option<usize> {
+        let leaf = exclude?;
+        (self.first_leaf[i]..self.first_leaf[i + 1])
+            .contains(&leaf)
+            .then(|| leaf - self.first_leaf[i])
+    }
+
+    /// Hijo más cercano según `test`, que da la distancia a un hijo y lo que haga falta de
+    /// él si el rayo lo toca. La BVH se recorre de adelante hacia atrás y cada hijo se prueba
+    /// una sola vez, así que `hit` no repite el recorrido para resolver al ganador.
+    fn closest<T>(
+        &self,
+        ray: &Ray,
+        test: impl Fn(usize) -> Option<(f32, T)>,
+    ) -> Option<(usize, f32, T)> {
+        let mut best: Option<(usize, f32, T)> = None;
+        // En caras coincidentes gana el hijo con menor índice, igual que en una lista plana;
+        // así el resultado no depende del orden de recorrido de la BVH.
+        let consider = |i: usize, best: &mut Option<(usize, f32, T)>| {
+            if let Some((t, value)) = test(i)
+                && t > 0.0
+                && best
+                    .as_ref()
+                    .is_none_or(|&(bi, bt, _)| t < bt || (t == bt && i < bi))
+            {
+                *best = Some((i, t, value));
+            }
+        };
+        for &i in &self.unbounded {
+            consider(i, &mut best);
+        }
+        let Some(root) = self.nodes.first() else {
+            return best;
+        };
+        let Some(entry) = ray_box(ray, root.min, root.max, f32::INFINITY) else {
+            return best;
+        };
+
+        let mut stack = vec![(0usize, entry)];
+        while let Some((n, entry)) = stack.pop() {
+            let limit = best.as_ref().map_or(f32::INFINITY, |&(_, t, _)| t);
+            if entry > limit {
+                continue;
+            }
+            match self.nodes[n].kind {
+                BvhKind::Leaf { start, count } => {
+                    for &i in &self.order[start..start + count] {
+                        consider(i, &mut best);
+                    }
+                }
+                BvhKind::Inner { left, right } => {
+                    let near = |c: usize| {
+                        let node = &self.nodes[c];
+                        ray_box(ray, node.min, node.max, limit).map(|t| (c, t))
+                    };
+                    // El hijo más cercano va arriba de la pila para que se pruebe primero.
+                    match (near(left), near(right)) {
+                        (Some(a), Some(b)) if a.1 <= b.1 => stack.extend([b, a]),
+                        (Some(a), Some(b)) => stack.extend([a, b]),
+                        (Some(a), None) | (None, Some(a)) => stack.push(a),
+                        (None, None) => {}
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    /// Hijo cuya caja contiene `p` (el de menor índice si hay varios), buscado en la BVH;
+    /// sirve para las consultas por punto sin rayo.
+    fn child_at(&self, p: Vec3) -> Option<&Child<'a>> {
+        let eps = 1e-3;
+        let inside = |(min, max): (Vec3, Vec3)| {
+            p.x >= min.x - eps
+                && p.x <= max.x + eps
+                && p.y >= min.y - eps
+                && p.y <= max.y + eps
+                && p.z >= min.z - eps
+                && p.z <= max.z + eps
+        };
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let mut found: Option<usize> = None;
+        let mut stack = vec![0usize];
+        while let Some(n) = stack.pop() {
+            let node = &self.nodes[n];
+            if !inside((node.min, node.max)) {
+                continue;
+            }
+            match node.kind {
+                BvhKind::Leaf { start, count } => {
+                    for &i in &self.order[start..start + count] {
+                        if found.is_none_or(|f| i < f)
+                            && self.objects[i].bounds().is_some_and(inside)
+                        {
+                            found = Some(i);
+                        }
+                    }
+                }
+                BvhKind::Inner { left, right } => {
+                    stack.push(left);
+                    stack.push(right);
+                }
+            }
+        }
+        found.map(|i| &self.objects[i])
+    }
+}
+
+impl<'a> Intersectable for Group<'a> {
+    fn intersect(&self, ray: &Ray) -> Option<f32> {
+        self.closest(ray, |i| self.objects[i].intersect(ray).map(|t| (t, ())))
+            .map(|(_, t, _)| t)
+    }
+    fn hit(&self, ray: &Ray, exclude: Option<usize>) -> Option<SurfaceHit> {
+        let (i, _, hit) = self.closest(ray, |i| {
+            let hit = self.objects[i].hit(ray, self.child_exclude(i, exclude))?;
+            Some((hit.t, hit))
+        })?;
+        Some(SurfaceHit {
+            leaf: self.first_leaf[i] + hit.leaf,
+            ..hit
+        })
+    }
+    fn leaf_count(&self) -> usize {
+        self.first_leaf[self.objects.len()]
+    }
+    fn leaves(&self) -> Vec<LeafBounds> {
+        let mut leaves = Vec::new();
+        for (o, &first) in self.objects.iter().zip(&self.first_leaf) {
+            leaves.extend(o.leaves().into_iter().map(|l| LeafBounds {
+                leaf: first + l.leaf,
+                ..l
+            }));
+        }
+        leaves
+    }
+    fn normal_at(&self, point: Vec3) -> Vec3 {
+        self.child_at(point)
+            .map_or(Vec3::new(0.0, 1.0, 0.0), |o| o.normal_at(point))
+    }
+    fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        if !self.unbounded.is_empty() {
+            return None;
+        }
+        self.nodes.first().map(|n| (n.min, n.max))
+    }
+    fn albedo(&self) -> Vec3 {
+        self.objects
+            .first()
+            .map_or(Vec3::new(1.0, 1.0, 1.0), |o| o.albedo())
+    }
+    fn material_at(&self, p: Vec3) -> MaterialParams {
+        match self.child_at(p) {
+            Some(o) => o.material_at(p),
+            None => MaterialParams {
+                albedo: self.albedo(),
+                specular_strength: 0.0,
+                shininess: 16.0,
+                reflectivity: 0.0,
+                transparency: 0.0,
+                ior: 1.0,
+                emissive: Vec3::new(0.0, 0.0, 0.0),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::geometry::{Instance, SolidBlock};
+    use crate::math::Transform;
+
+    fn block(x: f32) -> Child<'static> {
+        Box::new(SolidBlock {
+            min: Vec3::new(x - 0.5, -0.5, -0.5),
+            max: Vec3::new(x + 0.5, 0.5, 0.5),
+            ..SolidBlock::unit()
+        })
+    }
+
+    /// `[C, instancia de [A, B] en x = 3, D]`: C es el leaf 0, A y B el 1 y el 2, D el 3.
+    fn nested() -> Group<'static> {
+        let inner = Arc::new(Group::new(vec![block(0.0), block(1.0)]));
+        let placed = Instance::new(inner, Transform::translate(Vec3::new(3.0, 0.0, 0.0)));
+        Group::new(vec![block(0.0), Box::new(placed), block(7.0)])
+    }
+
+    fn down_at(x: f32) -> Ray {
+        Ray {
+            orig: Vec3::new(x, 10.0, 0.0),
+            dir: Vec3::new(0.0, -1.0, 0.0),
+        }
+    }
+
+    #[test]
+    fn numbers_leaves_across_nested_groups() {
+        let group = nested();
+        assert_eq!(group.leaf_count(), 4);
+        let leaves: Vec<usize> = group.leaves().iter().map(|l| l.leaf).collect();
+        assert_eq!(leaves, [0, 1, 2, 3]);
+        for (x, leaf) in [(0.0, 0), (3.0, 1), (4.0, 2), (7.0, 3)] {
+            let hit = group
+                .hit(&down_at(x), None)
+                .expect("el rayo toca un bloque");
+            assert_eq!(hit.leaf, leaf, "x = {}", x);
+        }
+    }
+
+    #[test]
+    fn excluded_leaf_inside_an_instance_is_skipped() {
+        let group = nested();
+        let along = Ray {
+            orig: Vec3::new(10.0, 0.0, 0.0),
+            dir: Vec3::new(-1.0, 0.0, 0.0),
+        };
+        assert_eq!(group.hit(&along, None).map(|h| h.leaf), Some(3));
+        assert_eq!(group.hit(&along, Some(3)).map(|h| h.leaf), Some(2));
+        // Sin B, el rayo sigue hasta A dentro de la misma instancia.
+        let inside_b = Ray {
+            orig: Vec3::new(4.0, 0.0, 0.0),
+            ..along
+        };
+        assert_eq!(group.hit(&inside_b, Some(2)).map(|h| h.leaf), Some(1));
+        assert!(group.hit(&down_at(4.0), Some(2)).is_none());
+    }
+}
@@ -0,0 +1,115 @@
+//! Instancia: coloca cualquier `Intersectable` compartido en el mundo con una transformación.
+
+use std::sync::Arc;
+
+use crate::math::{Transform, Vec3};
+use crate::ray::Ray;
+use crate::scene::{Intersectable, LeafBounds, MaterialParams, SurfaceHit};
+
+/// Objeto compartido (p. ej. un prefab) visto a través de una transformación local → mundo.
+///
+/// Los rayos se llevan a espacio objeto; como la dirección no se renormaliza, la distancia
+/// `t` devuelta vale igual en ambos espacios.
+pub struct Instance<'a> {
+    pub object: Arc<dyn Intersectable + Send + Sync + 'a>,
+    pub transform: Transform,
+}
+
+impl<'a> Instance<'a> {
+    pub fn new(object: Arc<dyn Intersectable + Send + Sync + 'a>, transform: Transform) -> Self {
+        Self { object, transform }
+    }
+
+    fn local_ray(&self, ray: &Ray) -> Ray {
+        Ray {
+            orig: self.transform.inverse_point(ray.orig),
+            dir: self.transform.inverse_vector(ray.dir),
+        }
+    }
+}
+
+impl<'a> Intersectable for Instance<'a> {
+    fn intersect(&self, ray: &Ray) -> Option<f32> {
+        self.object.intersect(&self.local_ray(ray))
+    }
+    fn hit(&self, ray: &Ray, exclude: Option<usize>) -> Option<SurfaceHit> {
+        let local = self.object.hit(&self.local_ray(ray), exclude)?;
+        Some(SurfaceHit {
+            normal: self.transform.apply_normal(local.normal),
+            ..local
+        })
+    }
+    fn leaf_count(&self) -> usize {
+        self.object.leaf_count()
+    }
+    fn leaves(&self) -> Vec<LeafBounds> {
+        self.object
+            .leaves()
+            .into_iter()
+            .map(|l| {
+                let (min, max) = self.transform.apply_bounds(l.min, l.max);
+                LeafBounds { min, max, ..l }
+            })
+            .collect()
+    }
+    fn normal_at(&self, point: Vec3) -> Vec3 {
+        let local = self.transform.inverse_point(point);
+        self.transform.apply_normal(self.object.normal_at(local))
+    }
+    fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        let (min, max) = self.object.bounds()?;
+        Some(self.transform.apply_bounds(min, max))
+    }
+    fn albedo(&self) -> Vec3 {
+        self.object.albedo()
+    }
+    fn material_at(&self, p: Vec3) -> MaterialParams {
+        self.object.material_at(self.transform.inverse_point(p))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_1_SQRT_2, FRAC_PI_4, SQRT_2};
+
+    use super::*;
+    use crate::geometry::SolidBlock;
+
+    /// Cubo unitario escalado al doble, girado 45° en `y` y con el centro en `(5, 0, 0)`.
+    fn turned_cube() -> Instance<'static> {
+        let transform = Transform::translate(Vec3::new(5.0, 0.0, 0.0))
+            .then(&Transform::rotate(Vec3::new(0.0, FRAC_PI_4, 0.0)))
+            .then(&Transform::scale(Vec3::new(2.0, 2.0, 2.0)));
+        Instance::new(Arc::new(SolidBlock::unit()), transform)
+    }
+
+    fn ray_from(x: f32) -> Ray {
+        Ray {
+            orig: Vec3::new(x, 0.0, -10.0),
+            dir: Vec3::new(0.0, 0.0, 1.0),
+        }
+    }
+
+    #[test]
+    fn hits_the_transformed_face_in_world_space() {
+        let cube = turned_cube();
+        // La cara local +x queda mirando a (1, 0, -1) a √2 del centro por la diagonal.
+        let hit = cube
+            .hit(&ray_from(5.3), None)
+            .expect("el rayo toca el cubo");
+        assert!((hit.t - (10.3 - SQRT_2)).abs() < 1e-4, "t = {}", hit.t);
+        assert!(
+            hit.normal
+                .sub(Vec3::new(FRAC_1_SQRT_2, 0.0, -FRAC_1_SQRT_2))
+                .len()
+                < 1e-4
+        );
+        assert_eq!(cube.intersect(&ray_from(5.3)), Some(hit.t));
+    }
+
+    #[test]
+    fn misses_outside_the_rotated_corner() {
+        assert!(turned_cube().intersect(&ray_from(5.0 + 1.5)).is_none());
+        assert!(turned_cube().intersect(&ray_from(5.0 + 1.4)).is_some());
+    }
+}
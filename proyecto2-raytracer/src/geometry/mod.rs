@@ -1,7 +1,11 @@
 //! Primitivas geométricas utilizadas para trazar (cubos sólidos, variantes texturizadas, etc.).
 
+pub mod group;
+pub mod instance;
 pub mod solid_block;
 pub mod textured_block;
 
+pub use group::Group;
+pub use instance::Instance;
 pub use solid_block::SolidBlock;
 pub use textured_block::TexturedBlock;
@@ -116,6 +116,9 @@ impl Intersectable for SolidBlock {
     fn intersect(&self, ray: &Ray) -> Option<f32> {
         self.intersect_impl(ray)
     }
+    fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        Some((self.min, self.max))
+    }
     fn normal_at(&self, point: Vec3) -> Vec3 {
         self.normal_impl(point)
     }
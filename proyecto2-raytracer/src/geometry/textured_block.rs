@@ -103,6 +103,9 @@ impl<'a> Intersectable for TexturedBlock<'a> {
     fn intersect(&self, ray: &Ray) -> Option<f32> {
         self.inner.intersect(ray)
     }
+    fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        self.inner.bounds()
+    }
     fn normal_at(&self, point: Vec3) -> Vec3 {
         self.inner.normal_at(point)
     }
@@ -1,5 +1,7 @@
-//! Utilidades matemáticas (vectores, productos, etc.).
+//! Utilidades matemáticas (vectores, transformaciones, etc.).
 
+pub mod transform;
 pub mod vec3;
 
+pub use transform::Transform;
 pub use vec3::Vec3;
@@ -0,0 +1,213 @@
+//! Transformaciones afines (traslación, rotación y escala) para el grafo de escena.
+
+use super::vec3::Vec3;
+
+/// Matriz 3×3 por filas.
+#[derive(Copy, Clone, Debug)]
+struct Mat3([[f32; 3]; 3]);
+
+impl Mat3 {
+    fn identity() -> Self {
+        Mat3([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]])
+    }
+
+    fn mul_vec(&self, v: Vec3) -> Vec3 {
+        let m = &self.0;
+        Vec3::new(
+            m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+            m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+            m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z,
+        )
+    }
+
+    fn mul(&self, o: &Mat3) -> Mat3 {
+        let mut r = [[0.0; 3]; 3];
+        for (i, row) in r.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = (0..3).map(|k| self.0[i][k] * o.0[k][j]).sum();
+            }
+        }
+        Mat3(r)
+    }
+
+    fn transpose(&self) -> Mat3 {
+        let m = &self.0;
+        Mat3([
+            [m[0][0], m[1][0], m[2][0]],
+            [m[0][1], m[1][1], m[2][1]],
+            [m[0][2], m[1][2], m[2][2]],
+        ])
+    }
+
+    /// Inversa por adjunta; una matriz singular (escala 0) devuelve la identidad.
+    fn inverse(&self) -> Mat3 {
+        let m = &self.0;
+        let c00 = m[1][1] * m[2][2] - m[1][2] * m[2][1];
+        let c01 = m[1][2] * m[2][0] - m[1][0] * m[2][2];
+        let c02 = m[1][0] * m[2][1] - m[1][1] * m[2][0];
+        let det = m[0][0] * c00 + m[0][1] * c01 + m[0][2] * c02;
+        if det.abs() < 1e-12 {
+            return Mat3::identity();
+        }
+        let inv_det = 1.0 / det;
+        Mat3([
+            [
+                c00 * inv_det,
+                (m[0][2] * m[2][1] - m[0][1] * m[2][2]) * inv_det,
+                (m[0][1] * m[1][2] - m[0][2] * m[1][1]) * inv_det,
+            ],
+            [
+                c01 * inv_det,
+                (m[0][0] * m[2][2] - m[0][2] * m[2][0]) * inv_det,
+                (m[0][2] * m[1][0] - m[0][0] * m[1][2]) * inv_det,
+            ],
+            [
+                c02 * inv_det,
+                (m[0][1] * m[2][0] - m[0][0] * m[2][1]) * inv_det,
+                (m[0][0] * m[1][1] - m[0][1] * m[1][0]) * inv_det,
+            ],
+        ])
+    }
+}
+
+/// Transformación afín `p' = M·p + t` junto con las inversas que necesita el trazador.
+#[derive(Copy, Clone, Debug)]
+pub struct Transform {
+    linear: Mat3,
+    translation: Vec3,
+    inv_linear: Mat3,
+    /// Inversa transpuesta, para llevar normales a espacio mundo.
+    normal_linear: Mat3,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Self::from_parts(Mat3::identity(), Vec3::new(0.0, 0.0, 0.0))
+    }
+
+    fn from_parts(linear: Mat3, translation: Vec3) -> Self {
+        let inv_linear = linear.inverse();
+        Self {
+            linear,
+            translation,
+            inv_linear,
+            normal_linear: inv_linear.transpose(),
+        }
+    }
+
+    pub fn translate(offset: Vec3) -> Self {
+        Self::from_parts(Mat3::identity(), offset)
+    }
+
+    /// Rotación en radianes: primero `z`, luego `x` y al final `y` (giro horizontal).
+    pub fn rotate(angles: Vec3) -> Self {
+        let (sx, cx) = angles.x.sin_cos();
+        let (sy, cy) = angles.y.sin_cos();
+        let (sz, cz) = angles.z.sin_cos();
+        let rx = Mat3([[1.0, 0.0, 0.0], [0.0, cx, -sx], [0.0, sx, cx]]);
+        let ry = Mat3([[cy, 0.0, sy], [0.0, 1.0, 0.0], [-sy, 0.0, cy]]);
+        let rz = Mat3([[cz, -sz, 0.0], [sz, cz, 0.0], [0.0, 0.0, 1.0]]);
+        Self::from_parts(ry.mul(&rx).mul(&rz), Vec3::new(0.0, 0.0, 0.0))
+    }
+
+    pub fn scale(factors: Vec3) -> Self {
+        let m = Mat3([
+            [factors.x, 0.0, 0.0],
+            [0.0, factors.y, 0.0],
+            [0.0, 0.0, factors.z],
+        ]);
+        Self::from_parts(m, Vec3::new(0.0, 0.0, 0.0))
+    }
+
+    /// Composición `self ∘ inner`: aplica primero `inner` y después `self`.
+    pub fn then(&self, inner: &Transform) -> Transform {
+        Self::from_parts(
+            self.linear.mul(&inner.linear),
+            self.linear.mul_vec(inner.translation).add(self.translation),
+        )
+    }
+
+    pub fn apply_point(&self, p: Vec3) -> Vec3 {
+        self.linear.mul_vec(p).add(self.translation)
+    }
+
+    /// Normal en espacio local → normal unitaria en espacio mundo.
+    pub fn apply_normal(&self, n: Vec3) -> Vec3 {
+        self.normal_linear.mul_vec(n).norm()
+    }
+
+    pub fn inverse_point(&self, p: Vec3) -> Vec3 {
+        self.inv_linear.mul_vec(p.sub(self.translation))
+    }
+
+    pub fn inverse_vector(&self, v: Vec3) -> Vec3 {
+        self.inv_linear.mul_vec(v)
+    }
+
+    /// Caja envolvente en espacio mundo de una caja local `(min, max)`.
+    pub fn apply_bounds(&self, min: Vec3, max: Vec3) -> (Vec3, Vec3) {
+        let mut out_min = Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+        let mut out_max = Vec3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for i in 0..8 {
+            let corner = Vec3::new(
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            );
+            let p = self.apply_point(corner);
+            out_min = Vec3::new(out_min.x.min(p.x), out_min.y.min(p.y), out_min.z.min(p.z));
+            out_max = Vec3::new(out_max.x.max(p.x), out_max.y.max(p.y), out_max.z.max(p.z));
+        }
+        (out_min, out_max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!(a.sub(b).len() < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn mat3_inverse_round_trips() {
+        let m = Transform::rotate(Vec3::new(0.3, 1.1, -0.7))
+            .then(&Transform::scale(Vec3::new(2.0, 0.5, 3.0)))
+            .linear;
+        let product = m.mul(&m.inverse());
+        let identity = Mat3::identity();
+        for (row, expected) in product.0.iter().zip(identity.0.iter()) {
+            for (value, e) in row.iter().zip(expected) {
+                assert!((value - e).abs() < 1e-5, "{:?}", product);
+            }
+        }
+    }
+
+    #[test]
+    fn singular_matrix_inverts_to_identity() {
+        let flat = Transform::scale(Vec3::new(1.0, 0.0, 1.0));
+        let p = Vec3::new(1.0, 2.0, 3.0);
+        assert_close(flat.inverse_vector(p), p);
+    }
+
+    #[test]
+    fn affine_inverse_undoes_apply() {
+        let t = Transform::translate(Vec3::new(4.0, -1.0, 2.5))
+            .then(&Transform::rotate(Vec3::new(0.0, 2.4, 0.2)))
+            .then(&Transform::scale(Vec3::new(0.35, 0.35, 0.35)));
+        let p = Vec3::new(0.5, -2.0, 1.25);
+        assert_close(t.inverse_point(t.apply_point(p)), p);
+        let v = Vec3::new(0.0, 1.0, -1.0);
+        let moved = t
+            .apply_point(v)
+            .sub(t.apply_point(Vec3::new(0.0, 0.0, 0.0)));
+        assert_close(t.inverse_vector(moved), v);
+    }
+}
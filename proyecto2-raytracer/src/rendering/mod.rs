@@ -1,7 +1,10 @@
 //! Núcleo del trazador y utilidades de iluminación.
 
 pub mod lighting;
+pub mod photon_map;
 pub mod raytracer;
 
 pub use lighting::{Skybox, Tex};
-pub use raytracer::{Assets, WorldKind, build_scene, render};
+pub use raytracer::{
+    Assets, SceneData, TileRect, WorldKind, build_scene, render, render_tile, update_caustics,
+};
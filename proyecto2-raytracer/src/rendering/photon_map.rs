@@ -0,0 +1,269 @@
+//! Mapa de fotones para cáusticas: los fotones salen de la luz, atraviesan los bloques
+//! transparentes (agua, hielo, portal) y se guardan en un kd-tree al tocar una superficie difusa.
+
+use std::f32::consts::PI;
+
+use super::lighting::{reflect, refract};
+use super::raytracer::{closest_hit, exit_origin};
+use crate::geometry::Group;
+use crate::math::Vec3;
+use crate::ray::Ray;
+use crate::scene::Intersectable;
+
+/// Intensidad de la luz puntual usada para escalar la energía de los fotones. Con la luz a
+/// unas 3 unidades de la isla, la irradiancia cáustica queda en el mismo rango que el Phong directo.
+const LIGHT_INTENSITY: f32 = 9.0;
+/// Radio (en unidades de bloque) dentro del cual se recogen fotones al sombrear.
+const GATHER_RADIUS: f32 = 0.1;
+/// Transparencia a partir de la cual un material desvía fotones en lugar de absorberlos.
+const CAUSTIC_MIN_TRANSPARENCY: f32 = 0.5;
+/// Rebotes especulares máximos por fotón.
+const MAX_PHOTON_BOUNCES: usize = 8;
+
+/// Fotón almacenado: posición, dirección de llegada y energía (RGB).
+#[derive(Copy, Clone)]
+struct Photon {
+    pos: Vec3,
+    dir: Vec3,
+    power: Vec3,
+}
+
+/// Generador xorshift pequeño y determinista; todos los workers de la granja deben
+/// obtener exactamente el mismo mapa.
+struct XorShift(u32);
+
+impl XorShift {
+    fn next_f32(&mut self) -> f32 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        (x >> 8) as f32 / (1u32 << 24) as f32
+    }
+}
+
+fn axis_value(v: Vec3, axis: u8) -> f32 {
+    match axis {
+        0 => v.x,
+        1 => v.y,
+        _ => v.z,
+    }
+}
+
+/// kd-tree balanceado de fotones cáusticos, construido para una posición de luz concreta.
+pub struct PhotonMap {
+    photons: Vec<Photon>,
+    axes: Vec<u8>,
+    light_pos: Vec3,
+    emitted: usize,
+}
+
+impl PhotonMap {
+    /// Emite `count` fotones desde `light_pos` hacia los bloques transparentes y guarda
+    /// los que terminan en una superficie difusa tras al menos una refracción.
+    pub fn build(world: &Group<'_>, light_pos: Vec3, count: usize) -> Self {
+        // Cada primitiva transparente recibe un cono de emisión que abarca su esfera
+        // envolvente; las de un prefab cuentan por separado, con su propio material.
+        let targets: Vec<(usize, Vec3, f32)> = world
+            .leaves()
+            .into_iter()
+            .filter(|leaf| leaf.material.transparency >= CAUSTIC_MIN_TRANSPARENCY)
+            .map(|leaf| {
+                let center = leaf.min.add(leaf.max).mul(0.5);
+                (leaf.leaf, center, leaf.max.sub(leaf.min).len() * 0.5)
+            })
+            .collect();
+
+        let mut photons = Vec::new();
+        let mut rng = XorShift(0x9E37_79B9);
+        let per_target = if targets.is_empty() {
+            0
+        } else {
+            count / targets.len()
+        };
+
+        for &(leaf, center, radius) in &targets {
+            let to_center = center.sub(light_pos);
+            let dist = to_center.len();
+            let axis = to_center.norm();
+            let cos_max = if dist <= radius {
+                -1.0
+            } else {
+                (1.0 - (radius / dist).powi(2)).sqrt()
+            };
+            // Energía de la fracción de esfera cubierta por el cono, repartida entre sus fotones.
+            let solid_fraction = (1.0 - cos_max) * 0.5;
+            let power = LIGHT_INTENSITY * 4.0 * PI * solid_fraction / per_target.max(1) as f32;
+
+            let helper = if axis.x.abs() > 0.9 {
+                Vec3::new(0.0, 1.0, 0.0)
+            } else {
+                Vec3::new(1.0, 0.0, 0.0)
+            };
+            let u = axis.cross(helper).norm();
+            let v = axis.cross(u);
+
+            for _ in 0..per_target {
+                let cos_t = 1.0 - rng.next_f32() * (1.0 - cos_max);
+                let sin_t = (1.0 - cos_t * cos_t).max(0.0).sqrt();
+                let phi = 2.0 * PI * rng.next_f32();
+                let dir = u
+                    .mul(sin_t * phi.cos())
+                    .add(v.mul(sin_t * phi.sin()))
+                    .add(axis.mul(cos_t))
+                    .norm();
+                let ray = Ray {
+                    orig: light_pos,
+                    dir,
+                };
+                // Sólo cuenta si el primer impacto es el objetivo del cono: así los conos
+                // que se solapan no duplican energía y los bloques opacos hacen sombra.
+                match closest_hit(&ray, world, None) {
+                    Some(hit) if hit.leaf == leaf => {}
+                    _ => continue,
+                }
+                trace_photon(ray, Vec3::new(power, power, power), world, &mut photons);
+            }
+        }
+
+        let mut axes = vec![0u8; photons.len()];
+        build_kd(&mut photons, &mut axes);
+        PhotonMap {
+            photons,
+            axes,
+            light_pos,
+            emitted: count,
+        }
+    }
+
+    /// Indica si el mapa se construyó para esta luz y esta cantidad de fotones.
+    pub fn matches(&self, light_pos: Vec3, count: usize) -> bool {
+        self.emitted == count && self.light_pos.sub(light_pos).len() < 1e-4
+    }
+
+    /// Irradiancia cáustica que llega a `point` desde el lado de la normal `n`.
+    pub fn irradiance(&self, point: Vec3, n: Vec3) -> Vec3 {
+        let r2 = GATHER_RADIUS * GATHER_RADIUS;
+        let mut sum = Vec3::new(0.0, 0.0, 0.0);
+        self.query(0, self.photons.len(), point, r2, &mut |ph| {
+            if n.dot(ph.dir) < 0.0 {
+                sum = sum.add(ph.power);
+            }
+        });
+        sum.mul(1.0 / (PI * r2))
+    }
+
+    fn query(&self, lo: usize, hi: usize, p: Vec3, r2: f32, f: &mut impl FnMut(&Photon)) {
+        if lo >= hi {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let ph = &self.photons[mid];
+        let axis = self.axes[mid];
+        let d = axis_value(p, axis) - axis_value(ph.pos, axis);
+        if ph.pos.sub(p).dot(ph.pos.sub(p)) <= r2 {
+            f(ph);
+        }
+        let (near, far) = if d < 0.0 {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+        self.query(near.0, near.1, p, r2, f);
+        if d * d <= r2 {
+            self.query(far.0, far.1, p, r2, f);
+        }
+    }
+}
+
+/// Sigue un fotón a través de superficies transparentes hasta que lo absorbe una difusa.
+fn trace_photon(mut ray: Ray, mut power: Vec3, world: &Group<'_>, out: &mut Vec<Photon>) {
+    let bias = 1e-3;
+    let mut refracted = false;
+    let mut skip = None;
+    for _ in 0..MAX_PHOTON_BOUNCES {
+        let Some(hit) = closest_hit(&ray, world, skip) else {
+            return;
+        };
+        let mat = hit.material;
+        if mat.transparency < CAUSTIC_MIN_TRANSPARENCY {
+            if refracted {
+                out.push(Photon {
+                    pos: hit.point,
+                    dir: ray.dir,
+                    power,
+                });
+            }
+            return;
+        }
+
+        let n = hit.normal.norm();
+        let entering = ray.dir.dot(n) < 0.0;
+        let (n_out, eta) = if entering {
+            (n, 1.0 / mat.ior)
+        } else {
+            (-n, mat.ior)
+        };
+        if entering {
+            // El tinte del material y la parte absorbida se aplican una vez por bloque.
+            power = power.hadamard(mat.albedo).mul(mat.transparency);
+        }
+        match refract(ray.dir, n_out, eta) {
+            Some(tdir) => {
+                let (orig, next_skip) = exit_origin(&hit, tdir, n_out, bias);
+                ray = Ray { orig, dir: tdir };
+                skip = next_skip;
+            }
+            None => {
+                // Reflexión total interna: el fotón sigue dentro del bloque.
+                let rdir = reflect(ray.dir, n_out);
+                ray = Ray {
+                    orig: hit.point.add(rdir.mul(bias)),
+                    dir: rdir,
+                };
+                skip = None;
+            }
+        }
+        refracted = true;
+    }
+}
+
+/// Ordena `photons` in situ como kd-tree implícito: la mediana de cada rango es el nodo.
+fn build_kd(photons: &mut [Photon], axes: &mut [u8]) {
+    if photons.is_empty() {
+        return;
+    }
+    let mut min = photons[0].pos;
+    let mut max = photons[0].pos;
+    for ph in photons.iter() {
+        min = Vec3::new(
+            min.x.min(ph.pos.x),
+            min.y.min(ph.pos.y),
+            min.z.min(ph.pos.z),
+        );
+        max = Vec3::new(
+            max.x.max(ph.pos.x),
+            max.y.max(ph.pos.y),
+            max.z.max(ph.pos.z),
+        );
+    }
+    let ext = max.sub(min);
+    let axis = if ext.x >= ext.y && ext.x >= ext.z {
+        0
+    } else if ext.y >= ext.z {
+        1
+    } else {
+        2
+    };
+
+    let mid = photons.len() / 2;
+    photons.select_nth_unstable_by(mid, |a, b| {
+        axis_value(a.pos, axis).total_cmp(&axis_value(b.pos, axis))
+    });
+    axes[mid] = axis;
+    let (left, right) = photons.split_at_mut(mid);
+    let (axes_left, axes_right) = axes.split_at_mut(mid);
+    build_kd(left, axes_left);
+    build_kd(&mut right[1..], &mut axes_right[1..]);
+}
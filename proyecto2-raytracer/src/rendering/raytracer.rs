@@ -1,39 +1,82 @@
 //! Construye la escena de bloques y ejecuta el trazador de rayos en CPU.
 
 use std::collections::HashSet;
+use std::sync::Arc;
 use std::thread;
 
 use super::lighting::{Skybox, Tex, reflect, refract, sample_skybox, sky, specular_phong, to_rgba};
+use super::photon_map::PhotonMap;
 use crate::camera::Camera;
-use crate::geometry::{SolidBlock, TexturedBlock};
-use crate::math::Vec3;
+use crate::geometry::{Group, SolidBlock, TexturedBlock};
+use crate::math::{Transform, Vec3};
 use crate::ray::Ray;
-use crate::scene::Intersectable;
+use crate::scene::{Intersectable, MaterialParams, SceneNode};
 
-type DynObject<'a> = Box<dyn Intersectable + Send + Sync + 'a>;
+pub(crate) type DynObject<'a> = Box<dyn Intersectable + Send + Sync + 'a>;
 
-/// Identifica qué diorama (Overworld o Nether) se va a renderizar.
+/// Islotes con árbol que rodean el diorama en el mundo `Grove`.
+const GROVE_SIZE: usize = 20;
+/// Distancia de la arboleda al centro de la isla.
+const GROVE_RADIUS: f32 = 8.0;
+
+/// Identifica qué diorama (Overworld, Nether o el Overworld con su arboleda) se va a renderizar.
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub enum WorldKind {
     Overworld,
     Nether,
+    /// Overworld rodeado de islotes flotantes instanciados; es más lento de trazar, por eso
+    /// no es el mundo por defecto.
+    Grove,
 }
 
 impl WorldKind {
+    /// Siguiente mundo del ciclo Overworld → Nether → arboleda.
     pub fn toggle(self) -> Self {
         match self {
             WorldKind::Overworld => WorldKind::Nether,
-            WorldKind::Nether => WorldKind::Overworld,
+            WorldKind::Nether => WorldKind::Grove,
+            WorldKind::Grove => WorldKind::Overworld,
+        }
+    }
+
+    /// Nombre usado en la línea de comandos y en el protocolo de la granja.
+    pub fn name(self) -> &'static str {
+        match self {
+            WorldKind::Overworld => "overworld",
+            WorldKind::Nether => "nether",
+            WorldKind::Grove => "grove",
+        }
+    }
+
+    /// Inverso de [`WorldKind::name`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "overworld" => Some(WorldKind::Overworld),
+            "nether" => Some(WorldKind::Nether),
+            "grove" => Some(WorldKind::Grove),
+            _ => None,
         }
     }
 }
 
+/// Rectángulo de píxeles (tile) dentro del cuadro completo.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TileRect {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
 /// Datos de intersección utilizados durante el recorrido de rayos.
-struct Hit<'a> {
-    t: f32,
-    point: Vec3,
-    normal: Vec3,
-    object: &'a dyn Intersectable,
+pub(crate) struct Hit {
+    pub point: Vec3,
+    pub normal: Vec3,
+    /// Material evaluado en el punto (ya resuelto dentro de grupos e instancias).
+    pub material: MaterialParams,
+    /// Primitiva impactada en la numeración de `SceneData::world` (ver
+    /// [`crate::scene::SurfaceHit::leaf`]).
+    pub leaf: usize,
 }
 
 /// Manejadores de texturas y skyboxes que permanecen válidos durante el render.
@@ -59,8 +102,25 @@ pub struct Assets<'a> {
 
 /// Geometría ya preparada para renderizar.
 pub struct SceneData<'a> {
-    pub objects: Vec<DynObject<'a>>,
+    /// Todos los objetos del mundo bajo una BVH, incluidas las instancias de prefabs.
+    pub world: Group<'a>,
     pub skybox: Option<Skybox<'a>>,
+    /// Mapa de fotones cáusticos; `None` si las cáusticas están desactivadas.
+    pub caustics: Option<PhotonMap>,
+}
+
+/// Reconstruye el mapa de fotones si cambió la luz o la cantidad pedida (`0` lo desactiva).
+pub fn update_caustics(scene: &mut SceneData<'_>, light_pos: Vec3, photons: usize) {
+    if photons == 0 {
+        scene.caustics = None;
+        return;
+    }
+    if let Some(map) = &scene.caustics
+        && map.matches(light_pos, photons)
+    {
+        return;
+    }
+    scene.caustics = Some(PhotonMap::build(&scene.world, light_pos, photons));
 }
 
 /// Inserta un cubo sólido o texturizado en la lista de objetos.
@@ -159,42 +219,138 @@ fn place_block<'a>(
     place_with_tag(objects, used, mat, x, y, z, 0);
 }
 
-/// Rutina de trazado recursivo con poca profundidad para reflejos/refracciones.
-fn trace<'a>(
-    ray: &Ray,
-    objects: &'a [DynObject<'a>],
-    light_pos: Vec3,
-    depth: i32,
-    skybox: Option<&Skybox<'a>>,
-) -> Vec3 {
-    // Hit más cercano
-    let mut closest: Option<Hit> = None;
-    for o in objects.iter() {
-        if let Some(t) = o.intersect(ray) {
-            if t > 0.0 && (closest.is_none() || t < closest.as_ref().unwrap().t) {
-                let p = ray.orig.add(ray.dir.mul(t));
-                let n = o.normal_at(p);
-                closest = Some(Hit {
-                    t,
-                    point: p,
-                    normal: n,
-                    object: o.as_ref(),
-                });
+/// Prefab alineado a la grilla: geometría compartida y las celdas locales que ocupa.
+struct Prefab<'a> {
+    group: Arc<Group<'a>>,
+    cells: Vec<(i32, i32, i32)>,
+}
+
+/// Coloca una instancia de `prefab` con el origen local en `(x, y, z)` si todas sus celdas
+/// están libres para `tag`, y las reserva en `used` igual que `place_with_tag`: los bloques
+/// con el mismo tag que se pongan después en ellas se omiten.
+fn place_prefab<'a>(
+    nodes: &mut Vec<SceneNode<'a>>,
+    used: &mut HashSet<(i32, i32, i32, u8)>,
+    prefab: &Prefab<'a>,
+    (x, y, z): (i32, i32, i32),
+    tag: u8,
+) {
+    let cell = |&(cx, cy, cz): &(i32, i32, i32)| (x + cx, y + cy, z + cz, tag);
+    if prefab.cells.iter().any(|c| used.contains(&cell(c))) {
+        return;
+    }
+    used.extend(prefab.cells.iter().map(cell));
+    let offset = Vec3::new(x as f32, y as f32, z as f32);
+    nodes.push(SceneNode::prefab(&prefab.group).translated(offset));
+}
+
+/// Prefab del árbol: tronco de tres bloques con la base en el origen local y copa de hojas.
+fn tree_prefab<'a>(wood: BlockMaterial<'a>, leaves: BlockMaterial<'a>) -> Prefab<'a> {
+    let mut blocks: Vec<DynObject<'a>> = Vec::new();
+    let mut cells = Vec::new();
+    for y in 0..=2 {
+        wood.place(&mut blocks, 0, y, 0);
+        cells.push((0, y, 0));
+    }
+    for y in 2..=3 {
+        for dx in -1..=1 {
+            for dz in -1..=1 {
+                if y == 2 && dx == 0 && dz == 0 {
+                    continue;
+                }
+                leaves.place(&mut blocks, dx, y, dz);
+                cells.push((dx, y, dz));
             }
         }
     }
+    Prefab {
+        group: Arc::new(Group::new(blocks)),
+        cells,
+    }
+}
+
+/// Islote flotante: un bloque de tierra con césped y el prefab del árbol encima.
+fn islet_prefab<'a>(
+    tree: &Arc<Group<'a>>,
+    ground: BlockMaterial<'a>,
+    cover: BlockMaterial<'a>,
+) -> Arc<Group<'a>> {
+    let mut blocks: Vec<DynObject<'a>> = Vec::new();
+    ground.place(&mut blocks, 0, 0, 0);
+    cover.place_cover(&mut blocks, 0, 0, 0, 0.18);
+    let mut parts: Vec<SceneNode<'a>> = blocks.into_iter().map(SceneNode::object).collect();
+    parts.push(SceneNode::prefab(tree).translated(Vec3::new(0.0, 1.0, 0.0)));
+
+    let mut objects = Vec::new();
+    SceneNode::group(parts).flatten_into(&Transform::identity(), &mut objects);
+    Arc::new(Group::new(objects))
+}
 
-    // Fondo
-    if closest.is_none() {
-        return if let Some(sb) = skybox {
+/// Prefab del portal: marco de obsidiana de 3×5 (más una fila bajo el suelo) con la
+/// esquina inferior izquierda en el origen local y el velo del portal dentro.
+fn portal_prefab<'a>(obsidian: BlockMaterial<'a>, portal: BlockMaterial<'a>) -> Prefab<'a> {
+    let (right, top) = (2, 4);
+    let mut blocks: Vec<DynObject<'a>> = Vec::new();
+    let mut used = HashSet::new();
+    for y in 0..=top {
+        place_with_tag(&mut blocks, &mut used, obsidian, 0, y, 0, 1);
+        place_with_tag(&mut blocks, &mut used, obsidian, right, y, 0, 1);
+    }
+    for x in 0..=right {
+        place_with_tag(&mut blocks, &mut used, obsidian, x, 0, 0, 1);
+        place_with_tag(&mut blocks, &mut used, obsidian, x, -1, 0, 1);
+        place_with_tag(&mut blocks, &mut used, obsidian, x, top, 0, 1);
+    }
+    for y in 1..top {
+        for x in 1..right {
+            place_with_tag(&mut blocks, &mut used, portal, x, y, 0, 1);
+        }
+    }
+    Prefab {
+        group: Arc::new(Group::new(blocks)),
+        cells: used.into_iter().map(|(x, y, z, _)| (x, y, z)).collect(),
+    }
+}
+
+/// Primitiva a ignorar, como `leaf` en la numeración de `SceneData::world`.
+pub(crate) type Skip = Option<usize>;
+
+/// Busca la intersección más cercana del rayo con el mundo, ignorando `skip`. El objeto del
+/// que sale el rayo se prueba entero salvo la primitiva de salida, así una cara vecina
+/// coincidente del mismo grupo sigue siendo visible.
+pub(crate) fn closest_hit(ray: &Ray, world: &Group<'_>, skip: Skip) -> Option<Hit> {
+    let surface = world.hit(ray, skip)?;
+    Some(Hit {
+        point: ray.orig.add(ray.dir.mul(surface.t)),
+        normal: surface.normal,
+        material: surface.material,
+        leaf: surface.leaf,
+    })
+}
+
+/// Origen del rayo refractado y primitiva a ignorar. Al entrar, el rayo nace dentro del bloque.
+/// Al salir, nace justo antes de la cara y omite el bloque: así encuentra una cara vecina
+/// coincidente (p. ej. la piedra bajo el agua) en lugar de arrancar dentro de ella.
+pub(crate) fn exit_origin(hit: &Hit, dir: Vec3, n_out: Vec3, bias: f32) -> (Vec3, Skip) {
+    if n_out.dot(hit.normal) < 0.0 {
+        (hit.point.sub(dir.mul(bias)), Some(hit.leaf))
+    } else {
+        (hit.point.add(dir.mul(bias)), None)
+    }
+}
+
+/// Rutina de trazado recursivo con poca profundidad para reflejos/refracciones.
+/// `skip` excluye la primitiva de la que el rayo acaba de salir.
+fn trace(ray: &Ray, scene: &SceneData<'_>, light_pos: Vec3, depth: i32, skip: Skip) -> Vec3 {
+    // Hit más cercano; si no hay, fondo.
+    let Some(hit) = closest_hit(ray, &scene.world, skip) else {
+        return if let Some(sb) = scene.skybox.as_ref() {
             sample_skybox(ray.dir, sb)
         } else {
             sky(ray.dir)
         };
-    }
-
-    let hit = closest.unwrap();
-    let mat = hit.object.material_at(hit.point);
+    };
+    let mat = hit.material;
 
     // Cálculo de sombra simple (shadow ray).
     let bias = 1e-3;
@@ -205,15 +361,10 @@ fn trace<'a>(
         orig: shadow_origin,
         dir: ldir,
     };
-    let mut in_shadow = false;
-    for o in objects.iter() {
-        if let Some(t) = o.intersect(&sray) {
-            if t < light_distance {
-                in_shadow = true;
-                break;
-            }
-        }
-    }
+    let in_shadow = scene
+        .world
+        .intersect(&sray)
+        .is_some_and(|t| t < light_distance);
 
     // Iluminación local (Phong).
     let ambient = 0.1;
@@ -231,6 +382,11 @@ fn trace<'a>(
         local = local.add(Vec3::new(spec, spec, spec));
     }
 
+    // Cáusticas: luz que llegó a este punto a través de bloques transparentes.
+    if let Some(map) = &scene.caustics {
+        local = local.add(mat.albedo.hadamard(map.irradiance(hit.point, n)));
+    }
+
     // Componentes emisivas.
     local = local.add(mat.emissive);
 
@@ -251,12 +407,12 @@ fn trace<'a>(
             eta = mat.ior;
         }
         if let Some(tdir) = refract(ray.dir, n_out, eta) {
-            let ro = hit.point.add(tdir.mul(bias));
+            let (ro, exit_skip) = exit_origin(&hit, tdir, n_out, bias);
             let rr = Ray {
                 orig: ro,
                 dir: tdir,
             };
-            let refr_col = trace(&rr, objects, light_pos, depth - 1, skybox);
+            let refr_col = trace(&rr, scene, light_pos, depth - 1, exit_skip);
             accum = accum.add(refr_col.mul(mat.transparency));
             weight -= mat.transparency;
         }
@@ -270,7 +426,7 @@ fn trace<'a>(
             orig: ro,
             dir: rdir,
         };
-        let refl_col = trace(&rr, objects, light_pos, depth - 1, skybox);
+        let refl_col = trace(&rr, scene, light_pos, depth - 1, None);
         accum = accum.add(refl_col.mul(mat.reflectivity));
         weight -= mat.reflectivity;
     }
@@ -423,13 +579,22 @@ pub fn build_scene<'a>(assets: &Assets<'a>, world: WorldKind) -> SceneData<'a> {
         emissive: Vec3::new(1.5, 0.3, 1.8),
     };
 
+    // Estructuras repetibles: se construyen una vez en espacio local y se colocan con instancias.
+    let portal = portal_prefab(obsidian_mat, portal_mat);
+    let mut nodes: Vec<SceneNode<'a>> = Vec::new();
+
     let mut used: HashSet<(i32, i32, i32, u8)> = HashSet::new();
     let center_x: i32 = 3;
     let center_z: i32 = 2;
     let adjust = |x: i32, z: i32| -> (i32, i32) { (x - center_x, z - center_z) };
 
+    // El portal va primero en los dos mundos: reserva sus celdas y los bloques sueltos que
+    // caerían encima (como el borde de obsidiana del Nether a nivel del suelo) se omiten.
+    let (sx_portal, sz_portal) = adjust(4, 2);
+    place_prefab(&mut nodes, &mut used, &portal, (sx_portal, 0, sz_portal), 1);
+
     match world {
-        WorldKind::Overworld => {
+        WorldKind::Overworld | WorldKind::Grove => {
             let max_x = 6;
             let max_z = 5;
 
@@ -528,20 +693,28 @@ pub fn build_scene<'a>(assets: &Assets<'a>, world: WorldKind) -> SceneData<'a> {
                 place_with_tag(&mut objects, &mut used, stone_mat, sx, -1, sz, 1);
             }
 
-            for y in 1..=3 {
-                let (sx, sz) = adjust(2, 2);
-                place_with_tag(&mut objects, &mut used, wood_mat, sx, y, sz, 1);
-            }
-            for y in 3..=4 {
-                for dx in -1..=1 {
-                    for dz in -1..=1 {
-                        if y == 3 && dx == 0 && dz == 0 {
-                            continue;
-                        }
-                        let (sx, sz) = adjust(2 + dx, 2 + dz);
-                        place_with_tag(&mut objects, &mut used, leaves_mat, sx, y, sz, 1);
-                    }
-                }
+            let tree = tree_prefab(wood_mat, leaves_mat);
+            let (sx_tree, sz_tree) = adjust(2, 2);
+            place_prefab(&mut nodes, &mut used, &tree, (sx_tree, 1, sz_tree), 1);
+
+            // Arboleda de islotes flotantes: el mismo prefab instanciado con giro y escala propios.
+            if world == WorldKind::Grove {
+                let islet = islet_prefab(&tree.group, dirt_mat, grass_cover_mat);
+                let grove = (0..GROVE_SIZE)
+                    .map(|i| {
+                        let angle = i as f32 / GROVE_SIZE as f32 * std::f32::consts::TAU;
+                        let size = 0.35 + 0.1 * (i % 3) as f32;
+                        SceneNode::prefab(&islet)
+                            .scaled(Vec3::new(size, size, size))
+                            .rotated(Vec3::new(0.0, i as f32 * 2.4, 0.0))
+                            .translated(Vec3::new(
+                                angle.cos() * GROVE_RADIUS,
+                                (angle * 3.0).sin() * 0.4,
+                                angle.sin() * GROVE_RADIUS,
+                            ))
+                    })
+                    .collect();
+                nodes.push(SceneNode::group(grove).translated(Vec3::new(0.0, -1.0, 0.5)));
             }
 
             let (sx_ped1, sz_ped1) = adjust(2, 1);
@@ -552,29 +725,6 @@ pub fn build_scene<'a>(assets: &Assets<'a>, world: WorldKind) -> SceneData<'a> {
             place_with_tag(&mut objects, &mut used, iron_mat, sx_ped2, 1, sz_ped2, 1);
             let (sx_chest, sz_chest) = adjust(chest_tile.0, chest_tile.1);
             place_with_tag(&mut objects, &mut used, chest_mat, sx_chest, 0, sz_chest, 1);
-
-            let portal_left = 4;
-            let portal_right = 6;
-            let portal_z = 2;
-            let portal_top = 4;
-            for y in 0..=portal_top {
-                let (sx_l, sz) = adjust(portal_left, portal_z);
-                let (sx_r, sz_r) = adjust(portal_right, portal_z);
-                place_with_tag(&mut objects, &mut used, obsidian_mat, sx_l, y, sz, 1);
-                place_with_tag(&mut objects, &mut used, obsidian_mat, sx_r, y, sz_r, 1);
-            }
-            for x in portal_left..=portal_right {
-                let (sx, sz) = adjust(x, portal_z);
-                place_with_tag(&mut objects, &mut used, obsidian_mat, sx, 0, sz, 1);
-                place_with_tag(&mut objects, &mut used, obsidian_mat, sx, -1, sz, 1);
-                place_with_tag(&mut objects, &mut used, obsidian_mat, sx, portal_top, sz, 1);
-            }
-            for y in 1..portal_top {
-                for x in (portal_left + 1)..portal_right {
-                    let (sx, sz) = adjust(x, portal_z);
-                    place_with_tag(&mut objects, &mut used, portal_mat, sx, y, sz, 1);
-                }
-            }
         }
         WorldKind::Nether => {
             let max_x = 6;
@@ -620,29 +770,6 @@ pub fn build_scene<'a>(assets: &Assets<'a>, world: WorldKind) -> SceneData<'a> {
             let (sx_iron, sz_iron) = adjust(3, 1);
             place_with_tag(&mut objects, &mut used, iron_mat, sx_iron, 1, sz_iron, 1);
 
-            let portal_left = 4;
-            let portal_right = 6;
-            let portal_z = 2;
-            let portal_top = 4;
-            for y in 0..=portal_top {
-                let (sx_l, sz) = adjust(portal_left, portal_z);
-                let (sx_r, sz_r) = adjust(portal_right, portal_z);
-                place_with_tag(&mut objects, &mut used, obsidian_mat, sx_l, y, sz, 1);
-                place_with_tag(&mut objects, &mut used, obsidian_mat, sx_r, y, sz_r, 1);
-            }
-            for x in portal_left..=portal_right {
-                let (sx, sz) = adjust(x, portal_z);
-                place_with_tag(&mut objects, &mut used, obsidian_mat, sx, 0, sz, 1);
-                place_with_tag(&mut objects, &mut used, obsidian_mat, sx, -1, sz, 1);
-                place_with_tag(&mut objects, &mut used, obsidian_mat, sx, portal_top, sz, 1);
-            }
-            for y in 1..portal_top {
-                for x in (portal_left + 1)..portal_right {
-                    let (sx, sz) = adjust(x, portal_z);
-                    place_with_tag(&mut objects, &mut used, portal_mat, sx, y, sz, 1);
-                }
-            }
-
             let (sx_lava1, sz_lava1) = adjust(2, 2);
             place_with_tag(&mut objects, &mut used, lava_mat, sx_lava1, -1, sz_lava1, 1);
             let (sx_lava2, sz_lava2) = adjust(3, 3);
@@ -650,12 +777,20 @@ pub fn build_scene<'a>(assets: &Assets<'a>, world: WorldKind) -> SceneData<'a> {
         }
     }
 
+    for node in nodes {
+        node.flatten_into(&Transform::identity(), &mut objects);
+    }
+
     let skybox = match world {
-        WorldKind::Overworld => assets.skybox_overworld,
+        WorldKind::Overworld | WorldKind::Grove => assets.skybox_overworld,
         WorldKind::Nether => assets.skybox_nether,
     };
 
-    SceneData { objects, skybox }
+    SceneData {
+        world: Group::new(objects),
+        skybox,
+        caustics: None,
+    }
 }
 
 /// Traza la escena resultante y escribe el color final (RGBA) dentro de `frame`.
@@ -678,8 +813,6 @@ pub fn render<'a>(
         .min(height.max(1));
     let rows_per_chunk = (height + threads - 1) / threads;
     let pixels_per_row = width * 4;
-    let objects_ref: &[DynObject<'a>] = &scene.objects;
-    let skybox = scene.skybox.as_ref();
 
     thread::scope(|scope| {
         let mut start_row = 0usize;
@@ -702,7 +835,7 @@ pub fn render<'a>(
                     for x in 0..width {
                         let u = (x as f32 + 0.5) / w as f32;
                         let ray = cam_ref.make_ray(u, v, aspect);
-                        let color = trace(&ray, objects_ref, light_pos, max_depth, skybox);
+                        let color = trace(&ray, scene, light_pos, max_depth, None);
                         let idx = x * 4;
                         row[idx..idx + 4].copy_from_slice(&to_rgba(color));
                     }
@@ -712,3 +845,62 @@ pub fn render<'a>(
         }
     });
 }
+
+/// Traza únicamente `rect` dentro de un cuadro de `w`×`h` y devuelve sus píxeles RGBA por filas.
+pub fn render_tile<'a>(
+    rect: TileRect,
+    w: i32,
+    h: i32,
+    cam: &Camera,
+    light_pos: Vec3,
+    scene: &SceneData<'a>,
+    max_depth: i32,
+) -> Vec<u8> {
+    let aspect = w as f32 / h as f32;
+    let mut out = Vec::with_capacity((rect.w * rect.h * 4) as usize);
+    for y in rect.y..rect.y + rect.h {
+        let v = (y as f32 + 0.5) / h as f32;
+        for x in rect.x..rect.x + rect.w {
+            let u = (x as f32 + 0.5) / w as f32;
+            let ray = cam.make_ray(u, v, aspect);
+            let color = trace(&ray, scene, light_pos, max_depth, None);
+            out.extend_from_slice(&to_rgba(color));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_cell_prefab() -> Prefab<'static> {
+        Prefab {
+            group: Arc::new(Group::new(Vec::new())),
+            cells: vec![(0, 0, 0), (1, 0, 0)],
+        }
+    }
+
+    #[test]
+    fn prefab_needs_all_its_cells_free() {
+        let prefab = two_cell_prefab();
+        let mut nodes = Vec::new();
+        let mut used = HashSet::from([(1, 0, 0, 1)]);
+
+        place_prefab(&mut nodes, &mut used, &prefab, (0, 0, 0), 1);
+        assert!(nodes.is_empty());
+        assert!(
+            !used.contains(&(0, 0, 0, 1)),
+            "no reserva celdas si no se coloca"
+        );
+
+        // Otro tag no choca con la celda ocupada.
+        place_prefab(&mut nodes, &mut used, &prefab, (0, 0, 0), 0);
+        place_prefab(&mut nodes, &mut used, &prefab, (5, 0, 0), 1);
+        assert_eq!(nodes.len(), 2);
+        assert!(used.contains(&(5, 0, 0, 1)) && used.contains(&(6, 0, 0, 1)));
+
+        place_prefab(&mut nodes, &mut used, &prefab, (4, 0, 0), 1);
+        assert_eq!(nodes.len(), 2);
+    }
+}
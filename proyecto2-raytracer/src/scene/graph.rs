@@ -0,0 +1,83 @@
+//! Grafo de escena: nodos con transformación local, subárboles y referencias a prefabs.
+
+use std::sync::Arc;
+
+use crate::geometry::{Group, Instance};
+use crate::math::{Transform, Vec3};
+use crate::scene::Intersectable;
+
+type Object<'a> = Box<dyn Intersectable + Send + Sync + 'a>;
+
+/// Contenido de un nodo del grafo.
+pub enum NodeContent<'a> {
+    /// Geometría propia del nodo, en su espacio local.
+    Object(Object<'a>),
+    /// Prefab compartido: sus bloques y su BVH existen una sola vez.
+    Prefab(Arc<Group<'a>>),
+    /// Subárbol; las transformaciones se acumulan de padre a hijo.
+    Children(Vec<SceneNode<'a>>),
+}
+
+/// Nodo del grafo de escena con su transformación respecto al padre.
+pub struct SceneNode<'a> {
+    pub transform: Transform,
+    pub content: NodeContent<'a>,
+}
+
+impl<'a> SceneNode<'a> {
+    pub fn object(object: Object<'a>) -> Self {
+        Self {
+            transform: Transform::identity(),
+            content: NodeContent::Object(object),
+        }
+    }
+
+    pub fn prefab(prefab: &Arc<Group<'a>>) -> Self {
+        Self {
+            transform: Transform::identity(),
+            content: NodeContent::Prefab(Arc::clone(prefab)),
+        }
+    }
+
+    pub fn group(children: Vec<SceneNode<'a>>) -> Self {
+        Self {
+            transform: Transform::identity(),
+            content: NodeContent::Children(children),
+        }
+    }
+
+    /// Traslada el nodo (después de su transformación actual).
+    pub fn translated(mut self, offset: Vec3) -> Self {
+        self.transform = Transform::translate(offset).then(&self.transform);
+        self
+    }
+
+    /// Rota el nodo en radianes (después de su transformación actual).
+    pub fn rotated(mut self, angles: Vec3) -> Self {
+        self.transform = Transform::rotate(angles).then(&self.transform);
+        self
+    }
+
+    /// Escala el nodo (después de su transformación actual).
+    pub fn scaled(mut self, factors: Vec3) -> Self {
+        self.transform = Transform::scale(factors).then(&self.transform);
+        self
+    }
+
+    /// Aplana el subárbol en objetos de espacio mundo que el trazador recorre directamente.
+    /// Cada prefab u objeto transformado se convierte en una [`Instance`].
+    pub fn flatten_into(self, parent: &Transform, out: &mut Vec<Object<'a>>) {
+        let world = parent.then(&self.transform);
+        match self.content {
+            NodeContent::Object(object) => {
+                out.push(Box::new(Instance::new(Arc::from(object), world)))
+            }
+            NodeContent::Prefab(prefab) => out.push(Box::new(Instance::new(prefab, world))),
+            NodeContent::Children(children) => {
+                for child in children {
+                    child.flatten_into(&world, out);
+                }
+            }
+        }
+    }
+}
@@ -15,11 +15,37 @@ pub struct MaterialParams {
     pub emissive: Vec3,         // luz propia
 }
 
+/// Resultado completo de una intersección: distancia, normal y material en el punto.
+#[derive(Copy, Clone)]
+pub struct SurfaceHit {
+    pub t: f32,
+    pub normal: Vec3,
+    pub material: MaterialParams,
+    /// Índice de la primitiva impactada dentro del objeto, entre `0` y `leaf_count()`;
+    /// cada grupo numera las de sus hijos al construirse.
+    pub leaf: usize,
+}
+
+/// Primitiva con caja envolvente dentro de un objeto: su `leaf`, su caja en el espacio
+/// del objeto y el material en su centro.
+#[derive(Copy, Clone)]
+pub struct LeafBounds {
+    pub leaf: usize,
+    pub min: Vec3,
+    pub max: Vec3,
+    pub material: MaterialParams,
+}
+
 /// Interfaz común para cualquier objeto intersectable por un rayo.
 pub trait Intersectable: Send + Sync {
     fn intersect(&self, ray: &Ray) -> Option<f32>;
     fn normal_at(&self, point: Vec3) -> Vec3;
 
+    /// Caja envolvente `(min, max)`, si el objeto es finito.
+    fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        None
+    }
+
     // Compatibilidad con tu versión previa
     fn albedo(&self) -> Vec3;
     fn albedo_at(&self, _point: Vec3) -> Vec3 {
@@ -38,4 +64,40 @@ pub trait Intersectable: Send + Sync {
             emissive: Vec3::new(0.0, 0.0, 0.0),
         }
     }
+
+    /// Cantidad de primitivas; una primitiva suelta es la `leaf` 0.
+    fn leaf_count(&self) -> usize {
+        1
+    }
+
+    /// Primitivas con caja envolvente, cada una por separado aunque estén dentro de un
+    /// grupo; el mapa de fotones apunta un cono a cada una.
+    fn leaves(&self) -> Vec<LeafBounds> {
+        let Some((min, max)) = self.bounds() else {
+            return Vec::new();
+        };
+        vec![LeafBounds {
+            leaf: 0,
+            min,
+            max,
+            material: self.material_at(min.add(max).mul(0.5)),
+        }]
+    }
+
+    /// Intersección con normal y material, ignorando la primitiva `exclude` (un `leaf` de
+    /// un impacto anterior). Los contenedores (grupos, instancias) la sobreescriben para
+    /// delegar en el hijo impactado y transformar el resultado.
+    fn hit(&self, ray: &Ray, exclude: Option<usize>) -> Option<SurfaceHit> {
+        if exclude == Some(0) {
+            return None;
+        }
+        let t = self.intersect(ray)?;
+        let p = ray.orig.add(ray.dir.mul(t));
+        Some(SurfaceHit {
+            t,
+            normal: self.normal_at(p),
+            material: self.material_at(p),
+            leaf: 0,
+        })
+    }
 }
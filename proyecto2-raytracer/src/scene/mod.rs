@@ -1,5 +1,7 @@
-//! Estructuras de escena: materiales e interfaz para objetos intersectables.
+//! Estructuras de escena: materiales, interfaz para objetos intersectables y grafo de escena.
 
+pub mod graph;
 pub mod material;
 
-pub use material::{Intersectable, MaterialParams};
+pub use graph::SceneNode;
+pub use material::{Intersectable, LeafBounds, MaterialParams, SurfaceHit};